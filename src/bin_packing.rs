@@ -0,0 +1,239 @@
+use crate::{
+    circle::Circle,
+    packing::{find_answer, is_valid_pack},
+    point::Point,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    utils::FloatType,
+};
+
+#[derive(Clone, Debug)]
+pub struct Bin {
+    /// Indices of packed circles, `circles[k]` is placement of `circle_indices[k]`.
+    pub circle_indices: Vec<usize>,
+    pub circles: Vec<Circle>,
+    /// Radius of the smallest main circle found for this bin's circles.
+    pub compact_radius: FloatType,
+}
+
+#[derive(Debug)]
+pub struct BinPackingResult {
+    pub bin_radius: FloatType,
+    pub bins: Vec<Bin>,
+    /// Circles that are bigger than a bin.
+    pub oversized: Vec<usize>,
+}
+
+impl BinPackingResult {
+    pub fn print(&self) {
+        println!("Bins used: {}", self.bins.len());
+        for (index, bin) in self.bins.iter().enumerate() {
+            println!(
+                "Bin {}: R = {}, circles = {:?}",
+                index + 1,
+                bin.compact_radius,
+                bin.circle_indices
+            );
+        }
+        if !self.oversized.is_empty() {
+            println!("Oversized: {:?}", self.oversized);
+        }
+    }
+
+    pub fn circles_by_bin(&self) -> Vec<Vec<Circle>> {
+        self.bins.iter().map(|bin| bin.circles.clone()).collect()
+    }
+}
+
+/// Restore which index each placed circle belongs to by matching radiuses
+/// (heuristic shuffles circles order).
+fn match_indices(
+    indices: &Vec<usize>,
+    radiuses: &Vec<FloatType>,
+    circles: &Vec<Circle>,
+) -> Vec<usize> {
+    let mut unmatched = indices.clone();
+
+    circles
+        .iter()
+        .map(|circle| {
+            let position = unmatched
+                .iter()
+                .position(|&index| radiuses[index] == circle.radius)
+                .expect("Circle with matching radius");
+            unmatched.remove(position)
+        })
+        .collect()
+}
+
+/// Circles placed one by one along a ring wide enough to avoid overlaps,
+/// used as start point for r-algorithm when heuristic fails (e.g. for two circles).
+fn ring_arrangement(radiuses: &Vec<FloatType>) -> (FloatType, Vec<Circle>) {
+    let max_radius = radiuses.iter().fold(0.0, |a: FloatType, &b| a.max(b));
+    let ring_radius = (radiuses.iter().sum::<FloatType>() * 1.1
+        / std::f64::consts::PI as FloatType)
+        .max(2.0 * max_radius);
+
+    let mut angle: FloatType = 0.0;
+    let circles = radiuses
+        .iter()
+        .map(|&radius| {
+            angle += 1.1 * radius / ring_radius;
+            let circle = Circle::new(
+                radius,
+                Point {
+                    x: ring_radius * angle.cos(),
+                    y: ring_radius * angle.sin(),
+                },
+            );
+            angle += 1.1 * radius / ring_radius;
+            circle
+        })
+        .collect();
+
+    (ring_radius + max_radius, circles)
+}
+
+/// Pack circles with given indices into one bin: heuristic first,
+/// then r-algorithm compaction if heuristic packing is too big.
+fn fit_into_bin(
+    bin_radius: FloatType,
+    radiuses: &Vec<FloatType>,
+    indices: &Vec<usize>,
+    number_of_iterations: u32,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Option<Bin> {
+    if let [index] = indices[..] {
+        return Some(Bin {
+            circle_indices: vec![index],
+            circles: vec![Circle::new(radiuses[index], Point::empty())],
+            compact_radius: radiuses[index],
+        });
+    }
+
+    let mut bin_radiuses: Vec<FloatType> = indices.iter().map(|&index| radiuses[index]).collect();
+    let (mut main_circle_radius, mut circles) =
+        find_answer(&mut bin_radiuses, number_of_iterations);
+
+    if circles.iter().any(|circle| circle.center.is_none()) {
+        (main_circle_radius, circles) = ring_arrangement(&bin_radiuses);
+    }
+
+    if main_circle_radius > bin_radius {
+        (main_circle_radius, circles) =
+            dichotomy_step_ralgo(main_circle_radius, &circles, reset_step, eps, ralgo_params);
+    }
+
+    if main_circle_radius <= bin_radius && is_valid_pack(bin_radius, &circles) {
+        Some(Bin {
+            circle_indices: match_indices(indices, radiuses, &circles),
+            circles,
+            compact_radius: main_circle_radius,
+        })
+    } else {
+        None
+    }
+}
+
+/// Pack circles into the smallest number of identical bins with first-fit decreasing strategy.
+/// Every bin is compacted with r-algorithm at the end.
+pub fn bin_packing(
+    radiuses: &Vec<FloatType>,
+    bin_radius: FloatType,
+    number_of_iterations: u32,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> BinPackingResult {
+    let mut order: Vec<usize> = (0..radiuses.len()).collect();
+    order.sort_by(|a, b| radiuses[*b].partial_cmp(&radiuses[*a]).unwrap());
+
+    let mut bins: Vec<Bin> = vec![];
+    let mut oversized: Vec<usize> = vec![];
+
+    for index in order {
+        if radiuses[index] > bin_radius {
+            oversized.push(index);
+            continue;
+        }
+
+        let fit = |indices: &Vec<usize>| {
+            fit_into_bin(
+                bin_radius,
+                radiuses,
+                indices,
+                number_of_iterations,
+                reset_step,
+                eps,
+                ralgo_params,
+            )
+        };
+
+        let placed = bins.iter_mut().any(|bin| {
+            let mut indices = bin.circle_indices.clone();
+            indices.push(index);
+
+            if let Some(new_bin) = fit(&indices) {
+                *bin = new_bin;
+                true
+            } else {
+                false
+            }
+        });
+
+        if !placed {
+            bins.push(fit(&vec![index]).expect("Single circle fits into bin"));
+        }
+    }
+
+    // compact every bin
+    for bin in bins.iter_mut() {
+        let (main_circle_radius, circles) = dichotomy_step_ralgo(
+            bin.compact_radius,
+            &bin.circles,
+            reset_step,
+            eps,
+            ralgo_params,
+        );
+
+        if main_circle_radius < bin.compact_radius && is_valid_pack(main_circle_radius, &circles) {
+            bin.compact_radius = main_circle_radius;
+            bin.circles = circles;
+        }
+    }
+
+    BinPackingResult {
+        bin_radius,
+        bins,
+        oversized,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_circle_is_assigned_once_to_valid_bin() {
+        let radiuses = vec![1.0, 2.0, 0.5, 3.0, 1.5, 4.0, 1.0, 2.5, 0.5, 2.0];
+        let ralgo_params = RalgoParams::default().with_max_iterations(200);
+
+        let result = bin_packing(&radiuses, 3.0, 10, false, 0.0, &ralgo_params);
+
+        let mut assigned = result.oversized.clone();
+        for bin in &result.bins {
+            assert_eq!(bin.circles.len(), bin.circle_indices.len());
+            assert!(bin.compact_radius <= result.bin_radius);
+            assert!(is_valid_pack(bin.compact_radius, &bin.circles));
+            for (&index, circle) in bin.circle_indices.iter().zip(&bin.circles) {
+                assert_eq!(circle.radius, radiuses[index]);
+            }
+            assigned.extend(&bin.circle_indices);
+        }
+        assigned.sort();
+
+        assert_eq!(assigned, (0..radiuses.len()).collect::<Vec<_>>());
+        assert_eq!(result.oversized, vec![5]);
+    }
+}
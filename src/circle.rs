@@ -1,41 +1,49 @@
-use crate::{point::Point, utils::FloatType};
+use crate::{obstacle::Obstacle, point::Point, real::Real, utils::FloatType};
 
 #[derive(Clone, Copy, Debug)]
-pub struct Circle {
-    pub center: Option<Point>,
-    pub radius: FloatType,
+pub struct Circle<T = FloatType> {
+    pub center: Option<Point<T>>,
+    pub radius: T,
 }
 
-impl Circle {
+impl<T: Real> Circle<T> {
     pub fn empty() -> Self {
         Circle {
             center: None,
-            radius: 0.0,
+            radius: T::zero(),
         }
     }
 
-    pub fn new(radius: FloatType, center: Point) -> Self {
+    pub fn new(radius: T, center: Point<T>) -> Self {
         Circle {
             center: Some(center),
             radius,
         }
     }
 
-    pub fn with_radius(radius: FloatType) -> Self {
+    pub fn with_radius(radius: T) -> Self {
         Circle {
             center: None,
             radius,
         }
     }
 
-    pub fn overlap(&self, other: &Circle) -> bool {
+    /// Same circle in another number type.
+    pub fn cast<U: Real>(&self) -> Circle<U> {
+        Circle {
+            center: self.center.as_ref().map(|center| center.cast()),
+            radius: U::from_f64(self.radius.to_f64()),
+        }
+    }
+
+    pub fn overlap(&self, other: &Circle<T>) -> bool {
         if let (Some(center_self), Some(center_other)) =
             (self.center.as_ref(), other.center.as_ref())
         {
-            let distance = ((center_self.x - center_other.x).powi(2)
-                + (center_self.y - center_other.y).powi(2))
+            let distance = ((center_self.x.clone() - center_other.x.clone()).square()
+                + (center_self.y.clone() - center_other.y.clone()).square())
             .sqrt();
-            let radius_sum = self.radius + other.radius;
+            let radius_sum = self.radius.clone() + other.radius.clone();
 
             return distance <= radius_sum;
         } else {
@@ -43,7 +51,7 @@ impl Circle {
         }
     }
 
-    pub fn is_overlap(&self, circles: &Vec<Circle>) -> bool {
+    pub fn is_overlap(&self, circles: &Vec<Circle<T>>) -> bool {
         for circle in circles {
             if self.overlap(circle) {
                 return true;
@@ -52,12 +60,24 @@ impl Circle {
         false
     }
 
-    pub fn is_inside_main_circle(&self, main_circle_radius: FloatType) -> bool {
+    pub fn is_inside_main_circle(&self, main_circle_radius: T) -> bool {
+        self.is_inside_container(&Point::empty(), main_circle_radius)
+    }
+
+    pub fn is_inside_container(&self, container_center: &Point<T>, main_circle_radius: T) -> bool {
         if let Some(center) = self.center.as_ref() {
-            let distance = (center.x.powi(2) + center.y.powi(2)).sqrt();
-            return distance <= (main_circle_radius - self.radius);
+            let distance = ((center.x.clone() - container_center.x.clone()).square()
+                + (center.y.clone() - container_center.y.clone()).square())
+            .sqrt();
+            return distance <= (main_circle_radius - self.radius.clone());
         } else {
             false
         }
     }
 }
+
+impl Circle {
+    pub fn is_overlap_obstacles(&self, obstacles: &[Obstacle]) -> bool {
+        obstacles.iter().any(|obstacle| obstacle.overlap(self))
+    }
+}
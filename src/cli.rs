@@ -0,0 +1,365 @@
+use std::{path::Path, str::FromStr};
+
+use crate::{
+    dxf::{write_dxf, DxfParams},
+    ellipse::EllipseContainer,
+    evaluate::{
+        bins_single_case_console::bins_single_case_console,
+        ellipses_single_case_console::ellipses_single_case_console,
+        ils_single_case_console::ils_single_case_console,
+        knapsack_single_case_console::knapsack_single_case_console,
+        memetic_single_case::memetic_single_case,
+        obstacles_single_case_console::obstacles_single_case_console,
+        packomania_batch::packomania_batch,
+        pipeline_single_case_console::pipeline_single_case_console,
+        refine_solution_console::refine_solution_console,
+        screening_single_case_console::screening_single_case_console,
+        solvers_single_case::solvers_single_case,
+        symmetric_single_case_console::symmetric_single_case_console,
+        trajectory_single_case::trajectory_single_case,
+    },
+    ils::IlsParams,
+    instance::{ContainerType, Instance},
+    knapsack::KnapsackObjective,
+    memetic::MemeticParams,
+    obstacle::Obstacle,
+    packomania::find_best_random,
+    pipeline::Pipeline,
+    plot::{draw_plot_with_params, PlotParams},
+    point::Point,
+    ralgo::{ralgo_params::RalgoParams, step_strategy::StepStrategy, symmetry::Symmetry},
+    serve,
+    solution::Solution,
+    solver::{
+        augmented_lagrangian::AugmentedLagrangianSolver,
+        projected_gradient::ProjectedGradientSolver, ralgo_solver::RalgoSolver,
+        smoothed_penalty::SmoothedPenaltySolver, Solver,
+    },
+    utils::FloatType,
+    workspace::{load_workspace, workspace},
+};
+
+pub const USAGE: &str = "\
+Usage: studying [<command> <arguments>]
+
+Without a command the default experiments of main are run. <instance> is a test number
+of the workspace input or an instance file (input or packomania .txt, .json, .toml),
+<test> of screening and random is the number of circles with radii 1..N.
+
+Commands:
+  help                                    this message
+  serve [address]                         viewer of solutions, 127.0.0.1:8080 by default
+  solvers <instance> [launches]           compare local solvers on random starts
+  screening <test> [launches] [candidates]
+                                          cheap screening of random starts, then ralgo
+  pipeline <instance> [description]       run pipeline, e.g. \"random; ralgo; best\"
+  memetic <instance> [seed]               population search with crossover
+  ils <instance> [seed]                   iterated local search with hole jumps
+  knapsack <instance> <R> [objective]     circles that fit into main circle of radius R,
+                                          objective is count, area or value (radius)
+  bins <instance> <R>                     assign circles to the fewest bins of radius R
+  ellipses <aspect ratio> <axes...>       pack ellipses a,b (or circles r) into ellipse
+                                          with semi-axes aspect ratio * R and R
+  symmetric <instance> <symmetry> [launches]
+                                          solve in symmetric subspace, e.g. C5 or D3:90
+  obstacles <instance> [obstacle...]      pack around obstacles circle:x,y,r,
+                                          rectangle:x1,y1,x2,y2 or polygon:x1,y1,...
+  random <test> [seed]                    best of random starts, compared with packomania
+  refine <solution> [description]         warm start pipeline from solution file
+  trajectory <instance> <path> [seed] [plot options]
+                                          animation of ralgo iterations (.gif or frames)
+  batch [directory] [seconds] [results]   compare pipeline with packomania records,
+                                          seconds per instance is a soft limit, results
+                                          is packomania-batch of the workspace results
+  export <solution> <path> [plot options] draw solution as .png, .svg, .pdf, .gif, .tex
+                                          or .dxf
+
+Plot options:
+  --labels=index|radius                   label circles by 1-based index or radius
+  --color-by-radius                       fill circles with colours from smallest to
+                                          largest radius (not in .dxf)";
+
+fn argument<T: FromStr>(args: &[String], index: usize, name: &str) -> Result<T, String> {
+    let value = args
+        .get(index)
+        .ok_or(format!("Missing argument <{name}>\n\n{USAGE}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("Invalid <{name}> '{value}'"))
+}
+
+fn optional_argument<T: FromStr>(
+    args: &[String],
+    index: usize,
+    name: &str,
+    default: T,
+) -> Result<T, String> {
+    match args.get(index) {
+        Some(_) => argument(args, index, name),
+        None => Ok(default),
+    }
+}
+
+/// Test number of the workspace input or path of instance file, see `Instance::read`.
+/// Commands pack circles into a circle, so an elliptic container is rejected.
+fn instance_argument(args: &[String], index: usize) -> Result<Instance, String> {
+    let value = args
+        .get(index)
+        .ok_or(format!("Missing argument <instance>\n\n{USAGE}"))?;
+    let instance = match value.parse::<u32>() {
+        Ok(test_number) => Instance::read_input(test_number),
+        Err(_) => Instance::read(value),
+    }
+    .map_err(|error| error.to_string())?;
+
+    if instance.container != ContainerType::Circle {
+        return Err(format!(
+            "Instance {} has {:?} container, only circle is supported",
+            instance.name, instance.container
+        ));
+    }
+    Ok(instance)
+}
+
+fn pipeline_argument(args: &[String], index: usize, default: Pipeline) -> Result<Pipeline, String> {
+    match args.get(index) {
+        Some(description) => description.parse(),
+        None => Ok(default),
+    }
+}
+
+/// Plot options (see `USAGE`) may follow the arguments of a command. Returns the other
+/// arguments and `params` with the options.
+fn plot_options(args: &[String], params: PlotParams) -> Result<(Vec<String>, PlotParams), String> {
+    let mut params = params;
+    let mut arguments = Vec::new();
+
+    for arg in args {
+        if let Some(labels) = arg.strip_prefix("--labels=") {
+            params = params.with_labels(Some(labels.parse()?));
+        } else if arg == "--color-by-radius" {
+            params = params.with_color_by_radius(true);
+        } else if arg.starts_with("--") {
+            return Err(format!("Unknown option '{arg}'\n\n{USAGE}"));
+        } else {
+            arguments.push(arg.clone());
+        }
+    }
+
+    Ok((arguments, params))
+}
+
+/// Semi-axes `a,b` of an ellipse or radius `r` of a circle.
+fn semi_axes(value: &str) -> Result<(FloatType, FloatType), String> {
+    let axes = value
+        .split(',')
+        .map(|axis| axis.trim().parse::<FloatType>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| format!("Invalid semi-axes '{value}'"))?;
+
+    match axes[..] {
+        [r] if r > 0.0 => Ok((r, r)),
+        [a, b] if a > 0.0 && b > 0.0 => Ok((a.max(b), a.min(b))),
+        _ => Err(format!(
+            "Invalid semi-axes '{value}', expected two positive numbers a,b or radius"
+        )),
+    }
+}
+
+fn ralgo_params() -> RalgoParams {
+    RalgoParams::default()
+        .with_alpha(1.5)
+        .with_q1(1.0)
+        .with_max_iterations(100_000)
+        .with_step_strategy(StepStrategy::relative())
+}
+
+fn export(solution_path: &str, path: &str, params: &PlotParams) -> Result<(), String> {
+    let solution = Solution::read(solution_path).map_err(|error| error.to_string())?;
+
+    if Path::new(path)
+        .extension()
+        .is_some_and(|extension| extension == "dxf")
+    {
+        return write_dxf(
+            path,
+            solution.main_circle_radius,
+            &Point::empty(),
+            &solution.circles,
+            &DxfParams::default().with_labels(params.labels),
+        )
+        .map_err(|error| format!("{path}: {error}"));
+    }
+
+    draw_plot_with_params(
+        solution.main_circle_radius,
+        &Point::empty(),
+        &solution.circles,
+        &[],
+        &params.clone().with_path(path),
+    )
+}
+
+/// Run the command of `args` (without the program name). Returns `false` when there is no
+/// command, so the default experiments should be run. A malformed `workspace.toml` is an
+/// error of any command.
+pub fn run(args: &[String]) -> Result<bool, String> {
+    load_workspace().map_err(|error| error.to_string())?;
+
+    let Some(command) = args.first() else {
+        return Ok(false);
+    };
+
+    match command.as_str() {
+        "help" | "--help" | "-h" => println!("{USAGE}"),
+        "serve" => {
+            let address = args.get(1).map_or("127.0.0.1:8080", |a| a.as_str());
+            serve::serve(address, &workspace().solutions.to_string_lossy())
+                .map_err(|error| format!("Failed to serve viewer on {address}: {error}"))?;
+        }
+        "solvers" => {
+            let solvers: Vec<Box<dyn Solver>> = vec![
+                Box::new(RalgoSolver::new(false, 0.0, ralgo_params())),
+                Box::new(ProjectedGradientSolver::default()),
+                Box::new(AugmentedLagrangianSolver::default()),
+                Box::new(SmoothedPenaltySolver::default()),
+            ];
+            solvers_single_case(
+                &instance_argument(args, 1)?,
+                optional_argument(args, 2, "launches", 10)?,
+                &solvers,
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        "screening" => {
+            screening_single_case_console(
+                argument(args, 1, "test")?,
+                optional_argument(args, 2, "launches", 500)?,
+                optional_argument(args, 3, "candidates", 10)?,
+                false,
+                0.0,
+                &ralgo_params(),
+            );
+        }
+        "pipeline" => {
+            pipeline_single_case_console(
+                &instance_argument(args, 1)?,
+                &pipeline_argument(args, 2, Pipeline::default())?,
+            );
+        }
+        "memetic" => {
+            let params = MemeticParams::default()
+                .with_seed(optional_argument(args, 2, "seed", 0)?)
+                .with_ralgo_params(ralgo_params());
+            memetic_single_case(&instance_argument(args, 1)?, &params)
+                .map_err(|error| error.to_string())?;
+        }
+        "ils" => {
+            let params = IlsParams::default()
+                .with_seed(optional_argument(args, 2, "seed", 0)?)
+                .with_ralgo_params(ralgo_params());
+            ils_single_case_console(&instance_argument(args, 1)?, &params);
+        }
+        "knapsack" => {
+            knapsack_single_case_console(
+                &instance_argument(args, 1)?,
+                argument(args, 2, "R")?,
+                optional_argument(args, 3, "objective", KnapsackObjective::Count)?,
+                100,
+                &ralgo_params(),
+            )?;
+        }
+        "bins" => {
+            bins_single_case_console(
+                &instance_argument(args, 1)?,
+                argument(args, 2, "R")?,
+                10,
+                false,
+                0.0,
+                &ralgo_params(),
+            )?;
+        }
+        "ellipses" => {
+            let aspect_ratio: FloatType = argument(args, 1, "aspect ratio")?;
+            if aspect_ratio <= 0.0 {
+                return Err(format!("Invalid <aspect ratio> '{aspect_ratio}'"));
+            }
+            let container = EllipseContainer { aspect_ratio };
+            let semi_axes = args[2..]
+                .iter()
+                .map(|value| semi_axes(value))
+                .collect::<Result<Vec<_>, String>>()?;
+            if semi_axes.is_empty() {
+                return Err(format!("Missing argument <axes>\n\n{USAGE}"));
+            }
+            ellipses_single_case_console(&semi_axes, &container, 10, false, 0.0, &ralgo_params())?;
+        }
+        "symmetric" => {
+            symmetric_single_case_console(
+                &instance_argument(args, 1)?,
+                &argument::<Symmetry>(args, 2, "symmetry")?,
+                optional_argument(args, 3, "launches", 100)?,
+                false,
+                0.0,
+                &ralgo_params(),
+            )?;
+        }
+        "obstacles" => {
+            let instance = instance_argument(args, 1)?;
+            let obstacles = args[2..]
+                .iter()
+                .map(|obstacle| obstacle.parse())
+                .collect::<Result<Vec<Obstacle>, String>>()?;
+            obstacles_single_case_console(&instance, &obstacles, 100, false, 0.0, &ralgo_params())?;
+        }
+        "random" => find_best_random(
+            argument(args, 1, "test")?,
+            optional_argument(args, 2, "seed", 0)?,
+        )
+        .map_err(|error| error.to_string())?,
+        "refine" => {
+            refine_solution_console(
+                argument::<String>(args, 1, "solution")?,
+                &pipeline_argument(args, 2, Pipeline::refinement())?,
+            )
+            .map_err(|error| error.to_string())?;
+        }
+        "trajectory" => {
+            let (args, plot_params) = plot_options(args, PlotParams::default())?;
+            trajectory_single_case(
+                &instance_argument(&args, 1)?,
+                optional_argument(&args, 3, "seed", 0)?,
+                false,
+                0.0,
+                &ralgo_params(),
+                None,
+                &plot_params.with_path(argument::<String>(&args, 2, "path")?),
+                10,
+            )?;
+        }
+        "batch" => {
+            let directory = match args.get(1) {
+                Some(directory) => directory.into(),
+                None => workspace().packomania.clone(),
+            };
+            let time_budget: FloatType = optional_argument(args, 2, "seconds", 10.0)?;
+            let results = match args.get(3) {
+                Some(results) => results.into(),
+                None => workspace().results.join("packomania-batch"),
+            };
+            packomania_batch(&directory, &Pipeline::default(), time_budget, &results)
+                .map_err(|error| error.to_string())?;
+        }
+        "export" => {
+            let (args, plot_params) = plot_options(args, PlotParams::default())?;
+            export(
+                &argument::<String>(&args, 1, "solution")?,
+                &argument::<String>(&args, 2, "path")?,
+                &plot_params,
+            )?;
+        }
+        _ => return Err(format!("Unknown command '{command}'\n\n{USAGE}")),
+    }
+
+    Ok(true)
+}
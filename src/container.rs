@@ -0,0 +1,34 @@
+use crate::point::Point;
+
+/// Center of the main circle.
+#[derive(Clone, Copy, Debug)]
+pub enum ContainerCenter {
+    /// Center is fixed at the given point.
+    Fixed(Point),
+    /// Center is optimized together with circles, starting from the given point.
+    Free(Point),
+}
+
+impl ContainerCenter {
+    pub fn origin() -> Self {
+        ContainerCenter::Fixed(Point::empty())
+    }
+
+    pub fn point(&self) -> Point {
+        match self {
+            ContainerCenter::Fixed(point) | ContainerCenter::Free(point) => *point,
+        }
+    }
+
+    pub fn is_free(&self) -> bool {
+        matches!(self, ContainerCenter::Free(_))
+    }
+
+    /// Same kind of center placed at the new point.
+    pub fn with_point(&self, point: Point) -> Self {
+        match self {
+            ContainerCenter::Fixed(_) => ContainerCenter::Fixed(point),
+            ContainerCenter::Free(_) => ContainerCenter::Free(point),
+        }
+    }
+}
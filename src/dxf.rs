@@ -0,0 +1,352 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{
+    circle::Circle,
+    plot::{label_text, CircleLabel},
+    point::Point,
+    utils::FloatType,
+};
+
+pub const CONTAINER_LAYER: &str = "CONTAINER";
+pub const CIRCLES_LAYER: &str = "CIRCLES";
+pub const LABELS_LAYER: &str = "LABELS";
+
+/// Drawing units, written to `$INSUNITS` of the header. The variable appeared in R2000,
+/// readers of R12 files ignore it, so there units are a hint and have to be set on import.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DxfUnits {
+    Unitless,
+    Inches,
+    Millimeters,
+    Centimeters,
+    Meters,
+}
+
+impl DxfUnits {
+    pub fn code(&self) -> u32 {
+        match self {
+            DxfUnits::Unitless => 0,
+            DxfUnits::Inches => 1,
+            DxfUnits::Millimeters => 4,
+            DxfUnits::Centimeters => 5,
+            DxfUnits::Meters => 6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DxfParams {
+    pub units: DxfUnits,
+    /// Packing coordinates and radii are multiplied by it, e.g. the size of unit in `units`.
+    pub scale: FloatType,
+    pub labels: Option<CircleLabel>,
+    /// Height of label text as a part of the circle radius.
+    pub label_height: FloatType,
+}
+
+impl Default for DxfParams {
+    fn default() -> Self {
+        DxfParams {
+            units: DxfUnits::Millimeters,
+            scale: 1.0,
+            labels: None,
+            label_height: 0.5,
+        }
+    }
+}
+
+impl DxfParams {
+    pub fn with_units(&self, units: DxfUnits) -> Self {
+        DxfParams { units, ..*self }
+    }
+
+    pub fn with_scale(&self, scale: FloatType) -> Self {
+        DxfParams { scale, ..*self }
+    }
+
+    pub fn with_labels(&self, labels: Option<CircleLabel>) -> Self {
+        DxfParams { labels, ..*self }
+    }
+
+    pub fn with_label_height(&self, label_height: FloatType) -> Self {
+        DxfParams {
+            label_height,
+            ..*self
+        }
+    }
+}
+
+/// Group code and value, every one on its own line.
+fn pair(dxf: &mut String, code: u32, value: impl Display) {
+    *dxf += &format!("{code:>3}\n{value}\n");
+}
+
+fn circle_entity(dxf: &mut String, layer: &str, x: FloatType, y: FloatType, radius: FloatType) {
+    pair(dxf, 0, "CIRCLE");
+    pair(dxf, 8, layer);
+    pair(dxf, 10, x);
+    pair(dxf, 20, y);
+    pair(dxf, 30, 0.0);
+    pair(dxf, 40, radius);
+}
+
+/// ASCII DXF (AutoCAD R12 entities) of the packing in a main circle with given center:
+/// main circle on `CONTAINER` layer, circles on `CIRCLES` and optional labels centered in
+/// circles on `LABELS`. Numbers are written exactly, so they read back unchanged.
+///
+/// The file is R12 (`AC1009`), which every reader accepts, with `$INSUNITS` of R2000 added.
+/// A full R2000 file would need handles, classes and objects for nothing but the units.
+pub fn to_dxf(
+    main_circle_radius: FloatType,
+    main_circle_center: &Point,
+    circles: &Vec<Circle>,
+    params: &DxfParams,
+) -> String {
+    let mut dxf = String::new();
+
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "HEADER");
+    pair(&mut dxf, 9, "$ACADVER");
+    pair(&mut dxf, 1, "AC1009");
+    pair(&mut dxf, 9, "$INSUNITS");
+    pair(&mut dxf, 70, params.units.code());
+    pair(&mut dxf, 0, "ENDSEC");
+
+    // layer and its colour number: blue, green and white (black on light background)
+    let layers = [(CONTAINER_LAYER, 5), (CIRCLES_LAYER, 3), (LABELS_LAYER, 7)];
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "TABLES");
+    pair(&mut dxf, 0, "TABLE");
+    pair(&mut dxf, 2, "LAYER");
+    pair(&mut dxf, 70, layers.len());
+    for (name, color) in layers {
+        pair(&mut dxf, 0, "LAYER");
+        pair(&mut dxf, 2, name);
+        pair(&mut dxf, 70, 0);
+        pair(&mut dxf, 62, color);
+        pair(&mut dxf, 6, "CONTINUOUS");
+    }
+    pair(&mut dxf, 0, "ENDTAB");
+    pair(&mut dxf, 0, "ENDSEC");
+
+    pair(&mut dxf, 0, "SECTION");
+    pair(&mut dxf, 2, "ENTITIES");
+    circle_entity(
+        &mut dxf,
+        CONTAINER_LAYER,
+        main_circle_center.x * params.scale,
+        main_circle_center.y * params.scale,
+        main_circle_radius * params.scale,
+    );
+    for circle in circles {
+        let center = circle.center.expect("Valid center");
+        circle_entity(
+            &mut dxf,
+            CIRCLES_LAYER,
+            center.x * params.scale,
+            center.y * params.scale,
+            circle.radius * params.scale,
+        );
+    }
+
+    if let Some(labels) = params.labels {
+        for (i, circle) in circles.iter().enumerate() {
+            let center = circle.center.expect("Valid center");
+            let (x, y) = (center.x * params.scale, center.y * params.scale);
+            pair(&mut dxf, 0, "TEXT");
+            pair(&mut dxf, 8, LABELS_LAYER);
+            pair(&mut dxf, 10, x);
+            pair(&mut dxf, 20, y);
+            pair(&mut dxf, 30, 0.0);
+            pair(
+                &mut dxf,
+                40,
+                circle.radius * params.scale * params.label_height,
+            );
+            pair(&mut dxf, 1, label_text(labels, i, circle));
+            // centered horizontally and vertically at the alignment point
+            pair(&mut dxf, 72, 1);
+            pair(&mut dxf, 11, x);
+            pair(&mut dxf, 21, y);
+            pair(&mut dxf, 31, 0.0);
+            pair(&mut dxf, 73, 2);
+        }
+    }
+
+    pair(&mut dxf, 0, "ENDSEC");
+    pair(&mut dxf, 0, "EOF");
+
+    dxf
+}
+
+pub fn write_dxf(
+    path: impl AsRef<Path>,
+    main_circle_radius: FloatType,
+    main_circle_center: &Point,
+    circles: &Vec<Circle>,
+    params: &DxfParams,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(
+        path,
+        to_dxf(main_circle_radius, main_circle_center, circles, params),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Entity of DXF with its group codes and values.
+    struct Entity {
+        kind: String,
+        groups: Vec<(u32, String)>,
+    }
+
+    impl Entity {
+        fn get(&self, code: u32) -> &str {
+            &self.groups.iter().find(|(c, _)| *c == code).unwrap().1
+        }
+
+        fn number(&self, code: u32) -> FloatType {
+            self.get(code).parse().unwrap()
+        }
+    }
+
+    /// Header variables, layer names and entities of ASCII DXF.
+    fn read_dxf(dxf: &str) -> (Vec<(String, String)>, Vec<String>, Vec<Entity>) {
+        let lines: Vec<&str> = dxf.lines().collect();
+        assert_eq!(lines.len() % 2, 0, "group codes and values go in pairs");
+        let pairs: Vec<(u32, String)> = lines
+            .chunks(2)
+            .map(|pair| (pair[0].trim().parse().unwrap(), pair[1].to_string()))
+            .collect();
+        assert_eq!(pairs.last().unwrap(), &(0, "EOF".to_string()));
+
+        let (mut header, mut layers, mut entities) = (Vec::new(), Vec::new(), Vec::new());
+        let mut section = String::new();
+        let mut i = 0;
+        while i < pairs.len() {
+            let (code, value) = &pairs[i];
+            match (*code, value.as_str()) {
+                (0, "SECTION") => {
+                    section = pairs[i + 1].1.clone();
+                    i += 2;
+                    continue;
+                }
+                (0, "ENDSEC") => section.clear(),
+                (9, variable) if section == "HEADER" => {
+                    header.push((variable.to_string(), pairs[i + 1].1.clone()));
+                    i += 2;
+                    continue;
+                }
+                (0, "LAYER") if section == "TABLES" => layers.push(pairs[i + 1].1.clone()),
+                (0, kind) if section == "ENTITIES" => entities.push(Entity {
+                    kind: kind.to_string(),
+                    groups: Vec::new(),
+                }),
+                (code, value) if section == "ENTITIES" => entities
+                    .last_mut()
+                    .unwrap()
+                    .groups
+                    .push((code, value.to_string())),
+                _ => {}
+            }
+            i += 1;
+        }
+
+        (header, layers, entities)
+    }
+
+    fn packing() -> (FloatType, Vec<Circle>) {
+        (
+            2.1547005383792515,
+            vec![
+                Circle::new(
+                    1.0,
+                    Point {
+                        x: 0.0,
+                        y: 1.1547005383792515,
+                    },
+                ),
+                Circle::new(
+                    1.0,
+                    Point {
+                        x: -1.0,
+                        y: -0.5773502691896257,
+                    },
+                ),
+                Circle::new(
+                    1.0,
+                    Point {
+                        x: 1.0,
+                        y: -0.5773502691896257,
+                    },
+                ),
+            ],
+        )
+    }
+
+    #[test]
+    fn circles_and_container_read_back() {
+        let (main_circle_radius, circles) = packing();
+        let params = DxfParams::default()
+            .with_units(DxfUnits::Centimeters)
+            .with_scale(2.5);
+        let center = Point { x: 0.5, y: -1.0 };
+        let (header, layers, entities) =
+            read_dxf(&to_dxf(main_circle_radius, &center, &circles, &params));
+
+        assert!(header.contains(&("$INSUNITS".to_string(), "5".to_string())));
+        assert_eq!(layers, [CONTAINER_LAYER, CIRCLES_LAYER, LABELS_LAYER]);
+        assert_eq!(entities.len(), 1 + circles.len());
+        assert!(entities.iter().all(|e| e.kind == "CIRCLE"));
+
+        let container = &entities[0];
+        assert_eq!(container.get(8), CONTAINER_LAYER);
+        assert_eq!(container.number(10), 0.5 * 2.5);
+        assert_eq!(container.number(20), -1.0 * 2.5);
+        assert_eq!(container.number(40), main_circle_radius * 2.5);
+
+        for (entity, circle) in entities[1..].iter().zip(circles.iter()) {
+            let center = circle.center.unwrap();
+            assert_eq!(entity.get(8), CIRCLES_LAYER);
+            assert_eq!(entity.number(10), center.x * 2.5);
+            assert_eq!(entity.number(20), center.y * 2.5);
+            assert_eq!(entity.number(40), circle.radius * 2.5);
+        }
+    }
+
+    #[test]
+    fn labels_read_back_from_file() {
+        let (main_circle_radius, circles) = packing();
+        let path = std::env::temp_dir().join("circles-packing-dxf-test.dxf");
+        write_dxf(
+            &path,
+            main_circle_radius,
+            &Point::empty(),
+            &circles,
+            &DxfParams::default().with_labels(Some(CircleLabel::Index)),
+        )
+        .unwrap();
+        let dxf = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).ok();
+
+        let (header, _, entities) = read_dxf(&dxf);
+        assert!(header.contains(&("$INSUNITS".to_string(), "4".to_string())));
+
+        let labels: Vec<&Entity> = entities.iter().filter(|e| e.kind == "TEXT").collect();
+        assert_eq!(labels.len(), circles.len());
+        for (i, (label, circle)) in labels.iter().zip(circles.iter()).enumerate() {
+            let center = circle.center.unwrap();
+            assert_eq!(label.get(8), LABELS_LAYER);
+            assert_eq!(label.get(1), (i + 1).to_string());
+            assert_eq!(label.number(11), center.x);
+            assert_eq!(label.number(21), center.y);
+            assert_eq!(label.number(40), 0.5);
+        }
+    }
+}
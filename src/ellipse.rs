@@ -0,0 +1,244 @@
+use nalgebra::{Matrix2, Vector2};
+
+use crate::{point::Point, utils::FloatType};
+
+/// Number of boundary points used to bracket the farthest from the container point.
+const CONTAINMENT_SAMPLES: usize = 32;
+
+/// Ellipse with semi-axes `semi_major` (along its own x axis) and `semi_minor`,
+/// rotated counterclockwise by `angle`.
+#[derive(Clone, Copy, Debug)]
+pub struct Ellipse {
+    pub center: Option<Point>,
+    pub semi_major: FloatType,
+    pub semi_minor: FloatType,
+    pub angle: FloatType,
+}
+
+/// Axis-aligned elliptical main container centered at (0, 0) with semi-axes
+/// `aspect_ratio * R` and `R`. Aspect ratio 1 is the usual main circle.
+#[derive(Clone, Copy, Debug)]
+pub struct EllipseContainer {
+    pub aspect_ratio: FloatType,
+}
+
+impl EllipseContainer {
+    pub fn circle() -> Self {
+        EllipseContainer { aspect_ratio: 1.0 }
+    }
+
+    /// Value of `x^2 / k^2 + y^2 - R^2`, positive when the point is outside the container.
+    pub fn outside_value(&self, point: &Point, main_radius: FloatType) -> FloatType {
+        (point.x / self.aspect_ratio).powi(2) + point.y.powi(2) - main_radius.powi(2)
+    }
+}
+
+/// Value of the Perram-Wertheim contact function and its derivatives by the first ellipse
+/// center and by both angles.
+pub struct Contact {
+    /// Less than 1 when ellipses overlap, equals 1 when they touch.
+    pub value: FloatType,
+    pub gradient_center: Vector2<FloatType>,
+    pub gradient_angle_first: FloatType,
+    pub gradient_angle_second: FloatType,
+}
+
+fn rotation(angle: FloatType) -> Matrix2<FloatType> {
+    let (sin, cos) = angle.sin_cos();
+    Matrix2::new(cos, -sin, sin, cos)
+}
+
+fn rotation_derivative(angle: FloatType) -> Matrix2<FloatType> {
+    let (sin, cos) = angle.sin_cos();
+    Matrix2::new(-sin, -cos, cos, -sin)
+}
+
+impl Ellipse {
+    pub fn new(
+        semi_major: FloatType,
+        semi_minor: FloatType,
+        center: Point,
+        angle: FloatType,
+    ) -> Self {
+        Ellipse {
+            center: Some(center),
+            semi_major,
+            semi_minor,
+            angle,
+        }
+    }
+
+    pub fn with_axes(semi_major: FloatType, semi_minor: FloatType) -> Self {
+        Ellipse {
+            center: None,
+            semi_major,
+            semi_minor,
+            angle: 0.0,
+        }
+    }
+
+    /// Circle is an ellipse with equal semi-axes, its angle doesn't matter.
+    pub fn is_round(&self) -> bool {
+        self.semi_major == self.semi_minor
+    }
+
+    /// Same ellipse scaled by the given factor with center (0, 0).
+    pub fn scaled(&self, factor: FloatType) -> Self {
+        Ellipse {
+            center: self.center.map(|center| Point {
+                x: center.x * factor,
+                y: center.y * factor,
+            }),
+            semi_major: self.semi_major * factor,
+            semi_minor: self.semi_minor * factor,
+            angle: self.angle,
+        }
+    }
+
+    /// Matrix `A` such that the ellipse is `{x: (x - c)^T A^-1 (x - c) <= 1}`.
+    fn shape_matrix(&self) -> Matrix2<FloatType> {
+        let rotation = rotation(self.angle);
+        let axes = Matrix2::new(self.semi_major.powi(2), 0.0, 0.0, self.semi_minor.powi(2));
+        rotation * axes * rotation.transpose()
+    }
+
+    fn shape_matrix_angle_derivative(&self) -> Matrix2<FloatType> {
+        let (rotation, derivative) = (rotation(self.angle), rotation_derivative(self.angle));
+        let axes = Matrix2::new(self.semi_major.powi(2), 0.0, 0.0, self.semi_minor.powi(2));
+        derivative * axes * rotation.transpose() + rotation * axes * derivative.transpose()
+    }
+
+    /// Point of the boundary with parameter `t` and its derivative by the angle.
+    pub fn boundary_point(&self, t: FloatType) -> (Point, Point) {
+        let center = self.center.expect("Valid center");
+        let local = Vector2::new(self.semi_major * t.cos(), self.semi_minor * t.sin());
+        let (point, derivative) = (
+            rotation(self.angle) * local,
+            rotation_derivative(self.angle) * local,
+        );
+
+        (
+            Point {
+                x: center.x + point.x,
+                y: center.y + point.y,
+            },
+            Point {
+                x: derivative.x,
+                y: derivative.y,
+            },
+        )
+    }
+
+    /// Perram-Wertheim contact function `F = max λ(1 - λ) r^T C(λ)^-1 r`,
+    /// `C(λ) = (1 - λ) A + λ B`, maximized over `λ` with golden-section search.
+    pub fn contact(&self, other: &Ellipse) -> Contact {
+        let (center_self, center_other) = (
+            self.center.expect("Valid center"),
+            other.center.expect("Valid center"),
+        );
+        let r = Vector2::new(
+            center_self.x - center_other.x,
+            center_self.y - center_other.y,
+        );
+        let (a, b) = (self.shape_matrix(), other.shape_matrix());
+
+        let function = |lambda: FloatType| -> FloatType {
+            let c = (1.0 - lambda) * a + lambda * b;
+            match c.try_inverse() {
+                Some(c_inverse) => lambda * (1.0 - lambda) * r.dot(&(c_inverse * r)),
+                None => 0.0,
+            }
+        };
+
+        let ratio = (5.0_f64.sqrt() as FloatType - 1.0) / 2.0;
+        let (mut left, mut right) = (0.0 as FloatType, 1.0 as FloatType);
+        while right - left > 1e-10 {
+            let (m1, m2) = (
+                right - ratio * (right - left),
+                left + ratio * (right - left),
+            );
+            if function(m1) < function(m2) {
+                left = m1;
+            } else {
+                right = m2;
+            }
+        }
+        let lambda = (left + right) / 2.0;
+
+        let c_inverse = ((1.0 - lambda) * a + lambda * b)
+            .try_inverse()
+            .unwrap_or(Matrix2::zeros());
+        let u = c_inverse * r;
+        let weight = lambda * (1.0 - lambda);
+
+        Contact {
+            value: weight * r.dot(&u),
+            gradient_center: 2.0 * weight * u,
+            gradient_angle_first: -weight
+                * (1.0 - lambda)
+                * u.dot(&(self.shape_matrix_angle_derivative() * u)),
+            gradient_angle_second: -weight
+                * lambda
+                * u.dot(&(other.shape_matrix_angle_derivative() * u)),
+        }
+    }
+
+    /// Touching ellipses are considered overlapping like `Circle::overlap` does.
+    pub fn overlap(&self, other: &Ellipse) -> bool {
+        if self.center.is_none() || other.center.is_none() {
+            return false;
+        }
+
+        self.contact(other).value <= 1.0
+    }
+
+    pub fn is_overlap(&self, ellipses: &Vec<Ellipse>) -> bool {
+        ellipses.iter().any(|ellipse| self.overlap(ellipse))
+    }
+
+    /// Boundary parameter `t` of the point farthest outside the container: best of
+    /// `CONTAINMENT_SAMPLES` samples refined with golden-section search.
+    pub fn farthest_boundary_parameter(
+        &self,
+        container: &EllipseContainer,
+        main_radius: FloatType,
+    ) -> FloatType {
+        let step = 2.0 * std::f64::consts::PI as FloatType / CONTAINMENT_SAMPLES as FloatType;
+        let function =
+            |t: FloatType| container.outside_value(&self.boundary_point(t).0, main_radius);
+
+        let best = (0..CONTAINMENT_SAMPLES)
+            .map(|k| k as FloatType * step)
+            .max_by(|a, b| function(*a).total_cmp(&function(*b)))
+            .unwrap();
+
+        let ratio = (5.0_f64.sqrt() as FloatType - 1.0) / 2.0;
+        let (mut left, mut right) = (best - step, best + step);
+        while right - left > 1e-10 {
+            let (m1, m2) = (
+                right - ratio * (right - left),
+                left + ratio * (right - left),
+            );
+            if function(m1) < function(m2) {
+                left = m1;
+            } else {
+                right = m2;
+            }
+        }
+
+        (left + right) / 2.0
+    }
+
+    pub fn is_inside_container(
+        &self,
+        container: &EllipseContainer,
+        main_radius: FloatType,
+    ) -> bool {
+        if self.center.is_none() {
+            return false;
+        }
+
+        let t = self.farthest_boundary_parameter(container, main_radius);
+        container.outside_value(&self.boundary_point(t).0, main_radius) <= 0.0
+    }
+}
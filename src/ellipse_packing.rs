@@ -0,0 +1,231 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::{Arc, Mutex};
+
+use crate::{
+    ellipse::{Ellipse, EllipseContainer},
+    point::Point,
+    ralgo::{
+        calcfg_ellipses::{angle_variables, calcfg_ellipses},
+        dichotomy_step_ralgo::dichotomy_step,
+        ralgo_params::RalgoParams,
+    },
+    random_arrangement::random_point,
+    scaling::Scaling,
+    utils::FloatType,
+};
+
+pub fn ellipses_to_dvector(ellipses: &Vec<Ellipse>, main_radius: FloatType) -> DVector<FloatType> {
+    let data: Vec<FloatType> = [
+        Vec::from_iter(ellipses.iter().map(|e| e.center.expect("Valid center").x)),
+        Vec::from_iter(ellipses.iter().map(|e| e.center.expect("Valid center").y)),
+        Vec::from_iter(ellipses.iter().filter(|e| !e.is_round()).map(|e| e.angle)),
+        vec![main_radius],
+    ]
+    .concat();
+    return DVector::from_vec(data);
+}
+
+pub fn dvector_to_ellipses(
+    x: &DVector<FloatType>,
+    semi_axes: &Vec<(FloatType, FloatType)>,
+) -> (FloatType, Vec<Ellipse>) {
+    let n = semi_axes.len();
+    let angles = angle_variables(semi_axes);
+    let ellipses = (0..n)
+        .map(|i| {
+            Ellipse::new(
+                semi_axes[i].0,
+                semi_axes[i].1,
+                Point {
+                    x: x[i],
+                    y: x[n + i],
+                },
+                // ellipse is symmetric, so angle is kept in [0, π)
+                angles[i].map_or(0.0, |k| x[k].rem_euclid(std::f64::consts::PI as FloatType)),
+            )
+        })
+        .collect();
+
+    return (x[x.len() - 1], ellipses);
+}
+
+pub fn is_valid_ellipse_pack(
+    main_radius: FloatType,
+    ellipses: &Vec<Ellipse>,
+    container: &EllipseContainer,
+) -> bool {
+    if ellipses
+        .iter()
+        .any(|ellipse| !ellipse.is_inside_container(container, main_radius))
+    {
+        return false;
+    }
+
+    for i in 0..ellipses.len() {
+        for j in i + 1..ellipses.len() {
+            if ellipses[i].overlap(&ellipses[j]) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
+pub fn dichotomy_step_ralgo_ellipses(
+    main_radius: FloatType,
+    ellipses: &Vec<Ellipse>,
+    container: &EllipseContainer,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (FloatType, Vec<Ellipse>) {
+    // solve in canonical scale with ellipses sizes of circles of the same area
+    let scaling = Scaling::canonical(
+        ellipses
+            .iter()
+            .map(|e| (e.semi_major * e.semi_minor).sqrt()),
+    );
+    let ellipses: Vec<Ellipse> = ellipses.iter().map(|e| e.scaled(scaling.factor)).collect();
+    let semi_axes: Vec<(FloatType, FloatType)> = ellipses
+        .iter()
+        .map(|e| (e.semi_major, e.semi_minor))
+        .collect();
+
+    let x = dichotomy_step(
+        ellipses_to_dvector(&ellipses, scaling.scale(main_radius)),
+        |x| calcfg_ellipses(x, &semi_axes, container),
+        reset_step,
+        eps,
+        ralgo_params,
+        |_, _, _| {},
+    );
+
+    let (main_radius, ellipses) = dvector_to_ellipses(&x, &semi_axes);
+    let ellipses = ellipses
+        .iter()
+        .map(|e| e.scaled(1.0 / scaling.factor))
+        .collect();
+
+    return (scaling.unscale(main_radius), ellipses);
+}
+
+/// Random centers and angles inside the container big enough to hold all ellipses loosely.
+fn generate_random_ellipses(
+    semi_axes: &Vec<(FloatType, FloatType)>,
+    container: &EllipseContainer,
+    rng: &Arc<Mutex<StdRng>>,
+) -> (FloatType, Vec<Ellipse>) {
+    let main_radius = 2.0
+        * semi_axes
+            .iter()
+            .map(|(a, b)| a * b)
+            .sum::<FloatType>()
+            .sqrt()
+        / container.aspect_ratio.min(1.0).sqrt();
+
+    let mut rng = rng.lock().unwrap();
+    let ellipses = semi_axes
+        .iter()
+        .map(|&(semi_major, semi_minor)| {
+            let Point { x, y } = random_point(1.0, &mut *rng);
+
+            Ellipse::new(
+                semi_major,
+                semi_minor,
+                Point {
+                    x: x * container.aspect_ratio * main_radius,
+                    y: y * main_radius,
+                },
+                rng.gen_range(0.0..std::f64::consts::PI as FloatType),
+            )
+        })
+        .collect();
+
+    return (main_radius, ellipses);
+}
+
+/// Multistart r-algorithm from random arrangements, returns the best valid packing.
+pub fn find_ellipses_answer(
+    semi_axes: &Vec<(FloatType, FloatType)>,
+    container: &EllipseContainer,
+    launches: usize,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (FloatType, Vec<Ellipse>) {
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+    let answer = Arc::new(Mutex::new((FloatType::MAX, Vec::<Ellipse>::new())));
+
+    (0..launches).into_par_iter().for_each(|_| {
+        let (main_radius, ellipses) = generate_random_ellipses(semi_axes, container, &rng);
+
+        let (new_main_radius, new_ellipses) = dichotomy_step_ralgo_ellipses(
+            main_radius,
+            &ellipses,
+            container,
+            reset_step,
+            eps,
+            ralgo_params,
+        );
+
+        let mut answer = answer.lock().unwrap();
+        if is_valid_ellipse_pack(new_main_radius, &new_ellipses, container)
+            && new_main_radius < answer.0
+        {
+            *answer = (new_main_radius, new_ellipses);
+        }
+    });
+
+    let answer = answer.lock().unwrap().clone();
+    answer
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ralgo::step_strategy::{StepSchedule, StepStrategy};
+
+    use super::*;
+
+    #[test]
+    fn packs_round_and_elongated_ellipses() {
+        // angle of round ellipses has zero gradient, it used to drift to NaN
+        let semi_axes = vec![(1.0, 1.0), (1.0, 1.0), (2.0, 1.0)];
+        let container = EllipseContainer { aspect_ratio: 1.5 };
+        let ralgo_params = RalgoParams::default()
+            .with_max_iterations(200)
+            .with_step_strategy(
+                StepStrategy::relative().with_schedule(StepSchedule::levels(3).unwrap()),
+            );
+
+        let (main_radius, ellipses) =
+            find_ellipses_answer(&semi_axes, &container, 1, false, 0.0, &ralgo_params);
+
+        assert_eq!(ellipses.len(), 3);
+        assert!(is_valid_ellipse_pack(main_radius, &ellipses, &container));
+        assert!(main_radius >= 1.0);
+        assert_eq!((ellipses[0].angle, ellipses[1].angle), (0.0, 0.0));
+        assert!((0.0..std::f64::consts::PI as FloatType).contains(&ellipses[2].angle));
+    }
+
+    #[test]
+    fn round_ellipses_have_no_angle_variables() {
+        let semi_axes = vec![(1.0, 1.0), (2.0, 1.0), (0.5, 0.5)];
+        let ellipses = vec![
+            Ellipse::new(1.0, 1.0, Point { x: 1.0, y: 2.0 }, 0.3),
+            Ellipse::new(2.0, 1.0, Point { x: 3.0, y: 4.0 }, 4.0),
+            Ellipse::new(0.5, 0.5, Point { x: 5.0, y: 6.0 }, 0.0),
+        ];
+
+        let x = ellipses_to_dvector(&ellipses, 7.0);
+        assert_eq!(x.len(), 2 * 3 + 1 + 1);
+
+        let (main_radius, restored) = dvector_to_ellipses(&x, &semi_axes);
+        assert_eq!(main_radius, 7.0);
+        assert_eq!(restored[0].angle, 0.0);
+        assert!((restored[1].angle - (4.0 - std::f64::consts::PI as FloatType)).abs() < 1e-12);
+        assert_eq!(restored[2].center.unwrap().y, 6.0);
+    }
+}
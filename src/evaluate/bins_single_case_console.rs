@@ -0,0 +1,52 @@
+use crate::{
+    bin_packing::{bin_packing, BinPackingResult},
+    instance::Instance,
+    plot::draw_bins_plot,
+    ralgo::ralgo_params::RalgoParams,
+    utils::{measure_time, FloatType},
+};
+
+/// Circles of the instance assigned to the smallest number of bins of `bin_radius`,
+/// packing of every bin is printed and drawn to `bins_plot` of the workspace.
+pub fn bins_single_case_console(
+    instance: &Instance,
+    bin_radius: FloatType,
+    number_of_iterations: u32,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Result<BinPackingResult, String> {
+    let radiuses = &instance.radiuses;
+
+    let (time, result) = measure_time(|| {
+        bin_packing(
+            radiuses,
+            bin_radius,
+            number_of_iterations,
+            reset_step,
+            eps,
+            ralgo_params,
+        )
+    });
+
+    println!(
+        "Bin packing of {} circles into bins of R = {bin_radius}: {time:.3}s",
+        radiuses.len()
+    );
+    result.print();
+    for (index, bin) in result.bins.iter().enumerate() {
+        println!("Circles of bin {}:", index + 1);
+        for (circle_index, circle) in bin.circle_indices.iter().zip(bin.circles.iter()) {
+            println!(
+                "{} {} {:.15} {:.15}",
+                circle_index,
+                circle.radius,
+                circle.center.unwrap().x,
+                circle.center.unwrap().y
+            );
+        }
+    }
+    draw_bins_plot(bin_radius, &result.circles_by_bin())?;
+
+    Ok(result)
+}
@@ -0,0 +1,49 @@
+use crate::{
+    ellipse::{Ellipse, EllipseContainer},
+    ellipse_packing::find_ellipses_answer,
+    plot::draw_ellipses_plot,
+    ralgo::ralgo_params::RalgoParams,
+    utils::{measure_time, FloatType},
+};
+
+/// Ellipses with `semi_axes` packed into the container by random launches of r-algorithm,
+/// the best packing is printed and drawn to `ellipse_plot` of the workspace.
+pub fn ellipses_single_case_console(
+    semi_axes: &Vec<(FloatType, FloatType)>,
+    container: &EllipseContainer,
+    launches: usize,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Result<(FloatType, Vec<Ellipse>), String> {
+    let (time, (main_radius, ellipses)) = measure_time(|| {
+        find_ellipses_answer(
+            semi_axes,
+            container,
+            launches,
+            reset_step,
+            eps,
+            ralgo_params,
+        )
+    });
+    if ellipses.is_empty() {
+        return Err(format!(
+            "No valid packing of ellipses in {launches} launches"
+        ));
+    }
+
+    println!(
+        "Ellipses, aspect ratio {}: {launches} launches took {time:.3}s, R = {main_radius}",
+        container.aspect_ratio
+    );
+    for ellipse in &ellipses {
+        let center = ellipse.center.unwrap();
+        println!(
+            "{} {} {:.15} {:.15} {:.15}",
+            ellipse.semi_major, ellipse.semi_minor, center.x, center.y, ellipse.angle
+        );
+    }
+    draw_ellipses_plot(main_radius, &ellipses, container)?;
+
+    Ok((main_radius, ellipses))
+}
@@ -3,6 +3,7 @@ use crate::packing::{self, find_answer};
 use crate::ralgo::dichotomy_step_ralgo::dichotomy_step_ralgo_result_with_iterations;
 use crate::ralgo::ralgo_params::RalgoParams;
 use crate::utils::{measure_time, FloatType};
+use crate::workspace::{save_workbook, workspace};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use rust_xlsxwriter::{column_number_to_name, Format, Formula, Workbook, Worksheet};
 use std::sync::{Arc, Mutex};
@@ -74,14 +75,14 @@ pub fn heuristic_all_cases(
             .ok();
     }
 
-    let number_of_tests = fs::read_dir("./input/")?
+    let number_of_tests = fs::read_dir(&workspace().input)?
         .map(|res| res.map(|e| e.path()))
         .collect::<Result<Vec<_>, io::Error>>()?
         .len();
 
     (1..=number_of_tests as u32)
         .into_par_iter()
-        .for_each(|test_number| {
+        .try_for_each(|test_number| -> io::Result<()> {
             println!("Test {}", test_number);
 
             // write the test number in the far left column
@@ -91,8 +92,8 @@ pub fn heuristic_all_cases(
                 .write(test_number, 0, test_number)
                 .ok();
 
-            let (_, mut radiuses) = get_input_data(test_number);
-            let jury_answer = get_jury_answer(test_number);
+            let (_, mut radiuses) = get_input_data(test_number)?;
+            let jury_answer = get_jury_answer(test_number)?;
 
             // get result of heuristic algorithm
             let (_, (main_circle_radius, circles)) =
@@ -139,7 +140,9 @@ pub fn heuristic_all_cases(
                     &cell_format,
                 );
             }
-        });
+
+            Ok(())
+        })?;
 
     let mut col: u16 = 2;
     while col < (algorithm_params.len() * 4 + 5) as u16 {
@@ -158,14 +161,17 @@ pub fn heuristic_all_cases(
         col += 2;
     }
 
+    write_step_strategy(&worksheet, (number_of_tests + 2) as u32, ralgo_params, &cell_format);
+
     worksheet.lock().unwrap().autofit();
 
-    workbook
-        .save(format!(
-            "./results/heuristic/result-multi-alpha={}-q1={}.xlsx",
+    save_workbook(
+        &mut workbook,
+        workspace().heuristic_results.join(format!(
+            "result-multi-alpha={}-q1={}.xlsx",
             ralgo_params.alpha, ralgo_params.q1
-        ))
-        .ok();
+        )),
+    )?;
 
     Ok(())
 }
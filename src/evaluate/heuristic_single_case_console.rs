@@ -7,16 +7,19 @@ use crate::{
     }, utils::FloatType,
 };
 
+/// Best valid packing of `dichotomy_step_ralgo` from the heuristic one over all parameters,
+/// with the best `R` after the heuristic and after every combination of parameters.
 pub fn heuristic_single_case_console(
     test_number: u32,
     algorithm_params: &[(bool, FloatType)],
     alpha_q1_pairs: &Vec<(FloatType, FloatType)>,
-) -> (FloatType, Vec<Circle>) {
+) -> (FloatType, Vec<Circle>, Vec<FloatType>) {
     let mut radiuses = (1..=test_number).map(|x| x as FloatType).collect::<Vec<_>>();
     let (main_circle_radius, circles) = find_answer(&mut radiuses, 100_000);
 
     let mut answer_main_circle_radius = main_circle_radius;
     let mut answer_circles = circles.clone();
+    let mut trace = vec![main_circle_radius];
 
     for (alpha, q1) in alpha_q1_pairs {
         for (reset_step, eps) in algorithm_params {
@@ -41,8 +44,9 @@ pub fn heuristic_single_case_console(
                 answer_main_circle_radius = new_main_circle_radius;
                 answer_circles = new_circles;
             }
+            trace.push(answer_main_circle_radius);
         }
     }
 
-    (answer_main_circle_radius, answer_circles)
+    (answer_main_circle_radius, answer_circles, trace)
 }
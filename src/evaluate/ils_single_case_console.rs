@@ -0,0 +1,55 @@
+use super::utils::print_points;
+use crate::{
+    ils::{iterated_local_search, IlsParams, IlsResult},
+    instance::Instance,
+    packomania::save_solution,
+    solution::Solution,
+    utils::{measure_time, FloatType},
+    workspace::workspace,
+};
+
+/// Run iterated local search with jump moves on the instance and compare the best `R` with
+/// the known best one. The best valid packing is saved to `ils-<name>.json` of the workspace
+/// solutions.
+pub fn ils_single_case_console(instance: &Instance, params: &IlsParams) -> IlsResult {
+    let (time, result) = measure_time(|| iterated_local_search(&instance.radiuses, params));
+    result.print();
+    if result.circles.is_empty() {
+        println!("No valid packing in {} trajectories", params.trajectories);
+        return result;
+    }
+    print_points(result.main_circle_radius, instance);
+
+    // best `R` of all trajectories after every iteration
+    let iterations = result.trajectories.iter().map(Vec::len).max().unwrap_or(0);
+    let trace = (0..iterations)
+        .map(|i| {
+            result
+                .trajectories
+                .iter()
+                .filter_map(|history| history.get(i))
+                .fold(FloatType::MAX, |a, &b| a.min(b))
+        })
+        .filter(|r| *r != FloatType::MAX)
+        .collect();
+
+    save_solution(
+        &Solution::new(result.main_circle_radius, result.circles.clone())
+            .with_instance(instance.name.clone())
+            .with_solver("ils")
+            .with_setting("trajectories", params.trajectories)
+            .with_setting("iterations", params.iterations)
+            .with_setting("jumps", params.jumps)
+            .with_setting("reset_step", params.reset_step)
+            .with_setting("eps", params.eps)
+            .with_ralgo_params(&params.ralgo_params)
+            .with_seed(params.seed)
+            .with_time(time)
+            .with_trace(trace),
+        &workspace()
+            .solutions
+            .join(format!("ils-{}.json", instance.name)),
+    );
+
+    result
+}
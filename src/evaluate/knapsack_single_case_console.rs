@@ -0,0 +1,39 @@
+use crate::{
+    instance::Instance,
+    knapsack::{knapsack_pack, KnapsackObjective, KnapsackResult},
+    ralgo::ralgo_params::RalgoParams,
+    utils::{measure_time, FloatType},
+};
+
+/// Subset of circles of the instance packed into the main circle of fixed radius.
+/// Instances have no values of circles, so `KnapsackObjective::Value` uses radii as values.
+pub fn knapsack_single_case_console(
+    instance: &Instance,
+    main_circle_radius: FloatType,
+    objective: KnapsackObjective,
+    number_of_iterations: u32,
+    ralgo_params: &RalgoParams,
+) -> Result<KnapsackResult, String> {
+    let radiuses = &instance.radiuses;
+
+    let (time, result) = measure_time(|| {
+        knapsack_pack(
+            main_circle_radius,
+            radiuses,
+            radiuses,
+            objective,
+            number_of_iterations,
+            ralgo_params,
+        )
+    });
+    let result = result?;
+
+    println!(
+        "Knapsack {objective:?} with R = {main_circle_radius}: {} of {} circles, {time:.3}s",
+        result.packed.len(),
+        radiuses.len()
+    );
+    result.print();
+
+    Ok(result)
+}
@@ -0,0 +1,128 @@
+use std::{
+    io,
+    sync::{Arc, Mutex},
+};
+
+use rust_xlsxwriter::{Chart, Format, Workbook};
+
+use super::utils::{print_points, write_step_strategy};
+use crate::instance::Instance;
+use crate::memetic::{memetic, MemeticParams, MemeticResult};
+use crate::packomania::save_solution;
+use crate::solution::Solution;
+use crate::utils::{measure_time, FloatType};
+use crate::workspace::{save_workbook, workspace};
+
+/// Run memetic algorithm on the instance, print generations and write best `R` and
+/// diversity curves into `memetic-single-result-test-<name>.xlsx` of the workspace. The best
+/// packing is saved to `memetic-<name>.json` of the workspace solutions.
+pub fn memetic_single_case(
+    instance: &Instance,
+    params: &MemeticParams,
+) -> io::Result<MemeticResult> {
+    let (time, result) = measure_time(|| memetic(&instance.radiuses, params));
+    let result = result.map_err(io::Error::other)?;
+    result.print();
+    print_points(result.main_circle_radius, instance);
+
+    save_solution(
+        &Solution::new(result.main_circle_radius, result.circles.clone())
+            .with_instance(instance.name.clone())
+            .with_solver("memetic")
+            .with_setting("population_size", params.population_size)
+            .with_setting("generations", params.generations)
+            .with_setting("offspring", params.offspring)
+            .with_setting("mutation_rate", params.mutation_rate)
+            .with_setting("reset_step", params.reset_step)
+            .with_setting("eps", params.eps)
+            .with_ralgo_params(&params.ralgo_params)
+            .with_seed(params.seed)
+            .with_time(time)
+            .with_trace(
+                result
+                    .reports
+                    .iter()
+                    .map(|report| report.best_main_circle_radius)
+                    .filter(|r| *r != FloatType::MAX)
+                    .collect(),
+            ),
+        &workspace()
+            .solutions
+            .join(format!("memetic-{}.json", instance.name)),
+    );
+
+    let mut workbook: Workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let sheet_name = "generations";
+    worksheet.set_name(sheet_name).ok();
+
+    let cell_format = Format::new().set_align(rust_xlsxwriter::FormatAlign::Center);
+
+    for (col, data) in [
+        "generation",
+        "best R",
+        "mean R",
+        "diversity",
+        "accepted",
+        "time",
+    ]
+    .iter()
+    .enumerate()
+    {
+        worksheet
+            .write_with_format(0, col as u16, *data, &cell_format)
+            .ok();
+    }
+
+    for (i, report) in result.reports.iter().enumerate() {
+        let row = (i + 1) as u32;
+        worksheet
+            .write_with_format(row, 0, report.generation as u32, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(row, 1, report.best_main_circle_radius, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(row, 2, report.mean_main_circle_radius, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(row, 3, report.diversity, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(row, 4, report.accepted_children as u32, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(row, 5, report.time, &cell_format)
+            .ok();
+    }
+
+    let last_row = result.reports.len() as u32;
+    for (chart_index, (col, name)) in [(1, "best R"), (3, "diversity")].iter().enumerate() {
+        let mut chart = Chart::new_line();
+        chart
+            .add_series()
+            .set_categories((sheet_name, 1, 0, last_row, 0))
+            .set_values((sheet_name, 1, *col, last_row, *col))
+            .set_name(*name);
+        chart.title().set_name(*name);
+        chart.x_axis().set_name("generation");
+
+        worksheet
+            .insert_chart(1 + 16 * chart_index as u32, 7, &chart)
+            .ok();
+    }
+
+    let worksheet = Arc::new(Mutex::new(worksheet));
+    write_step_strategy(&worksheet, last_row + 2, &params.ralgo_params, &cell_format);
+    worksheet.lock().unwrap().autofit();
+    drop(worksheet);
+
+    save_workbook(
+        &mut workbook,
+        workspace()
+            .memetic_results
+            .join(format!("memetic-single-result-test-{}.xlsx", instance.name)),
+    )?;
+
+    Ok(result)
+}
@@ -1,8 +1,21 @@
+pub mod bins_single_case_console;
+pub mod ellipses_single_case_console;
 pub mod heuristic_all_cases;
 pub mod heuristic_single_case;
 pub mod heuristic_single_case_console;
+pub mod ils_single_case_console;
+pub mod knapsack_single_case_console;
+pub mod memetic_single_case;
+pub mod obstacles_single_case_console;
+pub mod packomania_batch;
+pub mod pipeline_single_case_console;
 pub mod random_all_cases;
 pub mod random_single_case;
 pub mod random_single_case_iterations;
 pub mod random_single_case_console;
+pub mod refine_solution_console;
+pub mod screening_single_case_console;
+pub mod solvers_single_case;
+pub mod symmetric_single_case_console;
+pub mod trajectory_single_case;
 mod utils;
\ No newline at end of file
@@ -0,0 +1,56 @@
+use crate::{
+    circle::Circle,
+    instance::Instance,
+    obstacle::Obstacle,
+    packing::{find_answer_with_obstacles, is_valid_pack_with_obstacles},
+    plot::draw_plot_with_obstacles,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo_with_obstacles, ralgo_params::RalgoParams},
+    utils::{measure_time, FloatType},
+};
+
+/// Packing of the instance around `obstacles`: heuristic start improved by
+/// r-algorithm. Returns the best valid packing, it's drawn with the obstacles.
+pub fn obstacles_single_case_console(
+    instance: &Instance,
+    obstacles: &[Obstacle],
+    number_of_iterations: u32,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Result<(FloatType, Vec<Circle>), String> {
+    let mut radiuses = instance.radiuses.clone();
+
+    let (time, (main_circle_radius, circles)) =
+        measure_time(|| find_answer_with_obstacles(&mut radiuses, obstacles, number_of_iterations));
+    if !is_valid_pack_with_obstacles(main_circle_radius, &circles, obstacles) {
+        return Err(format!(
+            "Heuristic failed to pack {} around {} obstacles",
+            instance.name,
+            obstacles.len()
+        ));
+    }
+    println!("Heuristic: {time:.3}s, R = {main_circle_radius}");
+
+    let (time, (ralgo_main_circle_radius, ralgo_circles)) = measure_time(|| {
+        dichotomy_step_ralgo_with_obstacles(
+            main_circle_radius,
+            &circles,
+            obstacles,
+            reset_step,
+            eps,
+            ralgo_params,
+        )
+    });
+    let is_valid =
+        is_valid_pack_with_obstacles(ralgo_main_circle_radius, &ralgo_circles, obstacles);
+    println!("Ralgo: {time:.3}s, R = {ralgo_main_circle_radius}, valid: {is_valid}");
+
+    let answer = if is_valid && ralgo_main_circle_radius < main_circle_radius {
+        (ralgo_main_circle_radius, ralgo_circles)
+    } else {
+        (main_circle_radius, circles)
+    };
+    draw_plot_with_obstacles(answer.0, &answer.1, obstacles);
+
+    Ok(answer)
+}
@@ -0,0 +1,270 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use rust_xlsxwriter::{Format, Workbook};
+
+use crate::{
+    instance::{Instance, InstanceFormat},
+    packomania::{matching_decimal_digits, save_solution},
+    pipeline::{Candidate, Pipeline},
+    solution::Solution,
+    utils::FloatType,
+    workspace::{save_workbook, workspace},
+};
+
+const HEADING_NAMES: [&str; 9] = [
+    "File",
+    "Circles",
+    "R",
+    "Record R",
+    "Relative gap",
+    "Matching digits",
+    "Is valid?",
+    "Runs",
+    "Time",
+];
+
+/// Result of the solver on one packomania instance compared with its record.
+#[derive(Debug, Clone)]
+pub struct ComparisonRow {
+    pub file: String,
+    pub circles: usize,
+    /// Best valid `R`, `None` when the solver didn't find a valid packing.
+    pub main_circle_radius: Option<FloatType>,
+    pub record: FloatType,
+    /// `(R - record) / record`, negative when the record is improved.
+    pub relative_gap: Option<FloatType>,
+    pub matching_digits: Option<usize>,
+    pub is_valid: bool,
+    /// Number of pipeline runs made within the time budget.
+    pub runs: usize,
+    pub time: FloatType,
+}
+
+/// Packomania files (`.txt`) of the directory, in order of the number of circles for
+/// names like `N.txt`.
+fn packomania_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect();
+
+    paths.sort_by_key(|path| {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        (stem.parse::<u32>().unwrap_or(u32::MAX), stem.to_string())
+    });
+
+    Ok(paths)
+}
+
+/// Run the pipeline again with shifted seeds while `time_budget` seconds aren't spent and
+/// keep the best valid candidate. The budget is a soft limit checked between runs: stages
+/// can't be interrupted, so there is always at least one run and the last started run is
+/// finished, i.e. the time is up to the budget plus one run. A pipeline without random
+/// starts is run once. The best candidate comes with the pipeline of its run.
+fn run_with_budget(
+    radiuses: &Vec<FloatType>,
+    pipeline: &Pipeline,
+    time_budget: FloatType,
+) -> (Option<(Candidate, Pipeline)>, usize, FloatType) {
+    let start = Instant::now();
+    let mut best: Option<(Candidate, Pipeline)> = None;
+    let mut runs = 0;
+
+    loop {
+        let run_pipeline = pipeline.with_seed_offset(runs as u64);
+        let result = run_pipeline.run(radiuses);
+        runs += 1;
+
+        if let Some(candidate) = result.best {
+            if best
+                .as_ref()
+                .is_none_or(|(b, _)| candidate.main_circle_radius < b.main_circle_radius)
+            {
+                best = Some((candidate, run_pipeline));
+            }
+        }
+
+        if !pipeline.is_randomized() || start.elapsed().as_secs_f64() >= time_budget {
+            break;
+        }
+    }
+
+    (best, runs, start.elapsed().as_secs_f64())
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+fn write_csv(path: &Path, rows: &[ComparisonRow]) -> io::Result<()> {
+    let mut csv = HEADING_NAMES.join(",") + "\n";
+    for row in rows {
+        csv += &[
+            csv_field(&row.file),
+            row.circles.to_string(),
+            optional(row.main_circle_radius),
+            row.record.to_string(),
+            optional(row.relative_gap),
+            optional(row.matching_digits),
+            row.is_valid.to_string(),
+            row.runs.to_string(),
+            row.time.to_string(),
+        ]
+        .join(",");
+        csv += "\n";
+    }
+
+    fs::write(path, csv)
+}
+
+fn write_xlsx(path: &Path, rows: &[ComparisonRow]) -> io::Result<()> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name("packomania").ok();
+
+    let cell_format = Format::new().set_align(rust_xlsxwriter::FormatAlign::Center);
+
+    for (col, data) in HEADING_NAMES.iter().enumerate() {
+        worksheet
+            .write_with_format(0, col as u16, *data, &cell_format)
+            .ok();
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let line = index as u32 + 1;
+        worksheet
+            .write_with_format(line, 0, &row.file, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(line, 1, row.circles as u32, &cell_format)
+            .ok();
+        if let Some(main_circle_radius) = row.main_circle_radius {
+            worksheet
+                .write_with_format(line, 2, main_circle_radius, &cell_format)
+                .ok();
+        }
+        worksheet
+            .write_with_format(line, 3, row.record, &cell_format)
+            .ok();
+        if let Some(relative_gap) = row.relative_gap {
+            worksheet
+                .write_with_format(line, 4, relative_gap, &cell_format)
+                .ok();
+        }
+        if let Some(matching_digits) = row.matching_digits {
+            worksheet
+                .write_with_format(line, 5, matching_digits as u32, &cell_format)
+                .ok();
+        }
+        worksheet
+            .write_with_format(line, 6, row.is_valid, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(line, 7, row.runs as u32, &cell_format)
+            .ok();
+        worksheet
+            .write_with_format(line, 8, row.time, &cell_format)
+            .ok();
+    }
+
+    worksheet.autofit();
+
+    save_workbook(&mut workbook, path)
+}
+
+/// Solve every packomania file of `directory` with the pipeline, spending at least one run
+/// and about `time_budget` seconds per instance (see `run_with_budget`, a long run isn't
+/// cut), and compare the best `R` with the record of the file. The table is written to
+/// `results_path` with `.csv` and `.xlsx` appended to it (`batch.v2` gives `batch.v2.csv`),
+/// the best packing of `N.txt` is saved to `packomania-batch/N.json` of the workspace
+/// solutions.
+/// Files that can't be read or have no record are reported and skipped.
+pub fn packomania_batch(
+    directory: impl AsRef<Path>,
+    pipeline: &Pipeline,
+    time_budget: FloatType,
+    results_path: impl AsRef<Path>,
+) -> io::Result<Vec<ComparisonRow>> {
+    let mut rows = Vec::new();
+
+    for path in packomania_files(directory.as_ref())? {
+        let instance = match Instance::read_as(&path, InstanceFormat::Packomania) {
+            Ok(instance) => instance,
+            Err(error) => {
+                println!("Skipped {error}");
+                continue;
+            }
+        };
+        let Some(record) = instance.known_best else {
+            println!("Skipped {}: no record R", path.display());
+            continue;
+        };
+
+        let (best, runs, time) = run_with_budget(&instance.radiuses, pipeline, time_budget);
+        let main_circle_radius = best.as_ref().map(|(best, _)| best.main_circle_radius);
+
+        if let Some((best, run_pipeline)) = &best {
+            save_solution(
+                &Solution::new(best.main_circle_radius, best.circles.clone())
+                    .with_instance(instance.name.clone())
+                    .with_solver("pipeline")
+                    .with_pipeline(run_pipeline)
+                    .with_setting("runs", runs)
+                    .with_time(time),
+                &workspace().solutions.join("packomania-batch").join(format!(
+                    "{}.json",
+                    path.file_stem().unwrap_or_default().to_string_lossy()
+                )),
+            );
+        }
+
+        let row = ComparisonRow {
+            file: path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string(),
+            circles: instance.radiuses.len(),
+            main_circle_radius,
+            record,
+            relative_gap: main_circle_radius.map(|r| (r - record) / record),
+            matching_digits: main_circle_radius.map(|r| matching_decimal_digits(r, record)),
+            is_valid: best.is_some(),
+            runs,
+            time,
+        };
+
+        match row.main_circle_radius {
+            Some(main_circle_radius) => println!(
+                "{}: R = {main_circle_radius}, record = {record}, gap = {:e}, digits = {}, runs = {runs}, time = {time:.3}s",
+                row.file,
+                row.relative_gap.unwrap(),
+                row.matching_digits.unwrap()
+            ),
+            None => println!("{}: no valid packing found, record = {record}", row.file),
+        }
+        rows.push(row);
+    }
+
+    let results_path = results_path.as_ref();
+    if let Some(parent) = results_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_csv(&results_path.with_added_extension("csv"), &rows)?;
+    write_xlsx(&results_path.with_added_extension("xlsx"), &rows)?;
+
+    Ok(rows)
+}
@@ -0,0 +1,41 @@
+use super::utils::print_points;
+use crate::{
+    instance::Instance,
+    packomania::save_solution,
+    pipeline::{Pipeline, PipelineResult},
+    solution::Solution,
+    utils::{measure_time, FloatType},
+    workspace::workspace,
+};
+
+/// Run the pipeline on the instance and compare the best `R` with the known best one.
+/// The best packing is saved to `pipeline-<name>.json` of the workspace solutions.
+pub fn pipeline_single_case_console(instance: &Instance, pipeline: &Pipeline) -> PipelineResult {
+    let (time, result) = measure_time(|| pipeline.run(&instance.radiuses));
+    result.print();
+
+    if let Some(best) = &result.best {
+        print_points(best.main_circle_radius, instance);
+
+        save_solution(
+            &Solution::new(best.main_circle_radius, best.circles.clone())
+                .with_instance(instance.name.clone())
+                .with_solver("pipeline")
+                .with_pipeline(pipeline)
+                .with_time(time)
+                .with_trace(
+                    result
+                        .reports
+                        .iter()
+                        .map(|report| report.best_main_circle_radius)
+                        .filter(|r| *r != FloatType::MAX)
+                        .collect(),
+                ),
+            &workspace()
+                .solutions
+                .join(format!("pipeline-{}.json", instance.name)),
+        );
+    }
+
+    result
+}
@@ -6,6 +6,7 @@ use crate::packing::{self};
 use crate::ralgo::dichotomy_step_ralgo::dichotomy_step_ralgo;
 use crate::ralgo::ralgo_params::RalgoParams;
 use crate::utils::{measure_time, FloatType};
+use crate::workspace::{save_workbook, workspace};
 use crate::{circle::Circle, point::Point};
 use rust_xlsxwriter::{column_number_to_name, Format, Formula, Workbook};
 use std::sync::{Arc, Mutex};
@@ -112,12 +113,12 @@ pub fn random_all_cases(
             .ok();
     }
 
-    let number_of_tests = fs::read_dir("./input/")?
+    let number_of_tests = fs::read_dir(&workspace().input)?
         .map(|res| res.map(|e| e.path()))
         .collect::<Result<Vec<_>, io::Error>>()?
         .len();
 
-    (1..=50).into_par_iter().for_each(|test_number| {
+    (1..=50).into_par_iter().try_for_each(|test_number| -> io::Result<()> {
         println!("Test {}", test_number);
         let rng = Arc::clone(&rng);
 
@@ -128,8 +129,8 @@ pub fn random_all_cases(
             .write(test_number, 0, test_number)
             .ok();
 
-        let (_, radiuses) = get_input_data(test_number);
-        let jury_answer = get_jury_answer(test_number);
+        let (_, radiuses) = get_input_data(test_number)?;
+        let jury_answer = get_jury_answer(test_number)?;
 
         // generate start values
         let main_circle_radius: FloatType =
@@ -163,7 +164,9 @@ pub fn random_all_cases(
                 &cell_format,
             );
         }
-    });
+
+        Ok(())
+    })?;
 
     let mut col: u16 = 2;
     while col < (algorithm_params.len() * 4 + 1) as u16 {
@@ -182,13 +185,16 @@ pub fn random_all_cases(
         col += 2;
     }
 
+    write_step_strategy(&worksheet, (number_of_tests + 2) as u32, ralgo_params, &cell_format);
+
     worksheet.lock().unwrap().autofit();
 
-    workbook
-        .save(format!(
-            "./results/random/random-result-multi (density = {density:.5}).xlsx"
-        ))
-        .ok();
+    save_workbook(
+        &mut workbook,
+        workspace()
+            .random_results
+            .join(format!("random-result-multi (density = {density:.5}).xlsx")),
+    )?;
 
     Ok(())
 }
@@ -12,13 +12,11 @@ use std::
 
 fn generate_random_arrangement(
     main_circle_radius: FloatType,
-    rng: &Arc<Mutex<StdRng>>,
+    rng: &mut StdRng,
     radiuses: &Vec<FloatType>,
 ) -> (Vec<Circle>, FloatType) {
     let mut circles = vec![];
     for i in 0..radiuses.len() {
-        let mut rng = rng.lock().unwrap();
-
         let (mut x, mut y);
         loop {
             (x, y) = (
@@ -60,18 +58,25 @@ fn get_updated_main_cirlce_radius(circles: &Vec<Circle>, r: FloatType) -> FloatT
         .unwrap();
 }
 
+/// Best valid packing of `dichotomy_step_ralgo` from random starts over all parameters, with
+/// the best `R` after every combination of parameters which has found a valid packing and
+/// r-algorithm parameters of the best packing. Starts are generated from `seed` before the
+/// parallel launches, so the seed reproduces the result.
 pub fn random_single_case_console(
     test_number: u32,
     launches: usize,
+    seed: u64,
     algorithm_params: &[(bool, FloatType)],
     alpha_q1_pairs: &Vec<(FloatType, FloatType)>,
-) -> (FloatType, Vec<Circle>) {
-    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+) -> (FloatType, Vec<Circle>, Vec<FloatType>, RalgoParams) {
+    let mut rng = StdRng::seed_from_u64(seed);
     let radiuses = (1..=test_number).map(|x| x as FloatType).collect::<Vec<_>>();
     let gen_main_circle_radius: FloatType = radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
 
     let answer_main_circle_radius = Arc::new(Mutex::new(FloatType::MAX));
     let answer_circles = Arc::new(Mutex::new(Vec::<Circle>::new()));
+    let answer_ralgo_params = Arc::new(Mutex::new(RalgoParams::default()));
+    let mut trace = Vec::new();
 
     for (alpha, q1) in alpha_q1_pairs {
         for (reset_step, eps) in algorithm_params {
@@ -81,13 +86,11 @@ pub fn random_single_case_console(
                 .with_max_iterations(100_000);
             // println!("Generate with ralgo params = {ralgo_params:?}");
 
-            (1..=launches).into_par_iter().for_each(|launch| {
-                // println!("Launch: {launch}");
-
-                let rng = Arc::clone(&rng);
+            let starts: Vec<(Vec<Circle>, FloatType)> = (0..launches)
+                .map(|_| generate_random_arrangement(gen_main_circle_radius, &mut rng, &radiuses))
+                .collect();
 
-                let (circles, r) =
-                    generate_random_arrangement(gen_main_circle_radius, &rng, &radiuses);
+            starts.into_par_iter().for_each(|(circles, r)| {
                 let updated_main_circle_radius = get_updated_main_cirlce_radius(&circles, r);
 
                 // get result of dichotomy algorithm
@@ -106,13 +109,24 @@ pub fn random_single_case_console(
                 {
                     *answer_main_circle_radius = new_main_circle_radius;
                     *answer_circles.lock().unwrap() = new_circles;
+                    *answer_ralgo_params.lock().unwrap() = ralgo_params;
                 }
             });
+
+            let best_main_circle_radius = *answer_main_circle_radius.lock().unwrap();
+            if best_main_circle_radius != FloatType::MAX {
+                trace.push(best_main_circle_radius);
+            }
         }
     }
 
     // (0.0, Vec::<Circle>::new())
-    let answer = (*answer_main_circle_radius.lock().unwrap(), answer_circles.lock().unwrap().clone());
+    let answer = (
+        *answer_main_circle_radius.lock().unwrap(),
+        answer_circles.lock().unwrap().clone(),
+        trace,
+        *answer_ralgo_params.lock().unwrap(),
+    );
 
     answer
 }
@@ -1,4 +1,4 @@
-use super::utils::{calculate_points, get_input_data, get_jury_answer};
+use super::utils::{calculate_points, get_input_data, get_jury_answer, write_step_strategy};
 use crate::{
     circle::Circle,
     packing,
@@ -7,6 +7,7 @@ use crate::{
         dichotomy_step_ralgo::dichotomy_step_ralgo_result_with_iterations,
         ralgo_params::RalgoParams,
     }, utils::FloatType,
+    workspace::{save_workbook, workspace},
 };
 use rand::{rngs::StdRng, Rng, SeedableRng};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
@@ -142,8 +143,8 @@ pub fn random_single_case_iterations(
     alpha_q1_pairs: &Vec<(FloatType, FloatType)>,
 ) -> io::Result<()> {
     let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
-    let (_, radiuses) = get_input_data(test_number as u32);
-    let jury_answer = get_jury_answer(test_number as u32);
+    let (_, radiuses) = get_input_data(test_number as u32)?;
+    let jury_answer = get_jury_answer(test_number as u32)?;
 
     let gen_main_circle_radius: FloatType = radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
 
@@ -330,14 +331,17 @@ pub fn random_single_case_iterations(
             col += HEADING_NAMES.len() as u16;
         }
 
+        write_step_strategy(&worksheet, (last_row_index + 1) as u32, &ralgo_params, &cell_format);
+
         worksheet.lock().unwrap().autofit();
     }
 
-    workbook
-        .save(format!(
-            "./results/random/random-single-result-test-{test_number}.xlsx"
-        ))
-        .ok();
+    save_workbook(
+        &mut workbook,
+        workspace()
+            .random_results
+            .join(format!("random-single-result-test-{test_number}.xlsx")),
+    )?;
 
     Ok(())
 }
@@ -0,0 +1,88 @@
+use std::path::Path;
+
+use crate::{
+    instance::InstanceError,
+    pipeline::{Candidate, Pipeline},
+    solution::Solution,
+    utils::{measure_time, FloatType},
+};
+
+/// Run `pipeline` (e.g. `Pipeline::refinement()`) starting from the packing of the solution
+/// file (`.json` or packomania `.txt`) and report whether `R` improved. An improved packing
+/// is saved next to the file as `<name>-refined.json`. Returns the better of two solutions.
+pub fn refine_solution_console(
+    path: impl AsRef<Path>,
+    pipeline: &Pipeline,
+) -> Result<Solution, InstanceError> {
+    let path = path.as_ref();
+    let solution = Solution::read(path)?;
+    let initial_is_valid = solution.is_valid();
+    println!(
+        "Initial R = {}, valid = {initial_is_valid}",
+        solution.main_circle_radius
+    );
+
+    let radiuses = solution.circles.iter().map(|c| c.radius).collect();
+    let start = Candidate::new(
+        solution.main_circle_radius,
+        solution.circles.clone(),
+        path.display().to_string(),
+    );
+    let (time, result) = measure_time(|| pipeline.run_from(&radiuses, vec![start]));
+    result.print();
+
+    let best = match result.best {
+        Some(best)
+            if !initial_is_valid || best.main_circle_radius < solution.main_circle_radius =>
+        {
+            best
+        }
+        _ => {
+            println!("R is not improved");
+            return Ok(solution);
+        }
+    };
+
+    if initial_is_valid {
+        println!(
+            "R improved: {} -> {} (by {:e})",
+            solution.main_circle_radius,
+            best.main_circle_radius,
+            solution.main_circle_radius - best.main_circle_radius
+        );
+    } else {
+        println!("Packing is made valid with R = {}", best.main_circle_radius);
+    }
+
+    let refined = Solution::new(best.main_circle_radius, best.circles)
+        .with_instance(solution.instance.clone())
+        .with_solver("refine")
+        .with_setting("source", path.display())
+        .with_pipeline(pipeline)
+        .with_time(time)
+        .with_trace(
+            std::iter::once(solution.main_circle_radius)
+                .chain(
+                    result
+                        .reports
+                        .iter()
+                        .map(|report| report.best_main_circle_radius)
+                        .filter(|r| *r != FloatType::MAX),
+                )
+                .collect(),
+        );
+
+    let refined_path = path.with_file_name(format!(
+        "{}-refined.json",
+        path.file_stem().unwrap_or_default().to_string_lossy()
+    ));
+    match refined.write(&refined_path) {
+        Ok(()) => println!("Refined solution is saved to {}", refined_path.display()),
+        Err(error) => println!(
+            "Failed to save refined solution to {}: {error}",
+            refined_path.display()
+        ),
+    }
+
+    Ok(refined)
+}
@@ -0,0 +1,93 @@
+use crate::{
+    circle::Circle,
+    packing::is_valid_pack,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    random_arrangement::generate_random_arrangement,
+    utils::{measure_time, FloatType},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::{Arc, Mutex};
+
+/// Tolerances of r-algorithm in `f32`.
+const SCREENING_TOLERANCE: FloatType = 1e-4;
+/// Smallest relative decrease of `R` accepted by dichotomy in `f32`.
+const SCREENING_EPS: FloatType = 1e-5;
+
+/// Screen many random launches with r-algorithm in `f32`, then refine the `candidates`
+/// best of them in `FloatType` and return the best valid packing.
+///
+/// Only r-algorithm and `is_valid_pack` are generic, the request for generic number types
+/// was narrowed to them (see `FloatType`): random starts are generated in `FloatType` and
+/// cast to `f32`, the packing heuristic has no `f32` version.
+pub fn screening_single_case_console(
+    test_number: u32,
+    launches: usize,
+    candidates: usize,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (FloatType, Vec<Circle>) {
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+    let radiuses = (1..=test_number)
+        .map(|x| x as FloatType)
+        .collect::<Vec<_>>();
+    let gen_main_circle_radius: FloatType =
+        radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
+
+    // default tolerances are close to f32 precision, so r-algorithm and dichotomy would
+    // keep going on rounding noise
+    let screening_params = ralgo_params
+        .with_epsx(SCREENING_TOLERANCE)
+        .with_epsg(SCREENING_TOLERANCE);
+
+    let (screening_time, mut screened) = measure_time(|| {
+        (0..launches)
+            .into_par_iter()
+            .map(|_| {
+                let circles: Vec<Circle<f32>> = generate_random_arrangement(
+                    gen_main_circle_radius,
+                    &radiuses,
+                    &mut *rng.lock().unwrap(),
+                )
+                .iter()
+                .map(|c| c.cast())
+                .collect();
+
+                dichotomy_step_ralgo(
+                    gen_main_circle_radius as f32,
+                    &circles,
+                    reset_step,
+                    eps.max(SCREENING_EPS),
+                    &screening_params,
+                )
+            })
+            .collect::<Vec<(f32, Vec<Circle<f32>>)>>()
+    });
+    screened.sort_by(|a, b| a.0.total_cmp(&b.0));
+    screened.truncate(candidates);
+
+    let (refinement_time, answer) = measure_time(|| {
+        screened
+            .into_par_iter()
+            .map(|(main_circle_radius, circles)| {
+                dichotomy_step_ralgo(
+                    main_circle_radius as FloatType,
+                    &circles.iter().map(|c| c.cast()).collect(),
+                    reset_step,
+                    eps,
+                    ralgo_params,
+                )
+            })
+            .filter(|(main_circle_radius, circles)| is_valid_pack(*main_circle_radius, circles))
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .unwrap_or((FloatType::MAX, Vec::new()))
+    });
+
+    println!(
+        "Screening: {launches} launches in f32 took {screening_time:.3}s, refinement of {candidates} candidates took {refinement_time:.3}s, R = {}",
+        answer.0
+    );
+
+    answer
+}
@@ -0,0 +1,198 @@
+use super::utils::calculate_points;
+use crate::{
+    instance::Instance,
+    packing,
+    ralgo::ralgo_result::RalgoResult,
+    random_arrangement::{enclosing_radius, generate_random_arrangement},
+    solver::Solver,
+    utils::{measure_time, FloatType},
+    workspace::{save_workbook, workspace},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use rust_xlsxwriter::{column_number_to_name, Format, Formula, Workbook, Worksheet};
+use std::{
+    io::{self},
+    sync::{Arc, Mutex},
+};
+
+const HEADING_NAMES: [&str; 7] = [
+    "R",
+    "Points",
+    "Is valid?",
+    "Outer calls",
+    "Iterations",
+    "calcfg_calls",
+    "Time",
+];
+
+fn get_table_headings(solvers: &[Box<dyn Solver>]) -> Vec<String> {
+    let mut headings: Vec<String> = vec!["Launch", "R_gen", "R"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    for solver in solvers {
+        for i in 0..HEADING_NAMES.len() {
+            headings.push(format!("{} {}", &HEADING_NAMES[i], solver.name()));
+        }
+    }
+
+    return headings;
+}
+
+fn write_row_block(
+    worksheet: &Arc<Mutex<&mut Worksheet>>,
+    row: u32,
+    col: u16,
+    result: &RalgoResult,
+    points: FloatType,
+    time: FloatType,
+    format: &Format,
+) {
+    let mut worksheet = worksheet.lock().unwrap();
+
+    worksheet
+        .write_with_format(row, col, result.main_circle_radius, &format)
+        .ok();
+    worksheet
+        .write_with_format(row, col + 1, points, &format)
+        .ok();
+    worksheet
+        .write_with_format(
+            row,
+            col + 2,
+            packing::is_valid_pack(result.main_circle_radius, &result.circles),
+            &format,
+        )
+        .ok();
+    worksheet
+        .write_with_format(row, col + 3, result.ralgo_calls, &format)
+        .ok();
+    worksheet
+        .write_with_format(row, col + 4, result.iterations, &format)
+        .ok();
+    worksheet
+        .write_with_format(row, col + 5, result.calcfg_calls, &format)
+        .ok();
+    worksheet
+        .write_with_format(row, col + 6, time, &format)
+        .ok();
+}
+
+/// Run every solver from the same random arrangements and put results side by side, points
+/// are counted against the known best `R` of the instance.
+pub fn solvers_single_case(
+    instance: &Instance,
+    launches: usize,
+    solvers: &[Box<dyn Solver>],
+) -> io::Result<()> {
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+    let radiuses = &instance.radiuses;
+    let jury_answer = instance.known_best.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("Instance {} has no known best R", instance.name),
+        )
+    })?;
+
+    let gen_main_circle_radius: FloatType =
+        radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
+
+    let mut workbook: Workbook = Workbook::new();
+    let worksheet = Arc::new(Mutex::new(workbook.add_worksheet()));
+    worksheet.lock().unwrap().set_name("solvers").ok();
+
+    let cell_format = Format::new().set_align(rust_xlsxwriter::FormatAlign::Center);
+
+    // setup headings
+    for (col, data) in get_table_headings(solvers).iter().enumerate() {
+        worksheet
+            .lock()
+            .unwrap()
+            .write_with_format(0, col as u16, data, &cell_format)
+            .ok();
+    }
+
+    (1..=launches).into_par_iter().for_each(|launch| {
+        println!("Launch: {launch}");
+
+        let worksheet = Arc::clone(&worksheet);
+        let rng = Arc::clone(&rng);
+
+        let circles = generate_random_arrangement(
+            gen_main_circle_radius,
+            radiuses,
+            &mut *rng.lock().unwrap(),
+        );
+        let updated_main_circle_radius = enclosing_radius(&circles);
+
+        {
+            let mut worksheet = worksheet.lock().unwrap();
+            worksheet
+                .write_with_format(launch as u32, 0, launch as u32, &cell_format)
+                .ok();
+            worksheet
+                .write_with_format(launch as u32, 1, gen_main_circle_radius, &cell_format)
+                .ok();
+            worksheet
+                .write_with_format(launch as u32, 2, updated_main_circle_radius, &cell_format)
+                .ok();
+        }
+
+        for (index, solver) in solvers.iter().enumerate() {
+            let (time, result) =
+                measure_time(|| solver.solve(updated_main_circle_radius, &circles));
+            let points = calculate_points(result.main_circle_radius, jury_answer);
+
+            write_row_block(
+                &worksheet,
+                launch as u32,
+                (index * HEADING_NAMES.len() + 3) as u16,
+                &result,
+                points,
+                time,
+                &cell_format,
+            );
+        }
+    });
+
+    let (first_row_index, last_row_index) = (2, launches + 1);
+    let generate_range = |column: String| -> String {
+        format!("{column}{first_row_index}:{column}{last_row_index}")
+    };
+
+    for index in 0..solvers.len() {
+        let col = (index * HEADING_NAMES.len() + 3) as u16;
+        let radius_range = generate_range(column_number_to_name(col));
+        let validation_range = generate_range(column_number_to_name(col + 2));
+
+        let best_result_row_formula =
+            format!("MATCH(MINIFS({radius_range}; {validation_range}; TRUE()); {radius_range}; 0)");
+
+        // best valid launch for every column except validation
+        for offset in (0..HEADING_NAMES.len() as u16).filter(|offset| *offset != 2) {
+            let range = generate_range(column_number_to_name(col + offset));
+            worksheet
+                .lock()
+                .unwrap()
+                .write_with_format(
+                    last_row_index as u32,
+                    col + offset,
+                    Formula::new(format!("=INDEX({range}; {best_result_row_formula}; 0)")),
+                    &cell_format,
+                )
+                .ok();
+        }
+    }
+
+    worksheet.lock().unwrap().autofit();
+
+    save_workbook(
+        &mut workbook,
+        workspace()
+            .random_results
+            .join(format!("solvers-single-result-test-{}.xlsx", instance.name)),
+    )?;
+
+    Ok(())
+}
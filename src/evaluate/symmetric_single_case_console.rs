@@ -0,0 +1,76 @@
+use crate::{
+    circle::Circle,
+    instance::Instance,
+    packing::is_valid_pack,
+    ralgo::{
+        dichotomy_step_ralgo::dichotomy_step_ralgo_symmetric, ralgo_params::RalgoParams,
+        symmetry::Symmetry,
+    },
+    random_arrangement::generate_random_arrangement,
+    utils::{measure_time, FloatType},
+};
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::sync::{Arc, Mutex};
+
+/// Random launches of r-algorithm on the instance keeping `symmetry` of the
+/// packing. Returns the best valid packing.
+pub fn symmetric_single_case_console(
+    instance: &Instance,
+    symmetry: &Symmetry,
+    launches: usize,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Result<(FloatType, Vec<Circle>), String> {
+    let rng = Arc::new(Mutex::new(StdRng::seed_from_u64(0)));
+    let radiuses = &instance.radiuses;
+    let gen_main_circle_radius: FloatType =
+        radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
+
+    let orbits = symmetry.orbits(radiuses)?;
+    println!(
+        "Symmetry {symmetry}: {} variables instead of {}",
+        orbits.variables() + 1,
+        2 * radiuses.len() + 1
+    );
+
+    let (time, answer) = measure_time(|| {
+        (0..launches)
+            .into_par_iter()
+            .map(|_| {
+                let circles = generate_random_arrangement(
+                    gen_main_circle_radius,
+                    radiuses,
+                    &mut *rng.lock().unwrap(),
+                );
+
+                dichotomy_step_ralgo_symmetric(
+                    gen_main_circle_radius,
+                    &circles,
+                    symmetry,
+                    reset_step,
+                    eps,
+                    ralgo_params,
+                )
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(|results| {
+                results
+                    .into_iter()
+                    .filter(|(main_circle_radius, circles)| {
+                        is_valid_pack(*main_circle_radius, circles)
+                    })
+                    .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                    .unwrap_or((FloatType::MAX, Vec::new()))
+            })
+    });
+    let answer = answer?;
+
+    println!(
+        "Symmetric search: {launches} launches took {time:.3}s, R = {}",
+        answer.0
+    );
+
+    Ok(answer)
+}
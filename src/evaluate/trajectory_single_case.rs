@@ -0,0 +1,66 @@
+use crate::{
+    instance::Instance,
+    plot::{draw_animation, PlotParams},
+    ralgo::{
+        ralgo_params::RalgoParams,
+        trajectory::{dichotomy_step_ralgo_trajectory, Frame},
+    },
+    random_arrangement::generate_random_arrangement,
+    utils::FloatType,
+};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Compaction of a random packing of the instance by `dichotomy_step_ralgo`,
+/// drawn as animation (`.gif` path of `plot_params`) or numbered frames. Every
+/// r-algorithm run gives a frame, and every `k`-th iteration too when `every_iterations`
+/// is set.
+pub fn trajectory_single_case(
+    instance: &Instance,
+    seed: u64,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+    every_iterations: Option<usize>,
+    plot_params: &PlotParams,
+    frame_delay: u32,
+) -> Result<Vec<Frame>, String> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let radiuses = &instance.radiuses;
+    let gen_main_circle_radius: FloatType =
+        radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>().sqrt() * 1.2;
+
+    let circles = generate_random_arrangement(gen_main_circle_radius, radiuses, &mut rng);
+    let frames = dichotomy_step_ralgo_trajectory(
+        gen_main_circle_radius,
+        &circles,
+        reset_step,
+        eps,
+        ralgo_params,
+        every_iterations,
+    )?;
+
+    draw_animation(
+        &frames
+            .iter()
+            .map(|frame| {
+                (
+                    frame.main_circle_radius,
+                    frame.circles.clone(),
+                    frame.caption(),
+                )
+            })
+            .collect(),
+        plot_params,
+        frame_delay,
+    )?;
+
+    let last = frames.last().unwrap();
+    println!(
+        "{} frames are saved to {}, R = {}",
+        frames.len(),
+        plot_params.path,
+        last.main_circle_radius
+    );
+
+    Ok(frames)
+}
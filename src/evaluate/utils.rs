@@ -1,53 +1,24 @@
-use std::{
-    fs::File,
-    io::{BufRead, BufReader},
-    sync::{Arc, Mutex},
-};
+use std::sync::{Arc, Mutex};
 
 use rust_xlsxwriter::{Format, Worksheet};
 
-use crate::utils::FloatType;
-
-fn get_buf_reader(file_name: String) -> BufReader<File> {
-    let file = File::open(file_name).expect("Failed to open file");
-    BufReader::new(file)
-}
-
-pub fn get_input_data(test_number: u32) -> (usize, Vec<FloatType>) {
-    let file_name = format!("./input/input{:03}.txt", test_number);
-    let reader = get_buf_reader(file_name);
-
-    let mut lines = reader.lines();
-    let first_line = lines.next().expect("Empty file").unwrap();
-    let n: usize = first_line.trim().parse().expect("Invalid number");
-
-    let mut radiuses: Vec<FloatType> = Vec::with_capacity(n);
-    for line in lines {
-        let radius: FloatType = line
-            .expect("Failed to read line")
-            .trim()
-            .parse()
-            .expect("Invalid number");
+use crate::{
+    instance::{read_known_best, Instance, InstanceError, InstanceFormat},
+    ralgo::ralgo_params::RalgoParams,
+    utils::FloatType,
+    workspace::workspace,
+};
 
-        radiuses.push(radius);
-    }
+/// Radii of test `inputNNN.txt` of the workspace, the error has file and line of malformed
+/// input.
+pub fn get_input_data(test_number: u32) -> Result<(usize, Vec<FloatType>), InstanceError> {
+    let instance = Instance::read_as(workspace().input_file(test_number), InstanceFormat::Input)?;
 
-    return (n, radiuses);
+    Ok((instance.radiuses.len(), instance.radiuses))
 }
 
-pub fn get_jury_answer(test_number: u32) -> FloatType {
-    let file_name = format!("./output/out{:03}.txt", test_number);
-    let reader = get_buf_reader(file_name);
-
-    reader
-        .lines()
-        .next()
-        .unwrap()
-        .unwrap()
-        .trim_end()
-        .to_string()
-        .parse::<FloatType>()
-        .expect("Valid float jury answer.")
+pub fn get_jury_answer(test_number: u32) -> Result<FloatType, InstanceError> {
+    read_known_best(workspace().answer_file(test_number))
 }
 
 pub fn write_row_block(
@@ -82,6 +53,36 @@ pub fn write_row_block(
         .ok();
 }
 
+/// Record step strategy of dichotomy under the table, results depend on it.
+pub fn write_step_strategy(
+    worksheet: &Arc<Mutex<&mut Worksheet>>,
+    row: u32,
+    ralgo_params: &RalgoParams,
+    format: &Format,
+) {
+    worksheet
+        .lock()
+        .unwrap()
+        .write_with_format(row, 0, "Step strategy", &format)
+        .ok();
+    worksheet
+        .lock()
+        .unwrap()
+        .write_with_format(row, 1, ralgo_params.step_strategy.to_string(), &format)
+        .ok();
+}
+
 pub fn calculate_points(answer: FloatType, jury_answer: FloatType) -> FloatType {
     ((2.0 - (answer / jury_answer)) * 100.0).max(0.0)
 }
+
+/// Points of `answer` against the known best `R` of the instance, nothing is printed for
+/// instances without one.
+pub fn print_points(answer: FloatType, instance: &Instance) {
+    if let Some(known_best) = instance.known_best {
+        println!(
+            "Known best = {known_best}, points = {}",
+            calculate_points(answer, known_best)
+        );
+    }
+}
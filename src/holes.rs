@@ -0,0 +1,184 @@
+use crate::{circle::Circle, point::Point, utils::FloatType};
+
+/// Largest empty circle around a point of free space inside the main circle.
+#[derive(Debug, Clone, Copy)]
+pub struct Hole {
+    pub center: Point,
+    pub radius: FloatType,
+}
+
+/// Radius of the largest empty circle with center `point`, negative inside circles
+/// or outside the main circle.
+fn clearance(point: &Point, main_circle_radius: FloatType, circles: &Vec<Circle>) -> FloatType {
+    circles
+        .iter()
+        .map(|c| {
+            let center = c.center.unwrap();
+            ((point.x - center.x).powi(2) + (point.y - center.y).powi(2)).sqrt() - c.radius
+        })
+        .fold(
+            main_circle_radius - (point.x.powi(2) + point.y.powi(2)).sqrt(),
+            FloatType::min,
+        )
+}
+
+/// Move hole center while its radius grows, halving the step on failure.
+fn refine_hole(
+    hole: Hole,
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    step: FloatType,
+) -> Hole {
+    let mut hole = hole;
+    let mut step = step;
+    while step > hole.radius * 1e-6 {
+        let improved = [(1.0, 0.0), (-1.0, 0.0), (0.0, 1.0), (0.0, -1.0)]
+            .iter()
+            .map(|(dx, dy)| {
+                let center = Point {
+                    x: hole.center.x + dx * step,
+                    y: hole.center.y + dy * step,
+                };
+                Hole {
+                    radius: clearance(&center, main_circle_radius, circles),
+                    center,
+                }
+            })
+            .filter(|candidate| candidate.radius > hole.radius)
+            .max_by(|a, b| a.radius.total_cmp(&b.radius));
+
+        match improved {
+            Some(candidate) => hole = candidate,
+            None => step /= 2.0,
+        }
+    }
+
+    hole
+}
+
+/// Holes of the packing from free space sampled on a grid with `samples` points along
+/// the diameter of main circle. Samples are taken from the largest clearance down, the
+/// ones inside already found holes are skipped, the rest are refined into local maxima.
+/// Holes are sorted by radius, the largest first.
+pub fn find_holes(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    samples: usize,
+) -> Vec<Hole> {
+    let step = 2.0 * main_circle_radius / samples as FloatType;
+
+    let mut free_space: Vec<Hole> = (0..samples)
+        .flat_map(|i| (0..samples).map(move |j| (i, j)))
+        .map(|(i, j)| Point {
+            x: -main_circle_radius + (i as FloatType + 0.5) * step,
+            y: -main_circle_radius + (j as FloatType + 0.5) * step,
+        })
+        .map(|center| Hole {
+            radius: clearance(&center, main_circle_radius, circles),
+            center,
+        })
+        .filter(|hole| hole.radius > 0.0)
+        .collect();
+    free_space.sort_by(|a, b| b.radius.total_cmp(&a.radius));
+
+    let mut holes: Vec<Hole> = Vec::new();
+    for sample in free_space {
+        let is_covered = holes.iter().any(|hole| {
+            (sample.center.x - hole.center.x).powi(2) + (sample.center.y - hole.center.y).powi(2)
+                < hole.radius.powi(2)
+        });
+        if is_covered {
+            continue;
+        }
+
+        let hole = refine_hole(sample, main_circle_radius, circles, step / 2.0);
+        let is_known = holes.iter().any(|known| {
+            (known.center.x - hole.center.x).powi(2) + (known.center.y - hole.center.y).powi(2)
+                < (known.radius * 1e-3).powi(2)
+        });
+        if !is_known {
+            holes.push(hole);
+        }
+    }
+
+    holes.sort_by(|a, b| b.radius.total_cmp(&a.radius));
+    holes
+}
+
+/// Indices of circles touching the main circle, i.e. with gap smaller than
+/// `tolerance * R`.
+pub fn boundary_circles(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    tolerance: FloatType,
+) -> Vec<usize> {
+    circles
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| {
+            let center = c.center.unwrap();
+            main_circle_radius - (center.x.powi(2) + center.y.powi(2)).sqrt() - c.radius
+                < tolerance * main_circle_radius
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Jump move: take circle `index` and put it into the center of `hole`. When the hole
+/// is smaller than the circle, the neighbours overlap it and local search has to push
+/// them apart.
+pub fn jump(circles: &Vec<Circle>, index: usize, hole: &Hole) -> Vec<Circle> {
+    let mut circles = circles.clone();
+    circles[index].center = Some(hole.center);
+    circles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circle(radius: FloatType, x: FloatType, y: FloatType) -> Circle {
+        Circle::new(radius, Point { x, y })
+    }
+
+    #[test]
+    fn holes_between_two_circles() {
+        // hole touching both circles and the main circle: center (0, ±4/3), radius 2/3
+        let circles = vec![circle(1.0, -1.0, 0.0), circle(1.0, 1.0, 0.0)];
+        let holes = find_holes(2.0, &circles, 100);
+
+        assert!(holes.len() >= 2);
+        assert!(holes
+            .windows(2)
+            .all(|pair| pair[0].radius >= pair[1].radius));
+        for hole in &holes[..2] {
+            assert!((hole.radius - 2.0 / 3.0).abs() < 1e-3);
+            assert!(hole.center.x.abs() < 1e-2);
+            assert!((hole.center.y.abs() - 4.0 / 3.0).abs() < 1e-2);
+        }
+        for hole in &holes {
+            assert!(hole.radius > 0.0);
+            assert!(hole.radius <= clearance(&hole.center, 2.0, &circles) + 1e-12);
+        }
+    }
+
+    #[test]
+    fn no_holes_in_filled_main_circle() {
+        assert!(find_holes(1.0, &vec![circle(1.0, 0.0, 0.0)], 50).is_empty());
+    }
+
+    #[test]
+    fn jump_moves_only_one_circle() {
+        let circles = vec![circle(1.0, -1.0, 0.0), circle(0.5, 1.0, 0.0)];
+        let hole = Hole {
+            center: Point { x: 0.0, y: 1.5 },
+            radius: 0.5,
+        };
+        let moved = jump(&circles, 1, &hole);
+
+        assert_eq!(moved[0].center.unwrap().x, -1.0);
+        assert_eq!(moved[1].radius, 0.5);
+        assert_eq!(moved[1].center.unwrap().x, 0.0);
+        assert_eq!(moved[1].center.unwrap().y, 1.5);
+    }
+}
@@ -0,0 +1,293 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+
+use crate::{
+    circle::Circle,
+    holes::{boundary_circles, find_holes, jump},
+    packing::{is_valid_pack, restore_pack},
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    random_arrangement::random_start,
+    utils::FloatType,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct IlsParams {
+    /// Independent searches run in parallel, each from its own random arrangement.
+    pub trajectories: usize,
+    /// Perturbations (followed by local search) of every trajectory.
+    pub iterations: usize,
+    /// Jump moves in one perturbation.
+    pub jumps: usize,
+    /// Jump goes into one of this number of the largest holes.
+    pub candidate_holes: usize,
+    /// Grid size of `find_holes`.
+    pub hole_samples: usize,
+    /// Circle is on the boundary when its gap to the main circle is smaller than this
+    /// part of `R`.
+    pub boundary_tolerance: FloatType,
+    pub density: FloatType,
+    pub reset_step: bool,
+    pub eps: FloatType,
+    pub ralgo_params: RalgoParams,
+    pub seed: u64,
+}
+
+impl Default for IlsParams {
+    fn default() -> Self {
+        IlsParams {
+            trajectories: 8,
+            iterations: 20,
+            jumps: 1,
+            candidate_holes: 3,
+            hole_samples: 100,
+            boundary_tolerance: 1e-4,
+            density: 0.6,
+            reset_step: false,
+            eps: 0.0,
+            ralgo_params: RalgoParams::default()
+                .with_alpha(1.5)
+                .with_q1(1.0)
+                .with_max_iterations(100_000),
+            seed: 0,
+        }
+    }
+}
+
+impl IlsParams {
+    pub fn with_trajectories(&self, trajectories: usize) -> Self {
+        IlsParams {
+            trajectories,
+            ..*self
+        }
+    }
+
+    pub fn with_iterations(&self, iterations: usize) -> Self {
+        IlsParams {
+            iterations,
+            ..*self
+        }
+    }
+
+    pub fn with_jumps(&self, jumps: usize) -> Self {
+        IlsParams { jumps, ..*self }
+    }
+
+    pub fn with_hole_samples(&self, hole_samples: usize) -> Self {
+        IlsParams {
+            hole_samples,
+            ..*self
+        }
+    }
+
+    pub fn with_reset_step(&self, reset_step: bool) -> Self {
+        IlsParams {
+            reset_step,
+            ..*self
+        }
+    }
+
+    pub fn with_ralgo_params(&self, ralgo_params: RalgoParams) -> Self {
+        IlsParams {
+            ralgo_params,
+            ..*self
+        }
+    }
+
+    pub fn with_seed(&self, seed: u64) -> Self {
+        IlsParams { seed, ..*self }
+    }
+}
+
+#[derive(Debug)]
+pub struct IlsResult {
+    /// `FloatType::MAX` when no trajectory found a valid packing.
+    pub main_circle_radius: FloatType,
+    /// Best valid packing of all trajectories, empty when there is none.
+    pub circles: Vec<Circle>,
+    /// Best `R` of every trajectory after every iteration, `FloatType::MAX` until the
+    /// trajectory finds a valid packing.
+    pub trajectories: Vec<Vec<FloatType>>,
+    /// Accepted perturbations of every trajectory.
+    pub accepted: Vec<usize>,
+}
+
+impl IlsResult {
+    pub fn print(&self) {
+        for (i, (history, accepted)) in self.trajectories.iter().zip(&self.accepted).enumerate() {
+            println!(
+                "Trajectory {}: R = {} -> {}, accepted jumps = {accepted}",
+                i + 1,
+                history.first().unwrap_or(&FloatType::MAX),
+                history.last().unwrap_or(&FloatType::MAX)
+            );
+        }
+        println!("Best R = {}", self.main_circle_radius);
+    }
+}
+
+/// `dichotomy_step_ralgo` followed by restoration of small overlaps left by it.
+fn local_search(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    params: &IlsParams,
+) -> (FloatType, Vec<Circle>) {
+    let (main_circle_radius, circles) = dichotomy_step_ralgo(
+        main_circle_radius,
+        circles,
+        params.reset_step,
+        params.eps,
+        &params.ralgo_params,
+    );
+
+    if is_valid_pack(main_circle_radius, &circles) {
+        return (main_circle_radius, circles);
+    }
+    restore_pack(&circles)
+}
+
+/// Jump moves: a random boundary circle goes into one of the largest holes. The largest
+/// boundary circle that fits into the hole is preferred.
+pub fn perturb(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    params: &IlsParams,
+    rng: &mut StdRng,
+) -> Vec<Circle> {
+    let mut circles = circles.clone();
+
+    for _ in 0..params.jumps {
+        let holes = find_holes(main_circle_radius, &circles, params.hole_samples);
+        let boundary = boundary_circles(main_circle_radius, &circles, params.boundary_tolerance);
+        if holes.is_empty() || boundary.is_empty() {
+            break;
+        }
+
+        let hole = holes[rng.gen_range(0..holes.len().min(params.candidate_holes))];
+        let index = boundary
+            .iter()
+            .filter(|&&i| circles[i].radius <= hole.radius)
+            .max_by(|&&a, &&b| circles[a].radius.total_cmp(&circles[b].radius))
+            .copied()
+            .unwrap_or_else(|| boundary[rng.gen_range(0..boundary.len())]);
+
+        circles = jump(&circles, index, &hole);
+    }
+
+    circles
+}
+
+/// Iterated local search from `circles`: perturbation with jump moves and local search,
+/// the result is accepted when it's valid and decreases `R`. Returns the best packing and
+/// `R` after every iteration, the best packing is empty with `FloatType::MAX` when no valid
+/// packing was found.
+pub fn iterated_local_search_from(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    params: &IlsParams,
+    rng: &mut StdRng,
+) -> (FloatType, Vec<Circle>, Vec<FloatType>, usize) {
+    let (mut current_main_circle_radius, mut current_circles) =
+        local_search(main_circle_radius, circles, params);
+    let (mut best_main_circle_radius, mut best_circles) =
+        if is_valid_pack(current_main_circle_radius, &current_circles) {
+            (current_main_circle_radius, current_circles.clone())
+        } else {
+            (FloatType::MAX, Vec::new())
+        };
+
+    let mut history = vec![best_main_circle_radius];
+    let mut accepted = 0;
+
+    for _ in 0..params.iterations {
+        let perturbed = perturb(current_main_circle_radius, &current_circles, params, rng);
+        let (new_main_circle_radius, new_circles) =
+            local_search(current_main_circle_radius, &perturbed, params);
+
+        if is_valid_pack(new_main_circle_radius, &new_circles)
+            && new_main_circle_radius < best_main_circle_radius
+        {
+            (current_main_circle_radius, best_main_circle_radius) =
+                (new_main_circle_radius, new_main_circle_radius);
+            current_circles = new_circles;
+            best_circles = current_circles.clone();
+            accepted += 1;
+        }
+        history.push(best_main_circle_radius);
+    }
+
+    (best_main_circle_radius, best_circles, history, accepted)
+}
+
+/// Iterated local search with jump moves from `trajectories` random arrangements.
+pub fn iterated_local_search(radiuses: &Vec<FloatType>, params: &IlsParams) -> IlsResult {
+    let results: Vec<(FloatType, Vec<Circle>, Vec<FloatType>, usize)> = (0..params.trajectories)
+        .into_par_iter()
+        .map(|trajectory| {
+            let mut rng = StdRng::seed_from_u64(params.seed.wrapping_add(trajectory as u64));
+            let (main_circle_radius, circles) = random_start(radiuses, params.density, &mut rng);
+
+            iterated_local_search_from(main_circle_radius, &circles, params, &mut rng)
+        })
+        .collect();
+
+    let (main_circle_radius, circles) = results
+        .iter()
+        .filter(|(_, circles, _, _)| !circles.is_empty())
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(main_circle_radius, circles, _, _)| (*main_circle_radius, circles.clone()))
+        .unwrap_or((FloatType::MAX, Vec::new()));
+
+    let (trajectories, accepted) = results
+        .into_iter()
+        .map(|(_, _, history, accepted)| (history, accepted))
+        .unzip();
+
+    IlsResult {
+        main_circle_radius,
+        circles,
+        trajectories,
+        accepted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> IlsParams {
+        IlsParams::default()
+            .with_trajectories(2)
+            .with_iterations(3)
+            .with_hole_samples(30)
+            .with_ralgo_params(RalgoParams::default().with_max_iterations(300))
+    }
+
+    #[test]
+    fn best_radius_never_increases() {
+        let radiuses = vec![1.0, 2.0, 3.0, 4.0, 5.0];
+        let result = iterated_local_search(&radiuses, &params());
+
+        assert_eq!(result.trajectories.len(), 2);
+        for history in &result.trajectories {
+            assert_eq!(history.len(), 4);
+            assert!(history.windows(2).all(|pair| pair[1] <= pair[0]));
+        }
+        if result.circles.is_empty() {
+            assert_eq!(result.main_circle_radius, FloatType::MAX);
+        } else {
+            assert!(is_valid_pack(result.main_circle_radius, &result.circles));
+            let best = result
+                .trajectories
+                .iter()
+                .filter_map(|h| h.last())
+                .fold(FloatType::MAX, |a, &b| a.min(b));
+            assert_eq!(result.main_circle_radius, best);
+        }
+    }
+
+    #[test]
+    fn seed_near_max_does_not_overflow() {
+        let result = iterated_local_search(&vec![1.0, 1.0], &params().with_seed(u64::MAX));
+        assert_eq!(result.trajectories.len(), 2);
+    }
+}
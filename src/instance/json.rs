@@ -0,0 +1,268 @@
+use crate::utils::FloatType;
+
+use super::value::{Cursor, ParseError, Value, ValueKind};
+
+fn parse_value(cursor: &mut Cursor) -> Result<Value, ParseError> {
+    cursor.skip_whitespace(true);
+    let line = cursor.line;
+
+    let kind = match cursor.peek() {
+        None => return Err(cursor.error("Expected value, found end of file")),
+        Some('{') => {
+            cursor.next();
+            let mut entries = Vec::new();
+            cursor.skip_whitespace(true);
+            if cursor.peek() == Some('}') {
+                cursor.next();
+            } else {
+                loop {
+                    cursor.skip_whitespace(true);
+                    let key = cursor.parse_string()?;
+                    cursor.skip_whitespace(true);
+                    cursor.expect(':')?;
+                    entries.push((key, parse_value(cursor)?));
+
+                    cursor.skip_whitespace(true);
+                    match cursor.next() {
+                        Some(',') => continue,
+                        Some('}') => break,
+                        _ => return Err(cursor.error("Expected ',' or '}' in object")),
+                    }
+                }
+            }
+            ValueKind::Object(entries)
+        }
+        Some('[') => {
+            cursor.next();
+            let mut values = Vec::new();
+            cursor.skip_whitespace(true);
+            if cursor.peek() == Some(']') {
+                cursor.next();
+            } else {
+                loop {
+                    values.push(parse_value(cursor)?);
+
+                    cursor.skip_whitespace(true);
+                    match cursor.next() {
+                        Some(',') => continue,
+                        Some(']') => break,
+                        _ => return Err(cursor.error("Expected ',' or ']' in array")),
+                    }
+                }
+            }
+            ValueKind::Array(values)
+        }
+        Some('"') => ValueKind::String(cursor.parse_string()?),
+        Some(c) if c == '-' || c.is_ascii_digit() => ValueKind::Number(cursor.parse_number()?),
+        Some(_) => {
+            let word = cursor.take_while(|c| c.is_ascii_alphabetic());
+            match word.as_str() {
+                "true" => ValueKind::Bool(true),
+                "false" => ValueKind::Bool(false),
+                "null" => ValueKind::Null,
+                _ => {
+                    return Err(cursor.error(format!(
+                        "Unexpected '{}'",
+                        if word.is_empty() {
+                            cursor.peek().unwrap().to_string()
+                        } else {
+                            word
+                        }
+                    )))
+                }
+            }
+        }
+    };
+
+    Ok(Value::new(kind, line))
+}
+
+pub fn parse_json(text: &str) -> Result<Value, ParseError> {
+    let mut cursor = Cursor::new(text);
+    let value = parse_value(&mut cursor)?;
+
+    cursor.skip_whitespace(true);
+    if let Some(c) = cursor.peek() {
+        return Err(cursor.error(format!("Unexpected '{c}' after the end of document")));
+    }
+
+    Ok(value)
+}
+
+fn escape(string: &str) -> String {
+    string
+        .chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\t' => "\\t".to_string(),
+            c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+/// JSON text of the value, arrays of numbers are kept on one line.
+pub fn to_json(value: &Value, indent: usize) -> String {
+    let padding = "  ".repeat(indent + 1);
+    let closing_padding = "  ".repeat(indent);
+
+    match &value.kind {
+        ValueKind::Null => "null".to_string(),
+        ValueKind::Bool(b) => b.to_string(),
+        ValueKind::Number(number) => number_to_json(*number),
+        ValueKind::String(string) => format!("\"{}\"", escape(string)),
+        ValueKind::Array(values) => {
+            if values
+                .iter()
+                .all(|v| matches!(v.kind, ValueKind::Number(_)))
+            {
+                let numbers: Vec<String> = values.iter().map(|v| to_json(v, indent)).collect();
+                return format!("[{}]", numbers.join(", "));
+            }
+            let values: Vec<String> = values
+                .iter()
+                .map(|v| format!("{padding}{}", to_json(v, indent + 1)))
+                .collect();
+            format!("[\n{}\n{closing_padding}]", values.join(",\n"))
+        }
+        ValueKind::Object(entries) => {
+            if entries.is_empty() {
+                return "{}".to_string();
+            }
+            let entries: Vec<String> = entries
+                .iter()
+                .map(|(key, v)| format!("{padding}\"{}\": {}", escape(key), to_json(v, indent + 1)))
+                .collect();
+            format!("{{\n{}\n{closing_padding}}}", entries.join(",\n"))
+        }
+    }
+}
+
+/// Shortest representation that reads back to the same number, JSON has no infinities.
+fn number_to_json(number: FloatType) -> String {
+    if !number.is_finite() {
+        return "null".to_string();
+    }
+    format!("{number:?}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: &Value) -> FloatType {
+        value.as_number("test").unwrap()
+    }
+
+    #[test]
+    fn parses_every_kind_of_value() {
+        let text = r#"
+{
+  "name": "test \"1\"",
+  "empty_object": {},
+  "empty_array": [ ],
+  "flags": [true, false, null],
+  "numbers": [1, -2.5, 3e2],
+  "nested": {"a": [{"b": 1}]}
+}
+"#;
+        let value = parse_json(text).unwrap();
+
+        assert_eq!(value.line, 2);
+        assert_eq!(value.get("name").unwrap().as_str("name"), Ok("test \"1\""));
+        assert_eq!(
+            value.get("empty_object").unwrap().as_object("o"),
+            Ok(&vec![])
+        );
+        assert_eq!(value.get("empty_array").unwrap().as_array("a"), Ok(&vec![]));
+
+        let flags = value.get("flags").unwrap().as_array("flags").unwrap();
+        let kinds: Vec<ValueKind> = flags.iter().map(|v| v.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ValueKind::Bool(true),
+                ValueKind::Bool(false),
+                ValueKind::Null
+            ]
+        );
+
+        let numbers: Vec<FloatType> = value
+            .get("numbers")
+            .unwrap()
+            .as_array("numbers")
+            .unwrap()
+            .iter()
+            .map(number)
+            .collect();
+        assert_eq!(numbers, vec![1.0, -2.5, 300.0]);
+
+        let nested = value.get("nested").unwrap();
+        assert_eq!(nested.line, 8);
+        let b = nested.get("a").unwrap().as_array("a").unwrap()[0].get("b");
+        assert_eq!(number(b.unwrap()), 1.0);
+    }
+
+    #[test]
+    fn values_remember_their_lines() {
+        let value = parse_json("[\n1,\n\n  2\n]").unwrap();
+        let lines: Vec<usize> = value
+            .as_array("array")
+            .unwrap()
+            .iter()
+            .map(|v| v.line)
+            .collect();
+        assert_eq!(value.line, 1);
+        assert_eq!(lines, vec![2, 4]);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        for (text, line, message) in [
+            ("", 1, "Expected value, found end of file"),
+            (
+                "{\n  \"a\": 1\n  \"b\": 2\n}",
+                3,
+                "Expected ',' or '}' in object",
+            ),
+            ("[1,\n2\n3]", 3, "Expected ',' or ']' in array"),
+            ("{\n\"a\" 1}", 2, "Expected ':', found '1'"),
+            ("{\n\n  1: 2}", 3, "Expected '\"', found '1'"),
+            ("[\ntrue,\nyes]", 3, "Unexpected 'yes'"),
+            ("[\n  @]", 2, "Unexpected '@'"),
+            ("[1,\n-x]", 2, "Invalid number '-x'"),
+            ("{\"a\": \"b\n\"}", 1, "Unterminated string"),
+            ("{}\n\n{}", 3, "Unexpected '{' after the end of document"),
+            ("[1,\n", 2, "Expected value, found end of file"),
+        ] {
+            assert_eq!(
+                parse_json(text),
+                Err(ParseError::new(line, message)),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn written_json_reads_back() {
+        let text = r#"{"name": "a\tb\"c\\", "circles": [{"radius": 0.1, "x": -1e-17}], "empty": {}, "r": [1, 2.5]}"#;
+        let value = parse_json(text).unwrap();
+        let written = to_json(&value, 0);
+
+        let read_back = parse_json(&written).unwrap();
+        assert_eq!(to_json(&read_back, 0), written);
+        assert_eq!(
+            read_back.get("name").unwrap().as_str("name"),
+            Ok("a\tb\"c\\")
+        );
+        assert!(written.contains("\"r\": [1.0, 2.5]"));
+    }
+
+    #[test]
+    fn infinite_numbers_are_written_as_null() {
+        let value = Value::new(ValueKind::Number(FloatType::INFINITY), 1);
+        assert_eq!(to_json(&value, 0), "null");
+    }
+}
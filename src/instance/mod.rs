@@ -0,0 +1,424 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{
+    circle::Circle, ellipse::EllipseContainer, point::Point, utils::FloatType,
+    workspace::workspace,
+};
+
+use self::value::{ParseError, Value, ValueKind};
+
+pub mod json;
+pub mod text;
+pub mod toml;
+pub mod value;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContainerType {
+    Circle,
+    Ellipse { aspect_ratio: FloatType },
+}
+
+impl ContainerType {
+    pub fn ellipse_container(&self) -> EllipseContainer {
+        match self {
+            ContainerType::Circle => EllipseContainer::circle(),
+            ContainerType::Ellipse { aspect_ratio } => EllipseContainer {
+                aspect_ratio: *aspect_ratio,
+            },
+        }
+    }
+}
+
+/// Format of instance file.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InstanceFormat {
+    /// `./input/inputNNN.txt`: number of circles and radii.
+    Input,
+    /// `./packomania/N.txt`: best known `R` and circles with normalized centers.
+    Packomania,
+    Json,
+    Toml,
+}
+
+impl InstanceFormat {
+    /// Format by extension, `.txt` files are told apart by content.
+    pub fn detect(path: &Path, text: &str) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(InstanceFormat::Json),
+            "toml" => Some(InstanceFormat::Toml),
+            "txt" if text::is_packomania(text) => Some(InstanceFormat::Packomania),
+            "txt" => Some(InstanceFormat::Input),
+            _ => None,
+        }
+    }
+}
+
+/// Error of reading instance (or solution) file.
+#[derive(Debug)]
+pub enum InstanceError {
+    Io { path: String, error: io::Error },
+    Parse { path: String, error: ParseError },
+    UnknownFormat { path: String },
+}
+
+impl Display for InstanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstanceError::Io { path, error } => write!(f, "{path}: {error}"),
+            InstanceError::Parse { path, error } => {
+                write!(f, "{path}:{}: {}", error.line, error.message)
+            }
+            InstanceError::UnknownFormat { path } => {
+                write!(f, "{path}: unknown file format (by extension)")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InstanceError {}
+
+/// Drivers writing reports return `io::Result`, I/O errors keep their kind.
+impl From<InstanceError> for io::Error {
+    fn from(error: InstanceError) -> Self {
+        match error {
+            InstanceError::Io { error, .. } => error,
+            error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+        }
+    }
+}
+
+fn read_text(path: &Path) -> Result<String, InstanceError> {
+    fs::read_to_string(path).map_err(|error| InstanceError::Io {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+/// Problem of packing circles with given radii, with data about where it comes from.
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub name: String,
+    /// Origin of the instance, e.g. `input`, `packomania` or file name.
+    pub source: String,
+    pub container: ContainerType,
+    pub known_best: Option<FloatType>,
+    pub radiuses: Vec<FloatType>,
+    /// Known arrangement (packomania answer or warm start), `circles[i]` has radius
+    /// `radiuses[i]`.
+    pub circles: Option<Vec<Circle>>,
+}
+
+impl Instance {
+    pub fn new(name: impl Into<String>, radiuses: Vec<FloatType>) -> Self {
+        Instance {
+            name: name.into(),
+            source: String::new(),
+            container: ContainerType::Circle,
+            known_best: None,
+            radiuses,
+            circles: None,
+        }
+    }
+
+    pub fn with_source(self, source: impl Into<String>) -> Self {
+        Instance {
+            source: source.into(),
+            ..self
+        }
+    }
+
+    pub fn with_known_best(self, known_best: FloatType) -> Self {
+        Instance {
+            known_best: Some(known_best),
+            ..self
+        }
+    }
+
+    pub fn parse(text: &str, format: InstanceFormat, name: &str) -> Result<Self, ParseError> {
+        match format {
+            InstanceFormat::Input => {
+                Ok(Instance::new(name, text::parse_input(text)?).with_source("input"))
+            }
+            InstanceFormat::Packomania => {
+                let (main_circle_radius, circles) = text::parse_packomania(text)?;
+                Ok(Instance {
+                    circles: Some(circles.clone()),
+                    ..Instance::new(name, circles.iter().map(|c| c.radius).collect())
+                        .with_source("packomania")
+                        .with_known_best(main_circle_radius)
+                })
+            }
+            InstanceFormat::Json => Instance::from_value(&json::parse_json(text)?, name),
+            InstanceFormat::Toml => Instance::from_value(&toml::parse_toml(text)?, name),
+        }
+    }
+
+    /// Read instance in the format given by file extension, see `InstanceFormat::detect`.
+    /// The name of instance is the file stem unless the file sets it.
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, InstanceError> {
+        let path = path.as_ref();
+        let text = read_text(path)?;
+        let format = InstanceFormat::detect(path, &text).ok_or(InstanceError::UnknownFormat {
+            path: path.display().to_string(),
+        })?;
+
+        Instance::read_text_as(path, &text, format)
+    }
+
+    pub fn read_as(path: impl AsRef<Path>, format: InstanceFormat) -> Result<Self, InstanceError> {
+        let path = path.as_ref();
+        let text = read_text(path)?;
+
+        Instance::read_text_as(path, &text, format)
+    }
+
+    fn read_text_as(
+        path: &Path,
+        text: &str,
+        format: InstanceFormat,
+    ) -> Result<Self, InstanceError> {
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        Instance::parse(text, format, &name).map_err(|error| InstanceError::Parse {
+            path: path.display().to_string(),
+            error,
+        })
+    }
+
+    /// Test `inputNNN.txt` of the workspace named by its number, with jury answer from
+    /// `outNNN.txt` when there is one.
+    pub fn read_input(test_number: u32) -> Result<Self, InstanceError> {
+        let instance = Instance {
+            name: test_number.to_string(),
+            ..Instance::read_as(workspace().input_file(test_number), InstanceFormat::Input)?
+        };
+
+        let answer_path = workspace().answer_file(test_number);
+        if !answer_path.exists() {
+            return Ok(instance);
+        }
+        Ok(instance.with_known_best(read_known_best(answer_path)?))
+    }
+
+    /// `N.txt` of the packomania directory of the workspace with the best known packing.
+    pub fn read_packomania(test_number: u32) -> Result<Self, InstanceError> {
+        Instance::read_as(
+            workspace().packomania_file(test_number),
+            InstanceFormat::Packomania,
+        )
+    }
+
+    /// Instance from JSON or TOML document:
+    /// `name`, `source`, `known_best`, `container` (`"circle"` or
+    /// `{ type = "ellipse", aspect_ratio = 1.5 }`) are optional, `radiuses` (array of numbers)
+    /// or `circles` (array of `{ radius, x, y }`) is required.
+    pub fn from_value(value: &Value, name: &str) -> Result<Self, ParseError> {
+        value.as_object("instance")?;
+
+        let radiuses = value
+            .get("radiuses")
+            .map(|radiuses| {
+                radiuses
+                    .as_array("radiuses")?
+                    .iter()
+                    .map(|radius| radius.as_number("radiuses"))
+                    .collect::<Result<Vec<FloatType>, ParseError>>()
+            })
+            .transpose()?;
+        let circles = value
+            .get("circles")
+            .map(|circles| {
+                circles
+                    .as_array("circles")?
+                    .iter()
+                    .map(|circle| {
+                        let field = |key: &str| {
+                            circle
+                                .get(key)
+                                .ok_or_else(|| {
+                                    ParseError::new(
+                                        circle.line,
+                                        format!("Missing '{key}' of circle"),
+                                    )
+                                })?
+                                .as_number(key)
+                        };
+                        Ok(Circle::new(
+                            field("radius")?,
+                            Point {
+                                x: field("x")?,
+                                y: field("y")?,
+                            },
+                        ))
+                    })
+                    .collect::<Result<Vec<Circle>, ParseError>>()
+            })
+            .transpose()?;
+
+        let radiuses = match (radiuses, &circles) {
+            (Some(radiuses), Some(circles)) => {
+                if radiuses.len() != circles.len()
+                    || radiuses.iter().zip(circles).any(|(r, c)| *r != c.radius)
+                {
+                    return Err(ParseError::new(
+                        value.get("circles").unwrap().line,
+                        "'radiuses' and radii of 'circles' differ",
+                    ));
+                }
+                radiuses
+            }
+            (Some(radiuses), None) => radiuses,
+            (None, Some(circles)) => circles.iter().map(|c| c.radius).collect(),
+            (None, None) => {
+                return Err(ParseError::new(
+                    value.line,
+                    "Missing 'radiuses' or 'circles' of instance",
+                ))
+            }
+        };
+        if let Some((i, radius)) = radiuses
+            .iter()
+            .enumerate()
+            .find(|(_, r)| **r <= 0.0 || !r.is_finite())
+        {
+            let line = value
+                .get("radiuses")
+                .or(value.get("circles"))
+                .and_then(|values| values.as_array("").ok())
+                .map(|values| values[i].line)
+                .unwrap_or(value.line);
+            return Err(ParseError::new(
+                line,
+                format!("Radius must be positive, found {radius}"),
+            ));
+        }
+
+        let container = match value.get("container") {
+            None => ContainerType::Circle,
+            Some(container) => {
+                let (kind, line) = match &container.kind {
+                    ValueKind::String(kind) => (kind.as_str(), container.line),
+                    _ => (
+                        container
+                            .get("type")
+                            .ok_or_else(|| {
+                                ParseError::new(container.line, "Missing 'type' of container")
+                            })?
+                            .as_str("type")?,
+                        container.line,
+                    ),
+                };
+                match kind {
+                    "circle" => ContainerType::Circle,
+                    "ellipse" => ContainerType::Ellipse {
+                        aspect_ratio: container
+                            .get("aspect_ratio")
+                            .ok_or_else(|| {
+                                ParseError::new(line, "Missing 'aspect_ratio' of ellipse")
+                            })?
+                            .as_number("aspect_ratio")?,
+                    },
+                    _ => {
+                        return Err(ParseError::new(
+                            line,
+                            format!("Unknown container type '{kind}'"),
+                        ))
+                    }
+                }
+            }
+        };
+
+        Ok(Instance {
+            name: match value.get("name") {
+                Some(name) => name.as_str("name")?.to_string(),
+                None => name.to_string(),
+            },
+            source: match value.get("source") {
+                Some(source) => source.as_str("source")?.to_string(),
+                None => String::new(),
+            },
+            container,
+            known_best: value
+                .get("known_best")
+                .map(|known_best| known_best.as_number("known_best"))
+                .transpose()?,
+            radiuses,
+            circles,
+        })
+    }
+
+    /// Document of `from_value` (line numbers are 0).
+    pub fn to_value(&self) -> Value {
+        let value = |kind| Value::new(kind, 0);
+        let number = |number| value(ValueKind::Number(number));
+
+        let mut entries = vec![
+            (
+                "name".to_string(),
+                value(ValueKind::String(self.name.clone())),
+            ),
+            (
+                "source".to_string(),
+                value(ValueKind::String(self.source.clone())),
+            ),
+            (
+                "container".to_string(),
+                match self.container {
+                    ContainerType::Circle => value(ValueKind::String("circle".to_string())),
+                    ContainerType::Ellipse { aspect_ratio } => value(ValueKind::Object(vec![
+                        (
+                            "type".to_string(),
+                            value(ValueKind::String("ellipse".to_string())),
+                        ),
+                        ("aspect_ratio".to_string(), number(aspect_ratio)),
+                    ])),
+                },
+            ),
+        ];
+        if let Some(known_best) = self.known_best {
+            entries.push(("known_best".to_string(), number(known_best)));
+        }
+        entries.push((
+            "radiuses".to_string(),
+            value(ValueKind::Array(
+                self.radiuses.iter().map(|r| number(*r)).collect(),
+            )),
+        ));
+        if let Some(circles) = &self.circles {
+            entries.push((
+                "circles".to_string(),
+                value(ValueKind::Array(
+                    circles
+                        .iter()
+                        .map(|c| {
+                            let center = c.center.expect("Valid center");
+                            value(ValueKind::Object(vec![
+                                ("radius".to_string(), number(c.radius)),
+                                ("x".to_string(), number(center.x)),
+                                ("y".to_string(), number(center.y)),
+                            ]))
+                        })
+                        .collect(),
+                )),
+            ));
+        }
+
+        value(ValueKind::Object(entries))
+    }
+
+    pub fn to_json(&self) -> String {
+        json::to_json(&self.to_value(), 0)
+    }
+}
+
+/// Single number file, e.g. `./output/outNNN.txt`.
+pub fn read_known_best(path: impl AsRef<Path>) -> Result<FloatType, InstanceError> {
+    let path = path.as_ref();
+    text::parse_known_best(&read_text(path)?).map_err(|error| InstanceError::Parse {
+        path: path.display().to_string(),
+        error,
+    })
+}
@@ -0,0 +1,165 @@
+use crate::{circle::Circle, point::Point, utils::FloatType};
+
+use super::value::ParseError;
+
+/// Non-empty lines with their numbers.
+fn lines(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+}
+
+fn parse_number<T: std::str::FromStr>(
+    line: usize,
+    token: &str,
+    what: &str,
+) -> Result<T, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::new(line, format!("Invalid {what} '{token}'")))
+}
+
+/// `inputNNN.txt`: number of circles and then radius of every circle on its own line.
+pub fn parse_input(text: &str) -> Result<Vec<FloatType>, ParseError> {
+    let mut lines = lines(text);
+    let (line, first_line) = lines
+        .next()
+        .ok_or_else(|| ParseError::new(1, "Empty file"))?;
+    let n: usize = parse_number(line, first_line, "number of circles")?;
+
+    let mut radiuses = Vec::with_capacity(n);
+    let mut last_line = line;
+    for (line, text) in lines {
+        let radius: FloatType = parse_number(line, text, "radius")?;
+        if radius <= 0.0 || !radius.is_finite() {
+            return Err(ParseError::new(
+                line,
+                format!("Radius must be positive, found {text}"),
+            ));
+        }
+        if radiuses.len() == n {
+            return Err(ParseError::new(
+                line,
+                format!("Expected {n} radii, found more"),
+            ));
+        }
+        radiuses.push(radius);
+        last_line = line;
+    }
+
+    if radiuses.len() != n {
+        return Err(ParseError::new(
+            last_line,
+            format!("Expected {n} radii, found {}", radiuses.len()),
+        ));
+    }
+
+    Ok(radiuses)
+}
+
+/// `outNNN.txt` and the first line of packomania files: a single number.
+pub fn parse_known_best(text: &str) -> Result<FloatType, ParseError> {
+    let (line, first_line) = lines(text)
+        .next()
+        .ok_or_else(|| ParseError::new(1, "Empty file"))?;
+    parse_number(line, first_line, "main circle radius")
+}
+
+/// Packomania file: main circle radius `R` and then `radius x y` of every circle, where
+/// the center is divided by `R`. Returns `R` and circles in absolute coordinates.
+pub fn parse_packomania(text: &str) -> Result<(FloatType, Vec<Circle>), ParseError> {
+    let main_circle_radius = parse_known_best(text)?;
+
+    let circles = lines(text)
+        .skip(1)
+        .map(|(line, text)| {
+            let tokens: Vec<&str> = text.split_whitespace().collect();
+            if tokens.len() != 3 {
+                return Err(ParseError::new(
+                    line,
+                    format!("Expected 'radius x y', found {} values", tokens.len()),
+                ));
+            }
+
+            let radius: FloatType = parse_number(line, tokens[0], "radius")?;
+            let x: FloatType = parse_number(line, tokens[1], "x")?;
+            let y: FloatType = parse_number(line, tokens[2], "y")?;
+
+            Ok(Circle::new(
+                radius,
+                Point {
+                    x: x * main_circle_radius,
+                    y: y * main_circle_radius,
+                },
+            ))
+        })
+        .collect::<Result<Vec<Circle>, ParseError>>()?;
+
+    Ok((main_circle_radius, circles))
+}
+
+/// Packomania files have circles on every line after `R`, input files have radii only.
+pub fn is_packomania(text: &str) -> bool {
+    lines(text)
+        .nth(1)
+        .map(|(_, line)| line.split_whitespace().count() == 3)
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_skips_empty_lines() {
+        assert_eq!(parse_input("3\n1\n\n 2.5 \n3\n"), Ok(vec![1.0, 2.5, 3.0]));
+    }
+
+    #[test]
+    fn input_errors_have_line_numbers() {
+        for (text, line, message) in [
+            ("", 1, "Empty file"),
+            ("\nthree", 2, "Invalid number of circles 'three'"),
+            ("2\n1\nx", 3, "Invalid radius 'x'"),
+            ("2\n1\n\n-1", 4, "Radius must be positive, found -1"),
+            ("1\n1\n2", 3, "Expected 1 radii, found more"),
+            ("3\n1\n\n2\n", 4, "Expected 3 radii, found 2"),
+        ] {
+            assert_eq!(
+                parse_input(text),
+                Err(ParseError::new(line, message)),
+                "{text:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn packomania_centers_are_scaled_by_main_circle_radius() {
+        let text = "2.0\n1 -0.5 0\n1 0.5 0\n";
+        assert!(is_packomania(text));
+        assert!(!is_packomania("2\n1\n1\n"));
+
+        let (main_circle_radius, circles) = parse_packomania(text).unwrap();
+        assert_eq!(main_circle_radius, 2.0);
+        assert_eq!(circles.len(), 2);
+        assert_eq!(circles[0].center.unwrap().x, -1.0);
+        assert_eq!(circles[1].center.unwrap().x, 1.0);
+    }
+
+    #[test]
+    fn packomania_errors_have_line_numbers() {
+        assert_eq!(
+            parse_packomania("2.0\n1 0 0\n1 0\n").unwrap_err(),
+            ParseError::new(3, "Expected 'radius x y', found 2 values")
+        );
+        assert_eq!(
+            parse_packomania("2.0\n\n1 0 y\n").unwrap_err(),
+            ParseError::new(3, "Invalid y 'y'")
+        );
+        assert_eq!(
+            parse_known_best("R\n"),
+            Err(ParseError::new(1, "Invalid main circle radius 'R'"))
+        );
+    }
+}
@@ -0,0 +1,344 @@
+use super::value::{Cursor, ParseError, Value, ValueKind};
+
+/// Skip spaces, comments and (when `newlines` is set) new lines.
+fn skip_trivia(cursor: &mut Cursor, newlines: bool) {
+    loop {
+        cursor.skip_whitespace(newlines);
+        if cursor.peek() != Some('#') {
+            return;
+        }
+        cursor.take_while(|c| c != '\n');
+    }
+}
+
+fn is_bare_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '-'
+}
+
+fn parse_key(cursor: &mut Cursor) -> Result<String, ParseError> {
+    match cursor.peek() {
+        Some('"') => cursor.parse_string(),
+        Some(c) if is_bare_key_char(c) => Ok(cursor.take_while(is_bare_key_char)),
+        Some(c) => Err(cursor.error(format!("Expected key, found '{c}'"))),
+        None => Err(cursor.error("Expected key, found end of file")),
+    }
+}
+
+fn parse_literal_string(cursor: &mut Cursor) -> Result<String, ParseError> {
+    cursor.expect('\'')?;
+    let string = cursor.take_while(|c| c != '\'' && c != '\n');
+    cursor
+        .expect('\'')
+        .map_err(|_| cursor.error("Unterminated string"))?;
+    Ok(string)
+}
+
+fn parse_value(cursor: &mut Cursor) -> Result<Value, ParseError> {
+    let line = cursor.line;
+
+    let kind = match cursor.peek() {
+        None | Some('\n') => return Err(cursor.error("Expected value")),
+        Some('"') => ValueKind::String(cursor.parse_string()?),
+        Some('\'') => ValueKind::String(parse_literal_string(cursor)?),
+        Some('[') => {
+            cursor.next();
+            let mut values = Vec::new();
+            loop {
+                skip_trivia(cursor, true);
+                if cursor.peek() == Some(']') {
+                    cursor.next();
+                    break;
+                }
+                values.push(parse_value(cursor)?);
+
+                skip_trivia(cursor, true);
+                match cursor.next() {
+                    Some(',') => continue,
+                    Some(']') => break,
+                    _ => return Err(cursor.error("Expected ',' or ']' in array")),
+                }
+            }
+            ValueKind::Array(values)
+        }
+        Some('{') => {
+            cursor.next();
+            let mut entries = Vec::new();
+            cursor.skip_whitespace(false);
+            if cursor.peek() == Some('}') {
+                cursor.next();
+            } else {
+                loop {
+                    cursor.skip_whitespace(false);
+                    let key = parse_key(cursor)?;
+                    cursor.skip_whitespace(false);
+                    cursor.expect('=')?;
+                    cursor.skip_whitespace(false);
+                    entries.push((key, parse_value(cursor)?));
+
+                    cursor.skip_whitespace(false);
+                    match cursor.next() {
+                        Some(',') => continue,
+                        Some('}') => break,
+                        _ => return Err(cursor.error("Expected ',' or '}' in inline table")),
+                    }
+                }
+            }
+            ValueKind::Object(entries)
+        }
+        Some(c) if c == '+' || c == '-' || c.is_ascii_digit() => {
+            ValueKind::Number(cursor.parse_number()?)
+        }
+        Some(_) => {
+            let word = cursor.take_while(is_bare_key_char);
+            match word.as_str() {
+                "true" => ValueKind::Bool(true),
+                "false" => ValueKind::Bool(false),
+                "inf" | "nan" => ValueKind::Number(word.parse().unwrap()),
+                _ => return Err(cursor.error(format!("Invalid value '{word}'"))),
+            }
+        }
+    };
+
+    Ok(Value::new(kind, line))
+}
+
+fn entries_mut(value: &mut Value) -> &mut Vec<(String, Value)> {
+    match &mut value.kind {
+        ValueKind::Object(entries) => entries,
+        _ => unreachable!("tables are objects"),
+    }
+}
+
+/// Table where the following keys go: root, `[name]` or the last element of `[[name]]`.
+enum Target {
+    Root,
+    Table(String),
+    ArrayOfTables(String),
+}
+
+fn target_table<'a>(root: &'a mut Value, target: &Target) -> &'a mut Value {
+    let (name, is_array) = match target {
+        Target::Root => return root,
+        Target::Table(name) => (name, false),
+        Target::ArrayOfTables(name) => (name, true),
+    };
+
+    let table = entries_mut(root)
+        .iter_mut()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value)
+        .unwrap();
+
+    if is_array {
+        match &mut table.kind {
+            ValueKind::Array(tables) => tables.last_mut().unwrap(),
+            _ => unreachable!("arrays of tables are arrays"),
+        }
+    } else {
+        table
+    }
+}
+
+/// Parse the TOML subset used by instance and solution files: key/value pairs, strings,
+/// numbers, booleans, (multi-line) arrays, inline tables, `[table]` and `[[array]]` headers.
+/// Dotted keys and dates aren't supported.
+pub fn parse_toml(text: &str) -> Result<Value, ParseError> {
+    let mut cursor = Cursor::new(text);
+    let mut root = Value::new(ValueKind::Object(Vec::new()), 1);
+    let mut target = Target::Root;
+
+    loop {
+        skip_trivia(&mut cursor, true);
+        let line = cursor.line;
+
+        match cursor.peek() {
+            None => break,
+            Some('[') => {
+                cursor.next();
+                let is_array = cursor.peek() == Some('[');
+                if is_array {
+                    cursor.next();
+                }
+                cursor.skip_whitespace(false);
+                let name = parse_key(&mut cursor)?;
+                cursor.skip_whitespace(false);
+                cursor.expect(']')?;
+                if is_array {
+                    cursor.expect(']')?;
+                }
+
+                let entries = entries_mut(&mut root);
+                let existing = entries.iter_mut().find(|(key, _)| *key == name);
+                let table = Value::new(ValueKind::Object(Vec::new()), line);
+                match (existing, is_array) {
+                    (None, false) => entries.push((name.clone(), table)),
+                    (None, true) => entries.push((
+                        name.clone(),
+                        Value::new(ValueKind::Array(vec![table]), line),
+                    )),
+                    (Some((_, value)), true) => match &mut value.kind {
+                        ValueKind::Array(tables) => tables.push(table),
+                        _ => {
+                            return Err(ParseError::new(
+                                line,
+                                format!("'{name}' is not an array of tables"),
+                            ))
+                        }
+                    },
+                    (Some(_), false) => {
+                        return Err(ParseError::new(
+                            line,
+                            format!("Table '{name}' is defined twice"),
+                        ))
+                    }
+                }
+
+                target = if is_array {
+                    Target::ArrayOfTables(name)
+                } else {
+                    Target::Table(name)
+                };
+            }
+            Some(_) => {
+                let key = parse_key(&mut cursor)?;
+                cursor.skip_whitespace(false);
+                cursor.expect('=')?;
+                cursor.skip_whitespace(false);
+                let value = parse_value(&mut cursor)?;
+
+                let entries = entries_mut(target_table(&mut root, &target));
+                if entries.iter().any(|(k, _)| *k == key) {
+                    return Err(ParseError::new(
+                        line,
+                        format!("Key '{key}' is defined twice"),
+                    ));
+                }
+                entries.push((key, value));
+            }
+        }
+
+        // the rest of the line may hold only a comment
+        skip_trivia(&mut cursor, false);
+        match cursor.peek() {
+            None | Some('\n') => {}
+            Some(c) => return Err(cursor.error(format!("Unexpected '{c}' at the end of line"))),
+        }
+    }
+
+    Ok(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn number(value: &Value) -> f64 {
+        value.as_number("test").unwrap()
+    }
+
+    #[test]
+    fn parses_every_kind_of_value() {
+        let text = r#"
+# instance
+name = "test" # comment
+"quoted key" = 'C:\raw'
+known_best = +2.5
+big = 1_000
+flag = true
+other = false
+infinite = inf
+radiuses = [
+    1, 2, # comment inside array
+    3,
+]
+container = { type = "ellipse", aspect_ratio = 1.5 }
+empty = {}
+"#;
+        let value = parse_toml(text).unwrap();
+
+        assert_eq!(value.get("name").unwrap().as_str("name"), Ok("test"));
+        assert_eq!(
+            value.get("quoted key").unwrap().as_str("key"),
+            Ok("C:\\raw")
+        );
+        assert_eq!(number(value.get("known_best").unwrap()), 2.5);
+        assert_eq!(number(value.get("big").unwrap()), 1000.0);
+        assert_eq!(value.get("flag").unwrap().kind, ValueKind::Bool(true));
+        assert_eq!(value.get("other").unwrap().kind, ValueKind::Bool(false));
+        assert_eq!(number(value.get("infinite").unwrap()), f64::INFINITY);
+
+        let radiuses = value.get("radiuses").unwrap();
+        assert_eq!(radiuses.line, 10);
+        let radiuses: Vec<f64> = radiuses.as_array("r").unwrap().iter().map(number).collect();
+        assert_eq!(radiuses, vec![1.0, 2.0, 3.0]);
+
+        let container = value.get("container").unwrap();
+        assert_eq!(container.get("type").unwrap().as_str("type"), Ok("ellipse"));
+        assert_eq!(number(container.get("aspect_ratio").unwrap()), 1.5);
+        assert_eq!(value.get("empty").unwrap().as_object("empty"), Ok(&vec![]));
+    }
+
+    #[test]
+    fn tables_and_arrays_of_tables() {
+        let text = "\
+main_circle_radius = 3
+
+[solver]
+name = \"ralgo\"
+
+[[circles]]
+radius = 1
+x = 0
+
+[[circles]]
+radius = 2
+x = -1
+";
+        let value = parse_toml(text).unwrap();
+
+        assert_eq!(number(value.get("main_circle_radius").unwrap()), 3.0);
+        let solver = value.get("solver").unwrap();
+        assert_eq!(solver.line, 3);
+        assert_eq!(solver.get("name").unwrap().as_str("name"), Ok("ralgo"));
+
+        let circles = value.get("circles").unwrap().as_array("circles").unwrap();
+        let radii: Vec<f64> = circles
+            .iter()
+            .map(|c| number(c.get("radius").unwrap()))
+            .collect();
+        let lines: Vec<usize> = circles.iter().map(|c| c.line).collect();
+        assert_eq!(radii, vec![1.0, 2.0]);
+        assert_eq!(lines, vec![6, 10]);
+        assert_eq!(number(circles[1].get("x").unwrap()), -1.0);
+    }
+
+    #[test]
+    fn errors_have_line_numbers() {
+        for (text, line, message) in [
+            ("a = 1\nb =\n", 2, "Expected value"),
+            ("a = 1\nb = 2 3\n", 2, "Unexpected '3' at the end of line"),
+            ("a = 1\n\na = 2", 3, "Key 'a' is defined twice"),
+            ("[t]\n\n[t]", 3, "Table 't' is defined twice"),
+            ("t = 1\n[[t]]", 2, "'t' is not an array of tables"),
+            ("\n= 1", 2, "Expected key, found '='"),
+            ("a 1", 1, "Expected '=', found '1'"),
+            ("a = yes", 1, "Invalid value 'yes'"),
+            ("a = 1\nb = 'abc\n", 2, "Unterminated string"),
+            ("a = \"abc\nb = 1", 1, "Unterminated string"),
+            ("a = [1,\n2\n3]", 3, "Expected ',' or ']' in array"),
+            (
+                "a = { b = 1 c = 2 }",
+                1,
+                "Expected ',' or '}' in inline table",
+            ),
+            ("[t\n", 1, "Expected ']', found '\n'"),
+            ("a = [\n1,\n", 3, "Expected value"),
+        ] {
+            assert_eq!(
+                parse_toml(text),
+                Err(ParseError::new(line, message)),
+                "{text:?}"
+            );
+        }
+    }
+}
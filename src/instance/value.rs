@@ -0,0 +1,329 @@
+use std::fmt::Display;
+
+use crate::utils::FloatType;
+
+/// Error of parsing text with the (1-based) line where it happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line,
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueKind {
+    Null,
+    Bool(bool),
+    Number(FloatType),
+    String(String),
+    Array(Vec<Value>),
+    /// Keys in the order of the text.
+    Object(Vec<(String, Value)>),
+}
+
+/// Document of JSON or TOML, every value remembers the line it starts at.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Value {
+    pub kind: ValueKind,
+    pub line: usize,
+}
+
+impl Value {
+    pub fn new(kind: ValueKind, line: usize) -> Self {
+        Value { kind, line }
+    }
+
+    fn type_name(&self) -> &'static str {
+        match self.kind {
+            ValueKind::Null => "null",
+            ValueKind::Bool(_) => "boolean",
+            ValueKind::Number(_) => "number",
+            ValueKind::String(_) => "string",
+            ValueKind::Array(_) => "array",
+            ValueKind::Object(_) => "object",
+        }
+    }
+
+    fn expected(&self, what: &str, expected: &str) -> ParseError {
+        ParseError::new(
+            self.line,
+            format!(
+                "Expected {expected} for '{what}', found {}",
+                self.type_name()
+            ),
+        )
+    }
+
+    /// Value of `key` of the object, `None` for missing key or not an object.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match &self.kind {
+            ValueKind::Object(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self, what: &str) -> Result<FloatType, ParseError> {
+        match self.kind {
+            ValueKind::Number(number) => Ok(number),
+            _ => Err(self.expected(what, "number")),
+        }
+    }
+
+    pub fn as_str(&self, what: &str) -> Result<&str, ParseError> {
+        match &self.kind {
+            ValueKind::String(string) => Ok(string),
+            _ => Err(self.expected(what, "string")),
+        }
+    }
+
+    pub fn as_array(&self, what: &str) -> Result<&Vec<Value>, ParseError> {
+        match &self.kind {
+            ValueKind::Array(values) => Ok(values),
+            _ => Err(self.expected(what, "array")),
+        }
+    }
+
+    pub fn as_object(&self, what: &str) -> Result<&Vec<(String, Value)>, ParseError> {
+        match &self.kind {
+            ValueKind::Object(entries) => Ok(entries),
+            _ => Err(self.expected(what, "object")),
+        }
+    }
+}
+
+/// Characters of the text with position and current line, shared by JSON and TOML parsers.
+pub struct Cursor {
+    chars: Vec<char>,
+    position: usize,
+    pub line: usize,
+}
+
+impl Cursor {
+    pub fn new(text: &str) -> Self {
+        Cursor {
+            chars: text.chars().collect(),
+            position: 0,
+            line: 1,
+        }
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    pub fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, message)
+    }
+
+    /// Skip spaces and tabs, and new lines when `newlines` is set.
+    pub fn skip_whitespace(&mut self, newlines: bool) {
+        while let Some(c) = self.peek() {
+            if c == ' ' || c == '\t' || c == '\r' || (newlines && c == '\n') {
+                self.next();
+            } else {
+                break;
+            }
+        }
+    }
+
+    pub fn expect(&mut self, expected: char) -> Result<(), ParseError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.next();
+                Ok(())
+            }
+            Some(c) => Err(self.error(format!("Expected '{expected}', found '{c}'"))),
+            None => Err(self.error(format!("Expected '{expected}', found end of file"))),
+        }
+    }
+
+    /// Double-quoted string with JSON escapes (TOML basic strings use the same ones).
+    pub fn parse_string(&mut self) -> Result<String, ParseError> {
+        self.expect('"')?;
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                None => return Err(self.error("Unterminated string")),
+                // the new line is already counted, report the line of the string
+                Some('\n') => return Err(ParseError::new(self.line - 1, "Unterminated string")),
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let code: String = (0..4).filter_map(|_| self.next()).collect();
+                            u32::from_str_radix(&code, 16)
+                                .ok()
+                                .and_then(char::from_u32)
+                                .ok_or_else(|| self.error(format!("Invalid escape '\\u{code}'")))?
+                        }
+                        Some(c) => return Err(self.error(format!("Invalid escape '\\{c}'"))),
+                        None => return Err(self.error("Unterminated string")),
+                    };
+                    string.push(escaped);
+                }
+                Some(c) => string.push(c),
+            }
+        }
+    }
+
+    /// Characters while `accept` holds.
+    pub fn take_while(&mut self, accept: impl Fn(char) -> bool) -> String {
+        let mut taken = String::new();
+        while let Some(c) = self.peek() {
+            if !accept(c) {
+                break;
+            }
+            taken.push(c);
+            self.next();
+        }
+        taken
+    }
+
+    pub fn parse_number(&mut self) -> Result<FloatType, ParseError> {
+        let token = self.take_while(|c| c.is_ascii_alphanumeric() || "+-._".contains(c));
+        token
+            .replace('_', "")
+            .parse()
+            .map_err(|_| self.error(format!("Invalid number '{token}'")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_counts_lines() {
+        let mut cursor = Cursor::new("a\n \n\tb");
+        assert_eq!(cursor.next(), Some('a'));
+        cursor.skip_whitespace(false);
+        assert_eq!(cursor.line, 1);
+        cursor.skip_whitespace(true);
+        assert_eq!(cursor.line, 3);
+        assert_eq!(cursor.peek(), Some('b'));
+    }
+
+    #[test]
+    fn strings_with_escapes() {
+        let mut cursor = Cursor::new(r#""a\"b\\c\/\n\t\u0041" rest"#);
+        assert_eq!(cursor.parse_string().unwrap(), "a\"b\\c/\n\tA");
+        assert_eq!(cursor.peek(), Some(' '));
+    }
+
+    #[test]
+    fn string_errors_report_line_of_string() {
+        let mut cursor = Cursor::new("\n\"abc\nd\"");
+        cursor.skip_whitespace(true);
+        assert_eq!(
+            cursor.parse_string(),
+            Err(ParseError::new(2, "Unterminated string"))
+        );
+
+        let error = Cursor::new("\"abc").parse_string().unwrap_err();
+        assert_eq!(error, ParseError::new(1, "Unterminated string"));
+
+        let error = Cursor::new(r#""\q""#).parse_string().unwrap_err();
+        assert_eq!(error.message, "Invalid escape '\\q'");
+
+        let error = Cursor::new(r#""\uzzzz""#).parse_string().unwrap_err();
+        assert_eq!(error.message, "Invalid escape '\\uzzzz'");
+    }
+
+    #[test]
+    fn numbers() {
+        for (text, expected) in [
+            ("1", 1.0),
+            ("-2.5", -2.5),
+            ("+3", 3.0),
+            ("1e-3", 1e-3),
+            ("1_000.5", 1000.5),
+        ] {
+            assert_eq!(Cursor::new(text).parse_number(), Ok(expected), "{text}");
+        }
+
+        let error = Cursor::new("1.2.3").parse_number().unwrap_err();
+        assert_eq!(error, ParseError::new(1, "Invalid number '1.2.3'"));
+    }
+
+    #[test]
+    fn expect_reports_found_character() {
+        let mut cursor = Cursor::new("ab");
+        assert!(cursor.expect('a').is_ok());
+        assert_eq!(
+            cursor.expect('c').unwrap_err().message,
+            "Expected 'c', found 'b'"
+        );
+        cursor.next();
+        assert_eq!(
+            cursor.expect('c').unwrap_err().message,
+            "Expected 'c', found end of file"
+        );
+    }
+
+    #[test]
+    fn accessors_report_line_of_value() {
+        let value = Value::new(
+            ValueKind::Object(vec![(
+                "radiuses".to_string(),
+                Value::new(ValueKind::String("1".to_string()), 4),
+            )]),
+            1,
+        );
+
+        assert!(value.as_object("instance").is_ok());
+        assert!(value.get("missing").is_none());
+
+        let radiuses = value.get("radiuses").unwrap();
+        assert_eq!(radiuses.as_str("radiuses"), Ok("1"));
+        assert_eq!(
+            radiuses.as_array("radiuses"),
+            Err(ParseError::new(
+                4,
+                "Expected array for 'radiuses', found string"
+            ))
+        );
+        assert_eq!(radiuses.as_number("radiuses").unwrap_err().line, 4);
+        assert_eq!(value.as_number("instance").unwrap_err().line, 1);
+    }
+
+    #[test]
+    fn error_display_has_line() {
+        assert_eq!(
+            ParseError::new(7, "Expected value").to_string(),
+            "line 7: Expected value"
+        );
+    }
+}
@@ -0,0 +1,302 @@
+use std::str::FromStr;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    circle::Circle,
+    packing::{is_valid_pack, pack_circles},
+    point::Point,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    utils::FloatType,
+};
+
+/// What is maximised when not all circles fit into the main circle.
+#[derive(Clone, Copy, Debug)]
+pub enum KnapsackObjective {
+    Value,
+    Count,
+    Area,
+}
+
+impl KnapsackObjective {
+    fn item_value(&self, radius: FloatType, value: FloatType) -> FloatType {
+        match self {
+            KnapsackObjective::Value => value,
+            KnapsackObjective::Count => 1.0,
+            KnapsackObjective::Area => std::f64::consts::PI as FloatType * radius.powi(2),
+        }
+    }
+}
+
+impl FromStr for KnapsackObjective {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "value" => Ok(KnapsackObjective::Value),
+            "count" => Ok(KnapsackObjective::Count),
+            "area" => Ok(KnapsackObjective::Area),
+            _ => Err(format!(
+                "Unknown objective '{s}', expected value, count or area"
+            )),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct KnapsackResult {
+    /// Indices of packed circles, `circles[k]` is placement of `packed[k]`.
+    pub packed: Vec<usize>,
+    pub left_out: Vec<usize>,
+    pub circles: Vec<Circle>,
+    pub total_value: FloatType,
+}
+
+impl KnapsackResult {
+    pub fn print(&self) {
+        println!("Total value: {}", self.total_value);
+        println!("Packed: {:?}", self.packed);
+        println!("Left out: {:?}", self.left_out);
+        for (index, circle) in self.packed.iter().zip(self.circles.iter()) {
+            println!(
+                "{} {} {:.15} {:.15}",
+                index,
+                circle.radius,
+                circle.center.unwrap().x,
+                circle.center.unwrap().y
+            );
+        }
+    }
+}
+
+/// Try to place circles with heuristic, shuffling their order between attempts.
+/// Returned circles have the same order as `indices`.
+fn heuristic_fit(
+    main_circle_radius: FloatType,
+    radiuses: &Vec<FloatType>,
+    indices: &Vec<usize>,
+    number_of_iterations: u32,
+    rng: &mut StdRng,
+) -> Option<Vec<Circle>> {
+    let mut order: Vec<usize> = (0..indices.len()).collect();
+    order.sort_by(|a, b| radiuses[indices[*b]].total_cmp(&radiuses[indices[*a]]));
+
+    for _ in 0..number_of_iterations {
+        let ordered_radiuses = order.iter().map(|&k| radiuses[indices[k]]).collect();
+
+        if let Some(circles) = pack_circles(&ordered_radiuses, main_circle_radius, &[]) {
+            if is_valid_pack(main_circle_radius, &circles) {
+                let mut answer = vec![Circle::empty(); indices.len()];
+                for (position, &k) in order.iter().enumerate() {
+                    answer[k] = circles[position];
+                }
+                return Some(answer);
+            }
+        }
+
+        let number_of_circles = order.len();
+        order.swap(
+            rng.gen_range(0..number_of_circles),
+            rng.gen_range(0..number_of_circles),
+        );
+    }
+
+    None
+}
+
+/// Insert new circle at random point and compact arrangement with r-algorithm.
+/// Succeeds when compacted arrangement fits into the main circle.
+fn ralgo_fit(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    radius: FloatType,
+    launches: usize,
+    ralgo_params: &RalgoParams,
+    rng: &mut StdRng,
+) -> Option<Vec<Circle>> {
+    for _ in 0..launches {
+        // uniform point of the disc where the new circle fits, it is (0, 0) for the circle
+        // as large as the main one
+        let distance = (main_circle_radius - radius).max(0.0) * rng.gen::<FloatType>().sqrt();
+        let angle = rng.gen_range(0.0..2.0 * std::f64::consts::PI as FloatType);
+        let (x, y) = (distance * angle.cos(), distance * angle.sin());
+
+        let mut start_circles = circles.clone();
+        start_circles.push(Circle::new(radius, Point { x, y }));
+
+        let (new_main_circle_radius, new_circles) =
+            dichotomy_step_ralgo(main_circle_radius, &start_circles, false, 0.0, ralgo_params);
+
+        if new_main_circle_radius <= main_circle_radius
+            && is_valid_pack(main_circle_radius, &new_circles)
+        {
+            return Some(new_circles);
+        }
+    }
+
+    None
+}
+
+/// Choose and place subset of circles in the main circle of fixed radius maximizing the objective.
+/// Circles are added greedily by value per area using heuristic packing, then left out circles
+/// are inserted (or swapped with less valuable ones) using r-algorithm compaction.
+/// `values` has value of every circle, it is used by `KnapsackObjective::Value`.
+pub fn knapsack_pack(
+    main_circle_radius: FloatType,
+    radiuses: &Vec<FloatType>,
+    values: &Vec<FloatType>,
+    objective: KnapsackObjective,
+    number_of_iterations: u32,
+    ralgo_params: &RalgoParams,
+) -> Result<KnapsackResult, String> {
+    if values.len() != radiuses.len() {
+        return Err(format!(
+            "Expected value of every circle: {} radii, {} values",
+            radiuses.len(),
+            values.len()
+        ));
+    }
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let item_value = |index: usize| objective.item_value(radiuses[index], values[index]);
+
+    let mut candidates: Vec<usize> = (0..radiuses.len())
+        .filter(|&index| radiuses[index] <= main_circle_radius)
+        .collect();
+    candidates.sort_by(|a, b| {
+        let density_a = item_value(*a) / radiuses[*a].powi(2);
+        let density_b = item_value(*b) / radiuses[*b].powi(2);
+        density_b
+            .total_cmp(&density_a)
+            .then(item_value(*b).total_cmp(&item_value(*a)))
+    });
+
+    // greedy stage
+    let mut packed: Vec<usize> = vec![];
+    let mut circles: Vec<Circle> = vec![];
+    let mut left_out: Vec<usize> = vec![];
+    for &candidate in &candidates {
+        let mut indices = packed.clone();
+        indices.push(candidate);
+
+        match heuristic_fit(
+            main_circle_radius,
+            radiuses,
+            &indices,
+            number_of_iterations,
+            &mut rng,
+        ) {
+            Some(new_circles) => (packed, circles) = (indices, new_circles),
+            None => left_out.push(candidate),
+        }
+    }
+
+    // local improvement stage
+    let mut index = 0;
+    while index < left_out.len() {
+        let candidate = left_out[index];
+
+        if let Some(new_circles) = ralgo_fit(
+            main_circle_radius,
+            &circles,
+            radiuses[candidate],
+            5,
+            ralgo_params,
+            &mut rng,
+        ) {
+            packed.push(candidate);
+            circles = new_circles;
+            left_out.remove(index);
+            continue;
+        }
+
+        // swap with the least valuable packed circle if it pays off
+        if let Some(position) = (0..packed.len())
+            .min_by(|a, b| item_value(packed[*a]).total_cmp(&item_value(packed[*b])))
+        {
+            if item_value(packed[position]) < item_value(candidate) {
+                let mut reduced_circles = circles.clone();
+                reduced_circles.remove(position);
+
+                if let Some(new_circles) = ralgo_fit(
+                    main_circle_radius,
+                    &reduced_circles,
+                    radiuses[candidate],
+                    5,
+                    ralgo_params,
+                    &mut rng,
+                ) {
+                    left_out[index] = packed.remove(position);
+                    packed.push(candidate);
+                    circles = new_circles;
+                }
+            }
+        }
+
+        index += 1;
+    }
+
+    left_out.extend((0..radiuses.len()).filter(|&i| radiuses[i] > main_circle_radius));
+    left_out.sort();
+
+    Ok(KnapsackResult {
+        total_value: packed.iter().map(|&i| item_value(i)).sum(),
+        packed,
+        left_out,
+        circles,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn knapsack_of_radii_up_to_ten(objective: KnapsackObjective) -> KnapsackResult {
+        let radiuses: Vec<FloatType> = (1..=10).map(|r| r as FloatType).collect();
+        let ralgo_params = RalgoParams::default().with_max_iterations(200);
+
+        let result =
+            knapsack_pack(12.0, &radiuses, &radiuses, objective, 10, &ralgo_params).unwrap();
+
+        let mut indices = [result.packed.clone(), result.left_out.clone()].concat();
+        indices.sort();
+        assert_eq!(indices, (0..10).collect::<Vec<_>>());
+        for (&index, circle) in result.packed.iter().zip(&result.circles) {
+            assert_eq!(circle.radius, radiuses[index]);
+        }
+        assert!(is_valid_pack(12.0, &result.circles));
+
+        result
+    }
+
+    #[test]
+    fn count_objective_packs_six_smallest_circles() {
+        // any 7 of the circles have area at least 140π of 144π, so 6 is the most
+        let result = knapsack_of_radii_up_to_ten(KnapsackObjective::Count);
+
+        assert_eq!(result.packed, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(result.left_out, vec![6, 7, 8, 9]);
+        assert_eq!(result.total_value, 6.0);
+    }
+
+    #[test]
+    fn area_objective_starts_from_largest_circle() {
+        let result = knapsack_of_radii_up_to_ten(KnapsackObjective::Area);
+
+        assert!(result.packed.contains(&9));
+        assert!(result.total_value >= std::f64::consts::PI as FloatType * 100.0);
+    }
+
+    #[test]
+    fn values_must_match_radii() {
+        assert!(knapsack_pack(
+            12.0,
+            &vec![1.0, 2.0],
+            &vec![1.0],
+            KnapsackObjective::Value,
+            10,
+            &RalgoParams::default()
+        )
+        .is_err());
+    }
+}
@@ -12,18 +12,49 @@ use crate::{
     utils::measure_time,
 };
 
+mod bin_packing;
 mod circle;
+mod cli;
+mod container;
+mod dxf;
+mod ellipse;
+mod ellipse_packing;
 mod evaluate;
+mod holes;
+mod ils;
+mod instance;
+mod knapsack;
+mod memetic;
+mod obstacle;
 mod packing;
 mod packomania;
+mod pipeline;
 mod plot;
 mod point;
+mod polishing;
 mod ralgo;
+mod random_arrangement;
 mod read_and_gen_tables;
+mod real;
+mod scaling;
+mod serve;
+mod solution;
+mod solver;
 mod utils;
+mod workspace;
 
 fn main() {
-    find_best_heuristic(10);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match cli::run(&args) {
+        Ok(true) => return,
+        Ok(false) => {}
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    }
+
+    find_best_heuristic(10).unwrap();
     let variants_array = [false, true];
     let eps_array = [0.0];
     let algorithm_params = eps_array
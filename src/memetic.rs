@@ -0,0 +1,455 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::f64::consts::PI;
+
+use crate::{
+    circle::Circle,
+    packing::{is_valid_pack, restore_pack},
+    point::Point,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    random_arrangement::{enclosing_radius, random_point, random_start},
+    utils::{measure_time, FloatType},
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct MemeticParams {
+    pub population_size: usize,
+    pub generations: usize,
+    /// Children created (and locally optimised in parallel) in every generation.
+    pub offspring: usize,
+    /// Probability of mutation of a child after crossover.
+    pub mutation_rate: FloatType,
+    /// Density of random arrangements of the initial population.
+    pub density: FloatType,
+    /// Children closer than this (see `distance`) to an individual of the population
+    /// with not smaller `R` are dropped, so the population doesn't collapse into copies.
+    pub min_distance: FloatType,
+    pub reset_step: bool,
+    pub eps: FloatType,
+    pub ralgo_params: RalgoParams,
+    pub seed: u64,
+}
+
+impl Default for MemeticParams {
+    fn default() -> Self {
+        MemeticParams {
+            population_size: 20,
+            generations: 30,
+            offspring: 20,
+            mutation_rate: 0.3,
+            density: 0.6,
+            min_distance: 1e-3,
+            reset_step: false,
+            eps: 0.0,
+            ralgo_params: RalgoParams::default()
+                .with_alpha(1.5)
+                .with_q1(1.0)
+                .with_max_iterations(100_000),
+            seed: 0,
+        }
+    }
+}
+
+impl MemeticParams {
+    pub fn with_population_size(&self, population_size: usize) -> Self {
+        MemeticParams {
+            population_size,
+            ..*self
+        }
+    }
+
+    pub fn with_generations(&self, generations: usize) -> Self {
+        MemeticParams {
+            generations,
+            ..*self
+        }
+    }
+
+    pub fn with_offspring(&self, offspring: usize) -> Self {
+        MemeticParams { offspring, ..*self }
+    }
+
+    pub fn with_mutation_rate(&self, mutation_rate: FloatType) -> Self {
+        MemeticParams {
+            mutation_rate,
+            ..*self
+        }
+    }
+
+    pub fn with_reset_step(&self, reset_step: bool) -> Self {
+        MemeticParams {
+            reset_step,
+            ..*self
+        }
+    }
+
+    pub fn with_eps(&self, eps: FloatType) -> Self {
+        MemeticParams { eps, ..*self }
+    }
+
+    pub fn with_ralgo_params(&self, ralgo_params: RalgoParams) -> Self {
+        MemeticParams {
+            ralgo_params,
+            ..*self
+        }
+    }
+
+    /// Parameters the algorithm can't run with, e.g. empty population.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.population_size == 0 {
+            return Err("Population size must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.mutation_rate) {
+            return Err(format!(
+                "Mutation rate must be in [0, 1], got {}",
+                self.mutation_rate
+            ));
+        }
+        if !(self.density > 0.0) {
+            return Err(format!("Density must be positive, got {}", self.density));
+        }
+        Ok(())
+    }
+
+    pub fn with_seed(&self, seed: u64) -> Self {
+        MemeticParams { seed, ..*self }
+    }
+}
+
+/// Packing of the population, `circles[i]` has radius `radiuses[i]`.
+#[derive(Debug, Clone)]
+pub struct Individual {
+    pub main_circle_radius: FloatType,
+    pub circles: Vec<Circle>,
+    pub is_valid: bool,
+}
+
+impl Individual {
+    /// Locally optimise the arrangement with `dichotomy_step_ralgo` and restore it when
+    /// r-algorithm left small overlaps.
+    fn optimise(circles: Vec<Circle>, params: &MemeticParams) -> Self {
+        let (main_circle_radius, circles) = dichotomy_step_ralgo(
+            enclosing_radius(&circles),
+            &circles,
+            params.reset_step,
+            params.eps,
+            &params.ralgo_params,
+        );
+
+        let (main_circle_radius, circles) = if is_valid_pack(main_circle_radius, &circles) {
+            (main_circle_radius, circles)
+        } else {
+            restore_pack(&circles)
+        };
+
+        Individual {
+            is_valid: is_valid_pack(main_circle_radius, &circles),
+            main_circle_radius,
+            circles,
+        }
+    }
+
+    /// Distances of centers from the center of main circle relative to `R`. Doesn't
+    /// depend on rotations and reflections of the packing.
+    fn descriptor(&self) -> Vec<FloatType> {
+        self.circles
+            .iter()
+            .map(|c| {
+                let center = c.center.unwrap();
+                (center.x.powi(2) + center.y.powi(2)).sqrt() / self.main_circle_radius
+            })
+            .collect()
+    }
+
+    /// Root mean square difference of descriptors.
+    fn distance(&self, other: &Individual) -> FloatType {
+        let (a, b) = (self.descriptor(), other.descriptor());
+        (a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| (x - y).powi(2))
+            .sum::<FloatType>()
+            / a.len() as FloatType)
+            .sqrt()
+    }
+
+    /// Valid individuals first, then by `R`.
+    fn compare(&self, other: &Individual) -> std::cmp::Ordering {
+        other.is_valid.cmp(&self.is_valid).then(
+            self.main_circle_radius
+                .partial_cmp(&other.main_circle_radius)
+                .unwrap(),
+        )
+    }
+}
+
+#[derive(Debug)]
+pub struct GenerationReport {
+    pub generation: usize,
+    /// Best `R` among valid individuals, `FloatType::MAX` when there are none.
+    pub best_main_circle_radius: FloatType,
+    pub mean_main_circle_radius: FloatType,
+    /// Mean pairwise `distance` of individuals.
+    pub diversity: FloatType,
+    /// Children that weren't dropped as duplicates.
+    pub accepted_children: usize,
+    pub time: FloatType,
+}
+
+#[derive(Debug)]
+pub struct MemeticResult {
+    pub main_circle_radius: FloatType,
+    pub circles: Vec<Circle>,
+    /// Generation 0 is the initial population.
+    pub reports: Vec<GenerationReport>,
+}
+
+impl MemeticResult {
+    pub fn print(&self) {
+        for report in &self.reports {
+            println!(
+                "Generation {}: best R = {}, mean R = {}, diversity = {:.5}, accepted = {}, time = {:.3}s",
+                report.generation,
+                report.best_main_circle_radius,
+                report.mean_main_circle_radius,
+                report.diversity,
+                report.accepted_children,
+                report.time
+            );
+        }
+        println!("Best R = {}", self.main_circle_radius);
+    }
+}
+
+/// Child takes circles of `first` on one side of a random line through the center and
+/// circles of randomly rotated `second` on the other side. Circles taken from neither
+/// parent are put into places of circles taken twice, matching them by radius (the
+/// largest to the largest place), the rest get random places.
+fn crossover(first: &Individual, second: &Individual, rng: &mut StdRng) -> Vec<Circle> {
+    let angle = rng.gen_range(0.0..2.0 * PI) as FloatType;
+    let rotation = rng.gen_range(0.0..2.0 * PI) as FloatType;
+    let (nx, ny) = (angle.cos(), angle.sin());
+    let side = |p: &Point| p.x * nx + p.y * ny >= 0.0;
+
+    let rotated: Vec<Point> = second
+        .circles
+        .iter()
+        .map(|c| {
+            let p = c.center.unwrap();
+            Point {
+                x: p.x * rotation.cos() - p.y * rotation.sin(),
+                y: p.x * rotation.sin() + p.y * rotation.cos(),
+            }
+        })
+        .collect();
+
+    let mut centers: Vec<Option<Point>> = first
+        .circles
+        .iter()
+        .map(|c| c.center.filter(side))
+        .collect();
+
+    let mut free_places = Vec::new();
+    for (i, center) in rotated.iter().enumerate() {
+        if side(center) {
+            continue;
+        }
+        match centers[i] {
+            None => centers[i] = Some(*center),
+            Some(_) => free_places.push((second.circles[i].radius, *center)),
+        }
+    }
+
+    let mut unplaced: Vec<usize> = (0..centers.len())
+        .filter(|&i| centers[i].is_none())
+        .collect();
+    unplaced.sort_by(|&a, &b| {
+        first.circles[b]
+            .radius
+            .partial_cmp(&first.circles[a].radius)
+            .unwrap()
+    });
+    free_places.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap());
+
+    // places of the smallest taken twice circles stay empty when there are more of them
+    let mut free_places = free_places.into_iter();
+    let radius = first.main_circle_radius.max(second.main_circle_radius);
+    for i in unplaced {
+        centers[i] = Some(match free_places.next() {
+            Some((_, place)) => place,
+            None => random_point(radius - first.circles[i].radius, rng),
+        });
+    }
+
+    first
+        .circles
+        .iter()
+        .zip(centers)
+        .map(|(c, center)| Circle::new(c.radius, center.unwrap()))
+        .collect()
+}
+
+/// Relocate random circle into random place or swap centers of two circles.
+fn mutate(circles: &mut Vec<Circle>, rng: &mut StdRng) {
+    if circles.is_empty() {
+        return;
+    }
+    let i = rng.gen_range(0..circles.len());
+
+    if rng.gen_bool(0.5) || circles.len() < 2 {
+        let radius = enclosing_radius(circles);
+        circles[i].center = Some(random_point(radius - circles[i].radius, rng));
+    } else {
+        let j = (i + rng.gen_range(1..circles.len())) % circles.len();
+        let center = circles[i].center;
+        circles[i].center = circles[j].center;
+        circles[j].center = center;
+    }
+}
+
+fn diversity(population: &Vec<Individual>) -> FloatType {
+    let mut total = 0.0;
+    let mut pairs = 0;
+    for i in 0..population.len() {
+        for j in (i + 1)..population.len() {
+            total += population[i].distance(&population[j]);
+            pairs += 1;
+        }
+    }
+
+    if pairs == 0 {
+        return 0.0;
+    }
+    total / pairs as FloatType
+}
+
+fn report(
+    generation: usize,
+    population: &Vec<Individual>,
+    accepted_children: usize,
+    time: FloatType,
+) -> GenerationReport {
+    GenerationReport {
+        generation,
+        best_main_circle_radius: population
+            .iter()
+            .filter(|individual| individual.is_valid)
+            .map(|individual| individual.main_circle_radius)
+            .fold(FloatType::MAX, FloatType::min),
+        mean_main_circle_radius: population
+            .iter()
+            .map(|individual| individual.main_circle_radius)
+            .sum::<FloatType>()
+            / population.len() as FloatType,
+        diversity: diversity(population),
+        accepted_children,
+        time,
+    }
+}
+
+/// Memetic algorithm: random initial population, children from crossover of two parents
+/// chosen by binary tournament and mutation, every child is locally optimised with
+/// `dichotomy_step_ralgo`. The best `population_size` individuals survive. Fails on
+/// invalid parameters (see `MemeticParams::validate`) or no circles.
+pub fn memetic(radiuses: &Vec<FloatType>, params: &MemeticParams) -> Result<MemeticResult, String> {
+    params.validate()?;
+    if radiuses.is_empty() {
+        return Err("No circles to pack".to_string());
+    }
+
+    let mut rng = StdRng::seed_from_u64(params.seed);
+    let mut reports = Vec::new();
+
+    let arrangements: Vec<Vec<Circle>> = (0..params.population_size)
+        .map(|_| random_start(radiuses, params.density, &mut rng).1)
+        .collect();
+    let (time, mut population) = measure_time(|| {
+        arrangements
+            .into_par_iter()
+            .map(|circles| Individual::optimise(circles, params))
+            .collect::<Vec<Individual>>()
+    });
+    population.sort_by(Individual::compare);
+    reports.push(report(0, &population, population.len(), time));
+
+    for generation in 1..=params.generations {
+        let arrangements: Vec<Vec<Circle>> = (0..params.offspring)
+            .map(|_| {
+                let mut tournament = || {
+                    let (a, b) = (
+                        rng.gen_range(0..population.len()),
+                        rng.gen_range(0..population.len()),
+                    );
+                    a.min(b)
+                };
+                let (first, second) = (tournament(), tournament());
+
+                let mut circles = crossover(&population[first], &population[second], &mut rng);
+                if rng.gen_bool(params.mutation_rate) {
+                    mutate(&mut circles, &mut rng);
+                }
+                circles
+            })
+            .collect();
+
+        let (time, children) = measure_time(|| {
+            arrangements
+                .into_par_iter()
+                .map(|circles| Individual::optimise(circles, params))
+                .collect::<Vec<Individual>>()
+        });
+
+        let mut accepted_children = 0;
+        for child in children {
+            let is_duplicate = population.iter().any(|individual| {
+                individual.compare(&child).is_le()
+                    && individual.distance(&child) < params.min_distance
+            });
+            if !is_duplicate {
+                population.push(child);
+                accepted_children += 1;
+            }
+        }
+        population.sort_by(Individual::compare);
+        population.truncate(params.population_size);
+
+        reports.push(report(generation, &population, accepted_children, time));
+    }
+
+    let best = population
+        .into_iter()
+        .find(|individual| individual.is_valid)
+        .unwrap_or(Individual {
+            main_circle_radius: FloatType::MAX,
+            circles: Vec::new(),
+            is_valid: false,
+        });
+
+    Ok(MemeticResult {
+        main_circle_radius: best.main_circle_radius,
+        circles: best.circles,
+        reports,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_parameters_are_rejected() {
+        let radiuses = vec![1.0, 2.0, 3.0];
+        let params = MemeticParams::default();
+
+        assert!(memetic(&radiuses, &params.with_population_size(0)).is_err());
+        assert!(memetic(&radiuses, &params.with_mutation_rate(1.5)).is_err());
+        assert!(memetic(&Vec::new(), &params).is_err());
+    }
+
+    #[test]
+    fn mutation_of_no_circles() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut circles = Vec::new();
+        mutate(&mut circles, &mut rng);
+        assert!(circles.is_empty());
+    }
+}
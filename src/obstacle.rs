@@ -0,0 +1,329 @@
+use std::str::FromStr;
+
+use crate::{circle::Circle, point::Point, utils::FloatType};
+
+/// Vertices of a polygonal obstacle in order along its boundary, there are at least 3 of
+/// them, so it can be built only with `Obstacle::polygon`.
+#[derive(Clone, Debug)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    fn map(&self, f: impl Fn(&Point) -> Point) -> Self {
+        Polygon {
+            vertices: self.vertices.iter().map(f).collect(),
+        }
+    }
+}
+
+/// Forbidden zone inside the main circle that no packed circle may intersect.
+#[derive(Clone, Debug)]
+pub enum Obstacle {
+    Circle { center: Point, radius: FloatType },
+    Rectangle { min: Point, max: Point },
+    Polygon(Polygon),
+}
+
+impl Obstacle {
+    pub fn circle(center: Point, radius: FloatType) -> Self {
+        Obstacle::Circle { center, radius }
+    }
+
+    pub fn rectangle(min: Point, max: Point) -> Self {
+        Obstacle::Rectangle { min, max }
+    }
+
+    /// Polygon with at least 3 vertices, given in order along its boundary.
+    pub fn polygon(vertices: Vec<Point>) -> Result<Self, String> {
+        if vertices.len() < 3 {
+            return Err(format!(
+                "Polygon obstacle needs at least 3 vertices, found {}",
+                vertices.len()
+            ));
+        }
+
+        Ok(Obstacle::Polygon(Polygon { vertices }))
+    }
+
+    /// Vertices of a polygonal obstacle, rectangles are expanded to their four corners.
+    pub fn vertices(&self) -> Vec<Point> {
+        match self {
+            Obstacle::Circle { .. } => vec![],
+            Obstacle::Rectangle { min, max } => vec![
+                Point { x: min.x, y: min.y },
+                Point { x: max.x, y: min.y },
+                Point { x: max.x, y: max.y },
+                Point { x: min.x, y: max.y },
+            ],
+            Obstacle::Polygon(polygon) => polygon.vertices.clone(),
+        }
+    }
+
+    /// Same obstacle shifted by the given vector.
+    pub fn translated(&self, shift: &Point) -> Self {
+        let move_point = |p: &Point| Point {
+            x: p.x + shift.x,
+            y: p.y + shift.y,
+        };
+
+        match self {
+            Obstacle::Circle { center, radius } => Obstacle::Circle {
+                center: move_point(center),
+                radius: *radius,
+            },
+            Obstacle::Rectangle { min, max } => Obstacle::Rectangle {
+                min: move_point(min),
+                max: move_point(max),
+            },
+            Obstacle::Polygon(polygon) => Obstacle::Polygon(polygon.map(move_point)),
+        }
+    }
+
+    /// Same obstacle scaled by the given factor with center (0, 0).
+    pub fn scaled(&self, factor: FloatType) -> Self {
+        let scale_point = |p: &Point| Point {
+            x: p.x * factor,
+            y: p.y * factor,
+        };
+
+        match self {
+            Obstacle::Circle { center, radius } => Obstacle::Circle {
+                center: scale_point(center),
+                radius: radius * factor,
+            },
+            Obstacle::Rectangle { min, max } => Obstacle::Rectangle {
+                min: scale_point(min),
+                max: scale_point(max),
+            },
+            Obstacle::Polygon(polygon) => Obstacle::Polygon(polygon.map(scale_point)),
+        }
+    }
+
+    /// Discs `(center, radius)` whose union's convex hull covers the obstacle.
+    /// Obstacle is inside the main circle when all of them are.
+    pub fn bounding_discs(&self) -> Vec<(Point, FloatType)> {
+        match self {
+            Obstacle::Circle { center, radius } => vec![(*center, *radius)],
+            _ => self.vertices().into_iter().map(|p| (p, 0.0)).collect(),
+        }
+    }
+
+    /// Largest distance from (0, 0) to any point of the obstacle.
+    pub fn max_extent(&self) -> FloatType {
+        match self {
+            Obstacle::Circle { center, radius } => {
+                (center.x.powi(2) + center.y.powi(2)).sqrt() + radius
+            }
+            _ => self
+                .vertices()
+                .iter()
+                .map(|p| (p.x.powi(2) + p.y.powi(2)).sqrt())
+                .fold(0.0, FloatType::max),
+        }
+    }
+
+    /// Even-odd rule test of point inside polygon.
+    fn contains(vertices: &[Point], point: &Point) -> bool {
+        let mut inside = false;
+        let mut j = vertices.len() - 1;
+        for i in 0..vertices.len() {
+            let (pi, pj) = (&vertices[i], &vertices[j]);
+            if (pi.y > point.y) != (pj.y > point.y)
+                && point.x < (pj.x - pi.x) * (point.y - pi.y) / (pj.y - pi.y) + pi.x
+            {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+
+    fn closest_boundary_point(vertices: &[Point], point: &Point) -> Point {
+        let mut closest = vertices[0];
+        let mut closest_distance = FloatType::MAX;
+
+        for i in 0..vertices.len() {
+            let (a, b) = (&vertices[i], &vertices[(i + 1) % vertices.len()]);
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            let length = dx.powi(2) + dy.powi(2);
+
+            let t = if length > 0.0 {
+                (((point.x - a.x) * dx + (point.y - a.y) * dy) / length).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let candidate = Point {
+                x: a.x + t * dx,
+                y: a.y + t * dy,
+            };
+            let distance = (point.x - candidate.x).powi(2) + (point.y - candidate.y).powi(2);
+
+            if distance < closest_distance {
+                (closest, closest_distance) = (candidate, distance);
+            }
+        }
+
+        closest
+    }
+
+    /// Penalty term of circle with given center and radius and its subgradient by center.
+    /// The term is positive only when the circle intersects the obstacle.
+    pub fn penalty(&self, center: &Point, radius: FloatType) -> (FloatType, Point) {
+        match self {
+            Obstacle::Circle {
+                center: obstacle_center,
+                radius: obstacle_radius,
+            } => {
+                let (dx, dy) = (center.x - obstacle_center.x, center.y - obstacle_center.y);
+                (
+                    (radius + obstacle_radius).powi(2) - dx.powi(2) - dy.powi(2),
+                    Point { x: -dx, y: -dy },
+                )
+            }
+            _ => {
+                let vertices = self.vertices();
+                let closest = Obstacle::closest_boundary_point(&vertices, center);
+                let (dx, dy) = (center.x - closest.x, center.y - closest.y);
+                let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+
+                if Obstacle::contains(&vertices, center) {
+                    // center is inside, so circle must be pushed out through the closest edge
+                    let scale = if distance > 0.0 {
+                        (radius + distance) / distance
+                    } else {
+                        0.0
+                    };
+                    (
+                        (radius + distance).powi(2),
+                        Point {
+                            x: scale * dx,
+                            y: scale * dy,
+                        },
+                    )
+                } else {
+                    (radius.powi(2) - distance.powi(2), Point { x: -dx, y: -dy })
+                }
+            }
+        }
+    }
+
+    pub fn overlap(&self, circle: &Circle) -> bool {
+        if let Some(center) = circle.center.as_ref() {
+            match self {
+                Obstacle::Circle {
+                    center: obstacle_center,
+                    radius,
+                } => circle.overlap(&Circle::new(*radius, *obstacle_center)),
+                _ => {
+                    let vertices = self.vertices();
+                    let closest = Obstacle::closest_boundary_point(&vertices, center);
+                    let distance =
+                        ((center.x - closest.x).powi(2) + (center.y - closest.y).powi(2)).sqrt();
+
+                    Obstacle::contains(&vertices, center) || distance <= circle.radius
+                }
+            }
+        } else {
+            false
+        }
+    }
+}
+
+/// `circle:x,y,r`, `rectangle:x1,y1,x2,y2` (opposite corners) or `polygon:x1,y1,x2,y2,...`.
+impl FromStr for Obstacle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, numbers) = s
+            .split_once(':')
+            .ok_or(format!("Invalid obstacle '{s}', expected <kind>:<numbers>"))?;
+        let numbers = numbers
+            .split(',')
+            .map(|number| {
+                number
+                    .trim()
+                    .parse::<FloatType>()
+                    .ok()
+                    .filter(|number| number.is_finite())
+                    .ok_or(format!("Invalid number '{number}' in obstacle '{s}'"))
+            })
+            .collect::<Result<Vec<FloatType>, String>>()?;
+        let point = |i: usize| Point {
+            x: numbers[2 * i],
+            y: numbers[2 * i + 1],
+        };
+
+        match (kind, numbers.len()) {
+            ("circle", 3) if numbers[2] > 0.0 => Ok(Obstacle::circle(point(0), numbers[2])),
+            ("circle", _) => Err(format!("Obstacle '{s}' needs center and positive radius")),
+            ("rectangle", 4) => {
+                let (a, b) = (point(0), point(1));
+                Ok(Obstacle::rectangle(
+                    Point {
+                        x: a.x.min(b.x),
+                        y: a.y.min(b.y),
+                    },
+                    Point {
+                        x: a.x.max(b.x),
+                        y: a.y.max(b.y),
+                    },
+                ))
+            }
+            ("rectangle", _) => Err(format!("Obstacle '{s}' needs two corners")),
+            ("polygon", n) if n % 2 == 0 => Obstacle::polygon((0..n / 2).map(point).collect()),
+            ("polygon", _) => Err(format!("Obstacle '{s}' has odd number of coordinates")),
+            _ => Err(format!(
+                "Unknown obstacle '{kind}', expected circle, rectangle or polygon"
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_obstacles() {
+        match "circle:1,-2,0.5".parse::<Obstacle>().unwrap() {
+            Obstacle::Circle { center, radius } => {
+                assert_eq!((center.x, center.y, radius), (1.0, -2.0, 0.5))
+            }
+            obstacle => panic!("Expected circle, found {obstacle:?}"),
+        }
+        match "rectangle:3,1,-1,2".parse::<Obstacle>().unwrap() {
+            Obstacle::Rectangle { min, max } => {
+                assert_eq!((min.x, min.y, max.x, max.y), (-1.0, 1.0, 3.0, 2.0))
+            }
+            obstacle => panic!("Expected rectangle, found {obstacle:?}"),
+        }
+        let polygon = "polygon:0,0,2,0,1,1".parse::<Obstacle>().unwrap();
+        assert_eq!(polygon.vertices().len(), 3);
+        assert_eq!(
+            (polygon.vertices()[2].x, polygon.vertices()[2].y),
+            (1.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_obstacles() {
+        for obstacle in [
+            "circle:0,0",
+            "circle:0,0,-1",
+            "rectangle:0,0,1",
+            "polygon:0,0,1,1",
+            "polygon:0,0,1,1,2",
+            "triangle:0,0,1,0,0,1",
+            "circle:0,0,x",
+            "circle",
+        ] {
+            assert!(obstacle.parse::<Obstacle>().is_err(), "{obstacle}");
+        }
+        assert!(Obstacle::polygon(vec![Point { x: 0.0, y: 0.0 }; 2]).is_err());
+    }
+}
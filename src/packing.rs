@@ -4,7 +4,10 @@ use rand::SeedableRng;
 use std::cmp::min;
 
 use crate::circle::*;
+use crate::obstacle::Obstacle;
 use crate::point::Point;
+use crate::real::Real;
+use crate::scaling::Scaling;
 use crate::utils::FloatType;
 
 fn get_rotated_point(y_coord: FloatType, angle: FloatType) -> Point {
@@ -130,19 +133,63 @@ fn extra_angle(r1: FloatType, r2: FloatType, main_circle_radius: FloatType) -> F
     return (r1 / (main_circle_radius - r1)).asin() + (r2 / (main_circle_radius - r2)).asin();
 }
 
-fn pack_circles(radiuses: &Vec<FloatType>, main_circle_radius: FloatType) -> Option<Vec<Circle>> {
+/// Find the smallest angle (from 0 with fixed step) where circle touching the main circle
+/// does not intersect any obstacle.
+fn find_free_start_angle(
+    radius: FloatType,
+    main_circle_radius: FloatType,
+    obstacles: &[Obstacle],
+) -> Option<FloatType> {
+    const STEPS: u32 = 360;
+
+    (0..STEPS)
+        .map(|step| {
+            2.0 * std::f64::consts::PI as FloatType * step as FloatType / STEPS as FloatType
+        })
+        .find(|&angle| {
+            let circle = Circle::new(
+                radius,
+                get_rotated_point(main_circle_radius - radius, angle),
+            );
+            !circle.is_overlap_obstacles(obstacles)
+        })
+}
+
+/// Gap between circles, bisection tolerance and deltas of the heuristic are absolute, so
+/// the instance is mapped to the canonical scale first and the packing is mapped back.
+pub fn pack_circles(
+    radiuses: &Vec<FloatType>,
+    main_circle_radius: FloatType,
+    obstacles: &[Obstacle],
+) -> Option<Vec<Circle>> {
+    let scaling = Scaling::canonical(radiuses.iter().copied());
+    let circles = pack_circles_canonical(
+        &radiuses.iter().map(|&radius| scaling.scale(radius)).collect(),
+        scaling.scale(main_circle_radius),
+        &scaling.scale_obstacles(obstacles),
+    )?;
+
+    Some(scaling.unscale_circles(&circles))
+}
+
+fn pack_circles_canonical(
+    radiuses: &Vec<FloatType>,
+    main_circle_radius: FloatType,
+    obstacles: &[Obstacle],
+) -> Option<Vec<Circle>> {
     let mut circles: Vec<Circle> = radiuses
         .iter()
         .map(|&radius| Circle::with_radius(radius))
         .collect();
 
-    circles[0].center = Some(Point {
-        x: 0.0,
-        y: main_circle_radius - circles[0].radius,
-    });
+    let start_angle = find_free_start_angle(circles[0].radius, main_circle_radius, obstacles)?;
+    circles[0].center = Some(get_rotated_point(
+        main_circle_radius - circles[0].radius,
+        start_angle,
+    ));
 
     let mut level_of_placed_circle_indices: Vec<usize> = vec![0];
-    let mut prev_circle_angle: FloatType = 0.0;
+    let mut prev_circle_angle: FloatType = start_angle;
 
     for index in 1..circles.len() {
         let approximate_angle = prev_circle_angle
@@ -166,7 +213,7 @@ fn pack_circles(radiuses: &Vec<FloatType>, main_circle_radius: FloatType) -> Opt
                 radius: circles[index].radius,
             };
 
-            match !new_circle.is_overlap(&circles) {
+            match !new_circle.is_overlap(&circles) && !new_circle.is_overlap_obstacles(obstacles) {
                 true => {
                     right = angle;
                     new_circle_angle = angle;
@@ -208,6 +255,7 @@ fn pack_circles(radiuses: &Vec<FloatType>, main_circle_radius: FloatType) -> Opt
 
                     if new_circle.is_inside_main_circle(main_circle_radius)
                         && !new_circle.is_overlap(&circles)
+                        && !new_circle.is_overlap_obstacles(obstacles)
                     {
                         circles[i] = new_circle;
                         break 'circles_loop;
@@ -248,6 +296,7 @@ fn pack_circles(radiuses: &Vec<FloatType>, main_circle_radius: FloatType) -> Opt
 
                     if new_circle.is_inside_main_circle(main_circle_radius)
                         && !new_circle.is_overlap(&circles)
+                        && !new_circle.is_overlap_obstacles(obstacles)
                     {
                         circles[i] = new_circle;
                         new_level_of_placed_circle_indices.push(i);
@@ -266,10 +315,12 @@ fn pack_circles(radiuses: &Vec<FloatType>, main_circle_radius: FloatType) -> Opt
     Some(circles)
 }
 
-pub fn is_valid_pack(main_circle_radius: FloatType, circles: &Vec<Circle>) -> bool {
+/// Generic over the number type, so that packings refined with `BigFloat` are validated
+/// with the same precision.
+pub fn is_valid_pack<T: Real>(main_circle_radius: T, circles: &Vec<Circle<T>>) -> bool {
     if circles
         .iter()
-        .any(|circle| !circle.is_inside_main_circle(main_circle_radius))
+        .any(|circle| !circle.is_inside_main_circle(main_circle_radius.clone()))
     {
         return false;
     }
@@ -285,13 +336,138 @@ pub fn is_valid_pack(main_circle_radius: FloatType, circles: &Vec<Circle>) -> bo
     true
 }
 
+/// Make packing valid: move centers away from (0, 0) by the smallest homothety that
+/// separates overlapping circles and take the smallest main circle containing all of them.
+/// Circles with coincident centers can't be separated, the result is invalid then.
+pub fn restore_pack(circles: &Vec<Circle>) -> (FloatType, Vec<Circle>) {
+    let centers: Vec<Point> = circles
+        .iter()
+        .map(|c| *c.center.as_ref().expect("Valid center"))
+        .collect();
+
+    let mut scale: FloatType = 1.0;
+    for i in 0..circles.len() {
+        for j in (i + 1)..circles.len() {
+            let distance = ((centers[i].x - centers[j].x).powi(2)
+                + (centers[i].y - centers[j].y).powi(2))
+            .sqrt();
+            if distance > 0.0 {
+                scale = scale.max((circles[i].radius + circles[j].radius) / distance);
+            }
+        }
+    }
+
+    let mut margin = 4.0 * FloatType::EPSILON;
+    loop {
+        let factor = scale * (1.0 + margin);
+        let new_circles: Vec<Circle> = circles
+            .iter()
+            .zip(centers.iter())
+            .map(|(c, center)| {
+                Circle::new(
+                    c.radius,
+                    Point {
+                        x: center.x * factor,
+                        y: center.y * factor,
+                    },
+                )
+            })
+            .collect();
+        let main_circle_radius = new_circles
+            .iter()
+            .map(|c| {
+                let center = c.center.unwrap();
+                (center.x.powi(2) + center.y.powi(2)).sqrt() + c.radius
+            })
+            .fold(0.0, FloatType::max)
+            * (1.0 + margin);
+
+        if is_valid_pack(main_circle_radius, &new_circles) || margin > 1e-6 {
+            return (main_circle_radius, new_circles);
+        }
+        margin *= 2.0;
+    }
+}
+
+pub fn is_valid_pack_with_obstacles(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+    obstacles: &[Obstacle],
+) -> bool {
+    is_valid_pack_in_container(main_circle_radius, &Point::empty(), circles, obstacles)
+}
+
+pub fn is_valid_pack_in_container(
+    main_circle_radius: FloatType,
+    main_circle_center: &Point,
+    circles: &Vec<Circle>,
+    obstacles: &[Obstacle],
+) -> bool {
+    if circles.iter().any(|circle| {
+        !circle.is_inside_container(main_circle_center, main_circle_radius)
+            || circle.is_overlap_obstacles(obstacles)
+    }) {
+        return false;
+    }
+
+    for i in 0..circles.len() {
+        for j in i + 1..circles.len() {
+            if circles[i].overlap(&circles[j]) {
+                return false;
+            }
+        }
+    }
+
+    true
+}
+
 pub fn find_answer(
     radiuses: &mut Vec<FloatType>,
     number_of_iterations: u32,
+) -> (FloatType, Vec<Circle>) {
+    find_answer_with_obstacles(radiuses, &[], number_of_iterations)
+}
+
+pub fn find_answer_with_obstacles(
+    radiuses: &mut Vec<FloatType>,
+    obstacles: &[Obstacle],
+    number_of_iterations: u32,
+) -> (FloatType, Vec<Circle>) {
+    // heuristic works in the canonical scale, see `pack_circles`
+    let scaling = Scaling::canonical(radiuses.iter().copied());
+    let mut scaled_radiuses: Vec<FloatType> =
+        radiuses.iter().map(|&radius| scaling.scale(radius)).collect();
+    let (main_circle_radius, answer) = find_answer_canonical(
+        &mut scaled_radiuses,
+        &scaling.scale_obstacles(obstacles),
+        number_of_iterations,
+    );
+
+    // radiuses are permuted in the same order as circles of the answer
+    *radiuses = scaled_radiuses
+        .iter()
+        .map(|&radius| scaling.unscale(radius))
+        .collect();
+
+    (
+        scaling.unscale(main_circle_radius),
+        scaling.unscale_circles(&answer),
+    )
+}
+
+fn find_answer_canonical(
+    radiuses: &mut Vec<FloatType>,
+    obstacles: &[Obstacle],
+    number_of_iterations: u32,
 ) -> (FloatType, Vec<Circle>) {
     let number_of_circles: usize = radiuses.len();
+    // obstacles may block the area near (0, 0), so start from a radius that also covers them
+    let obstacles_extent = obstacles
+        .iter()
+        .map(|obstacle| obstacle.max_extent())
+        .fold(0.0, FloatType::max);
     let mut main_circle_radius: FloatType =
-        (radiuses.iter().sum::<FloatType>() as FloatType).ceil();
+        (radiuses.iter().sum::<FloatType>() as FloatType + obstacles_extent).ceil();
 
     let mut answer: Vec<Circle> = (0..number_of_circles).map(|_| Circle::empty()).collect();
     let mut new_circles: Vec<Circle> = Vec::new();
@@ -304,7 +480,7 @@ pub fn find_answer(
         while (right - left).abs() >= 1e-4 {
             let middle = (left + right) / 2.0;
 
-            if let Some(circles) = pack_circles(&radiuses, middle) {
+            if let Some(circles) = pack_circles_canonical(&radiuses, middle, obstacles) {
                 right = middle;
                 new_circles = circles.clone();
             } else {
@@ -316,7 +492,7 @@ pub fn find_answer(
             let new_main_circle_radius: FloatType = 1.001 * right;
 
             if new_main_circle_radius < main_circle_radius
-                && is_valid_pack(new_main_circle_radius, &new_circles)
+                && is_valid_pack_with_obstacles(new_main_circle_radius, &new_circles, obstacles)
             {
                 main_circle_radius = new_main_circle_radius;
                 answer = new_circles.clone();
@@ -331,3 +507,122 @@ pub fn find_answer(
 
     return (main_circle_radius, answer);
 }
+
+/// Heuristic packing into the main circle centered at the given point.
+/// Packing is built around (0, 0) with shifted obstacles and then moved to the center.
+pub fn find_answer_in_container(
+    radiuses: &mut Vec<FloatType>,
+    main_circle_center: &Point,
+    obstacles: &[Obstacle],
+    number_of_iterations: u32,
+) -> (FloatType, Vec<Circle>) {
+    let shifted_obstacles: Vec<Obstacle> = obstacles
+        .iter()
+        .map(|obstacle| {
+            obstacle.translated(&Point {
+                x: -main_circle_center.x,
+                y: -main_circle_center.y,
+            })
+        })
+        .collect();
+
+    let (main_circle_radius, circles) =
+        find_answer_with_obstacles(radiuses, &shifted_obstacles, number_of_iterations);
+
+    let circles = circles
+        .iter()
+        .map(|circle| Circle {
+            center: circle.center.map(|center| Point {
+                x: center.x + main_circle_center.x,
+                y: center.y + main_circle_center.y,
+            }),
+            radius: circle.radius,
+        })
+        .collect();
+
+    return (main_circle_radius, circles);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heuristic_doesnt_depend_on_scale() {
+        let mut radiuses: Vec<FloatType> = (1..=12).map(|r| r as FloatType).collect();
+        let mut small_radiuses: Vec<FloatType> = radiuses.iter().map(|r| r / 1024.0).collect();
+
+        let (main_circle_radius, circles) = find_answer(&mut radiuses, 10);
+        let (small_main_circle_radius, small_circles) = find_answer(&mut small_radiuses, 10);
+
+        assert!(is_valid_pack(small_main_circle_radius, &small_circles));
+        assert_eq!(small_main_circle_radius * 1024.0, main_circle_radius);
+        for (small, circle) in small_circles.iter().zip(circles.iter()) {
+            assert_eq!(small.center.unwrap().x * 1024.0, circle.center.unwrap().x);
+            assert_eq!(small.center.unwrap().y * 1024.0, circle.center.unwrap().y);
+        }
+    }
+
+    fn assert_avoids(obstacle: Obstacle) {
+        let mut radiuses: Vec<FloatType> = (1..=6).map(|r| r as FloatType).collect();
+        let obstacles = [obstacle];
+
+        let (main_circle_radius, circles) =
+            find_answer_with_obstacles(&mut radiuses, &obstacles, 10);
+
+        assert_eq!(circles.len(), 6);
+        assert!(is_valid_pack_with_obstacles(
+            main_circle_radius,
+            &circles,
+            &obstacles
+        ));
+        assert!(circles.iter().all(|circle| !obstacles[0].overlap(circle)));
+    }
+
+    #[test]
+    fn heuristic_avoids_circle_obstacle() {
+        assert_avoids(Obstacle::circle(Point { x: 1.0, y: -2.0 }, 3.0));
+    }
+
+    #[test]
+    fn heuristic_avoids_rectangle_obstacle() {
+        assert_avoids(Obstacle::rectangle(
+            Point { x: -2.0, y: -1.0 },
+            Point { x: 4.0, y: 2.0 },
+        ));
+    }
+
+    #[test]
+    fn heuristic_avoids_polygon_obstacle() {
+        assert_avoids(
+            Obstacle::polygon(vec![
+                Point { x: -3.0, y: -2.0 },
+                Point { x: 3.0, y: -2.0 },
+                Point { x: 0.0, y: 4.0 },
+            ])
+            .unwrap(),
+        );
+    }
+
+    #[test]
+    fn packing_through_obstacle_is_invalid() {
+        let circles = vec![
+            Circle::new(1.0, Point { x: -2.0, y: 0.0 }),
+            Circle::new(1.0, Point { x: 2.0, y: 0.0 }),
+        ];
+
+        assert!(is_valid_pack_with_obstacles(3.0, &circles, &[]));
+        for obstacle in [
+            Obstacle::circle(Point { x: 0.0, y: 0.0 }, 1.5),
+            Obstacle::rectangle(Point { x: -1.5, y: -0.5 }, Point { x: 0.0, y: 0.5 }),
+            Obstacle::polygon(vec![
+                Point { x: 2.0, y: -0.5 },
+                Point { x: 2.5, y: 0.5 },
+                Point { x: 1.5, y: 0.5 },
+            ])
+            .unwrap(),
+        ] {
+            assert!(!is_valid_pack_with_obstacles(3.0, &circles, &[obstacle]));
+        }
+    }
+}
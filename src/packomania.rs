@@ -1,70 +1,47 @@
+use std::path::Path;
+
 use crate::{
+    circle::Circle,
     evaluate::{
         heuristic_single_case_console::heuristic_single_case_console,
         random_single_case_console::random_single_case_console,
     },
+    instance::{value::ParseError, Instance, InstanceError},
     packing::is_valid_pack,
-    utils::FloatType,
+    polishing::{polish, PolishingParams, PolishingResult},
+    real::{BigFloat, Real},
+    solution::Solution,
+    utils::{measure_time, FloatType},
+    workspace::workspace,
 };
 
-fn get_input_data(test_number: u32) -> (FloatType, Vec<FloatType>, Vec<(FloatType, FloatType)>) {
-    use std::str::FromStr;
-    use std::{
-        fs::File,
-        io::{BufRead, BufReader},
-    };
+/// First line of packomania file of the test, i.e. the best known `R` as written there.
+fn get_packomania_answer(test_number: u32) -> Result<String, InstanceError> {
+    let path = workspace().packomania_file(test_number);
+    let text = std::fs::read_to_string(&path).map_err(|error| InstanceError::Io {
+        path: path.display().to_string(),
+        error,
+    })?;
 
-    let file_name = format!("./packomania/{test_number}.txt");
-    let file = File::open(file_name).expect("Failed to open file");
-    let reader = BufReader::new(file);
-
-    let mut lines = reader.lines();
-    let first_line = lines.next().expect("Empty file").unwrap();
-    let main_radius: FloatType = first_line
-        .trim()
-        .parse()
-        .expect("Invalid main circle radius");
-
-    let mut radiuses: Vec<FloatType> = Vec::new();
-    let mut normalized_coordinates: Vec<(FloatType, FloatType)> = Vec::new();
-    for line in lines {
-        let input_line = line.expect("Failed to read line");
-        let mut iter = input_line.split_whitespace();
-
-        let radius = FloatType::from_str(iter.next().unwrap()).unwrap();
-        let x = FloatType::from_str(iter.next().unwrap()).unwrap();
-        let y = FloatType::from_str(iter.next().unwrap()).unwrap();
-
-        radiuses.push(radius);
-        normalized_coordinates.push((x, y));
-    }
-
-    return (main_radius, radiuses, normalized_coordinates);
+    Ok(text.lines().next().unwrap_or("").trim().to_string())
 }
 
-fn get_packomania_answer(test_number: u32) -> String {
-    use std::{
-        fs::File,
-        io::{BufRead, BufReader},
+pub fn test_packomania_circles(test_number: u32) -> Result<(), InstanceError> {
+    let instance = Instance::read_packomania(test_number)?;
+    let (Some(main_radius), Some(circles)) = (instance.known_best, instance.circles) else {
+        return Err(InstanceError::Parse {
+            path: workspace()
+                .packomania_file(test_number)
+                .display()
+                .to_string(),
+            error: ParseError::new(1, "Expected packing of circles"),
+        });
     };
+    let radiuses = instance.radiuses;
 
-    let file_name = format!("./packomania/{test_number}.txt");
-    let file = File::open(file_name).expect("Failed to open file");
-    let reader = BufReader::new(file);
-
-    if let Some(Ok(first_line)) = reader.lines().next() {
-        return first_line;
-    } else {
-        "".to_string()
-    }
-}
-
-pub fn test_packomania_circles(test_number: u32) {
-    let (main_radius, radiuses, normalized_coordinates) = get_input_data(test_number);
-
-    let coordinates: Vec<(FloatType, FloatType)> = normalized_coordinates
+    let coordinates: Vec<(FloatType, FloatType)> = circles
         .iter()
-        .map(|(x, y)| (x * main_radius, y * main_radius))
+        .map(|c| (c.center.unwrap().x, c.center.unwrap().y))
         .collect();
 
     for i in 0..5 {
@@ -86,11 +63,13 @@ pub fn test_packomania_circles(test_number: u32) {
             }
         }
     }
+
+    Ok(())
 }
 
-fn matching_decimal_digits_after_point(number1_str: &String, number2_str: &String) -> usize {
-    let digits_after_point1 = number1_str.split('.').nth(1).unwrap();
-    let digits_after_point2 = number2_str.split('.').nth(1).unwrap();
+pub fn matching_decimal_digits_after_point(number1_str: &str, number2_str: &str) -> usize {
+    let digits_after_point1 = number1_str.split('.').nth(1).unwrap_or("");
+    let digits_after_point2 = number2_str.split('.').nth(1).unwrap_or("");
 
     let mut same_prefix_size = 0;
     for (digit1, digit2) in digits_after_point1.chars().zip(digits_after_point2.chars()) {
@@ -103,7 +82,45 @@ fn matching_decimal_digits_after_point(number1_str: &String, number2_str: &Strin
     same_prefix_size
 }
 
-pub fn find_best_heuristic(test_number: u32) {
+/// Decimal digits after the point to which `value` agrees with `record`, computed from
+/// their difference, so formatting (exponent, trailing zeros) and carries like
+/// `0.1999…` vs `0.2000…` don't matter. At most `FloatType::DIGITS`.
+pub fn matching_decimal_digits(value: FloatType, record: FloatType) -> usize {
+    let difference = (value - record).abs();
+    if difference == 0.0 {
+        return FloatType::DIGITS as usize;
+    }
+
+    (-difference.log10())
+        .floor()
+        .clamp(0.0, FloatType::DIGITS as FloatType) as usize
+}
+
+/// Polish in `f64` first and then refine the result in `BigFloat`.
+fn polish_and_refine(
+    main_circle_radius: FloatType,
+    circles: &Vec<Circle>,
+) -> (PolishingResult, PolishingResult<BigFloat>) {
+    let polished = polish(main_circle_radius, circles, &PolishingParams::default());
+    let refined = polish(
+        BigFloat::from_f64(polished.main_circle_radius),
+        &polished.circles.iter().map(|c| c.cast()).collect(),
+        &PolishingParams::default(),
+    );
+
+    (polished, refined)
+}
+
+pub fn save_solution(solution: &Solution, path: &Path) {
+    match solution.write(path) {
+        Ok(()) => println!("Solution is saved to {}", path.display()),
+        Err(error) => println!("Failed to save solution to {}: {error}", path.display()),
+    }
+}
+
+pub fn find_best_heuristic(test_number: u32) -> Result<(), InstanceError> {
+    let packomania_answer_str = get_packomania_answer(test_number)?;
+
     let alpha_array = [1.5, 2.0, 2.5];
     let q1_array = [0.8, 0.85, 0.9, 0.95, 1.0];
     let alpha_q1_pairs = alpha_array
@@ -118,13 +135,28 @@ pub fn find_best_heuristic(test_number: u32) {
             .map(|eps| (*variant, *eps))
             .collect::<Vec<(bool, FloatType)>>();
 
-        let (main_circle_radiuse, mut circles) =
-            heuristic_single_case_console(test_number, &algorithm_params, &alpha_q1_pairs);
+        let (time, (main_circle_radiuse, mut circles, trace)) = measure_time(|| {
+            heuristic_single_case_console(test_number, &algorithm_params, &alpha_q1_pairs)
+        });
 
         circles.sort_by(|a, b| a.radius.partial_cmp(&b.radius).unwrap());
 
+        save_solution(
+            &Solution::new(main_circle_radiuse, circles.clone())
+                .with_instance(test_number.to_string())
+                .with_solver("heuristic")
+                .with_setting("reset_step", variant)
+                .with_setting("eps", format!("{eps_array:?}"))
+                .with_setting("alpha_q1_pairs", format!("{alpha_q1_pairs:?}"))
+                .with_time(time)
+                .with_trace(trace),
+            &workspace().solutions.join(format!(
+                "heuristic-{test_number}-variant-{}.json",
+                index + 1
+            )),
+        );
+
         println!("{} variant", index + 1);
-        let packomania_answer_str = get_packomania_answer(test_number);
         let main_circle_radiuse_str = main_circle_radiuse.to_string();
 
         println!(
@@ -133,6 +165,18 @@ pub fn find_best_heuristic(test_number: u32) {
         );
         println!("{packomania_answer_str}");
         println!("{main_circle_radiuse}");
+
+        let (polished, refined) = polish_and_refine(main_circle_radiuse, &circles);
+        polished.print();
+        println!(
+            "Digits gained: {}",
+            polished.digits_gained(&packomania_answer_str)
+        );
+        refined.print();
+        println!(
+            "Digits gained: {}",
+            refined.digits_gained(&packomania_answer_str)
+        );
         println!(
             "{}",
             is_valid_pack(
@@ -150,9 +194,13 @@ pub fn find_best_heuristic(test_number: u32) {
         }
         println!();
     }
+
+    Ok(())
 }
 
-pub fn find_best_random(test_number: u32) {
+pub fn find_best_random(test_number: u32, seed: u64) -> Result<(), InstanceError> {
+    let packomania_answer_str = get_packomania_answer(test_number)?;
+
     let alpha_array = [1.5, 2.0, 2.5];
     let q1_array = [0.8, 0.85, 0.9, 0.95, 1.0];
     let alpha_q1_pairs = alpha_array
@@ -167,13 +215,30 @@ pub fn find_best_random(test_number: u32) {
             .map(|eps| (*variant, *eps))
             .collect::<Vec<(bool, FloatType)>>();
 
-        let (main_circle_radiuse, mut circles) =
-            random_single_case_console(test_number, 500, &algorithm_params, &alpha_q1_pairs);
+        let (time, (main_circle_radiuse, mut circles, trace, ralgo_params)) = measure_time(|| {
+            random_single_case_console(test_number, 500, seed, &algorithm_params, &alpha_q1_pairs)
+        });
 
         circles.sort_by(|a, b| a.radius.partial_cmp(&b.radius).unwrap());
 
+        save_solution(
+            &Solution::new(main_circle_radiuse, circles.clone())
+                .with_instance(test_number.to_string())
+                .with_solver("random")
+                .with_setting("reset_step", variant)
+                .with_setting("eps", format!("{eps_array:?}"))
+                .with_setting("alpha_q1_pairs", format!("{alpha_q1_pairs:?}"))
+                .with_setting("launches", 500)
+                .with_ralgo_params(&ralgo_params)
+                .with_seed(seed)
+                .with_time(time)
+                .with_trace(trace),
+            &workspace()
+                .solutions
+                .join(format!("random-{test_number}-variant-{}.json", index + 1)),
+        );
+
         println!("{} variant", index + 1);
-        let packomania_answer_str = get_packomania_answer(test_number);
         let main_circle_radiuse_str = main_circle_radiuse.to_string();
 
         println!(
@@ -183,6 +248,18 @@ pub fn find_best_random(test_number: u32) {
         println!("{packomania_answer_str}");
         println!("{main_circle_radiuse}");
 
+        let (polished, refined) = polish_and_refine(main_circle_radiuse, &circles);
+        polished.print();
+        println!(
+            "Digits gained: {}",
+            polished.digits_gained(&packomania_answer_str)
+        );
+        refined.print();
+        println!(
+            "Digits gained: {}",
+            refined.digits_gained(&packomania_answer_str)
+        );
+
         for circle in circles {
             println!(
                 "{} {:.15} {:.15}",
@@ -193,4 +270,6 @@ pub fn find_best_random(test_number: u32) {
         }
         println!();
     }
+
+    Ok(())
 }
@@ -0,0 +1,618 @@
+use rand::{rngs::StdRng, SeedableRng};
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    circle::Circle,
+    packing::{find_answer, is_valid_pack, restore_pack},
+    polishing::{polish, PolishingParams},
+    ralgo::{
+        dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams,
+        step_strategy::StepStrategy,
+    },
+    random_arrangement::random_start,
+    utils::{measure_time, FloatType},
+};
+
+/// Stage of the hybrid pipeline. Start stages add candidates, the others transform
+/// or select them.
+#[derive(Debug, Clone)]
+pub enum Stage {
+    /// Heuristic packing found with `find_answer`.
+    Heuristic { iterations: u32 },
+    /// Random centers in a circle, which area is `sum r_i^2 / density`.
+    RandomStarts {
+        launches: usize,
+        density: FloatType,
+        seed: u64,
+    },
+    /// Compaction of every candidate with `dichotomy_step_ralgo`, relative steps by default.
+    Ralgo {
+        reset_step: bool,
+        eps: FloatType,
+        ralgo_params: RalgoParams,
+    },
+    /// Make every candidate valid with `restore_pack`.
+    Restoration,
+    /// Active-set Newton polishing of every valid candidate.
+    Polishing(PolishingParams),
+    /// Keep `count` best candidates, valid ones first.
+    BestOf { count: usize },
+}
+
+impl Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Heuristic { iterations } => write!(f, "heuristic iterations={iterations}"),
+            Stage::RandomStarts {
+                launches,
+                density,
+                seed,
+            } => write!(
+                f,
+                "random launches={launches} density={density} seed={seed}"
+            ),
+            Stage::Ralgo {
+                reset_step,
+                eps,
+                ralgo_params,
+            } => write!(
+                f,
+                "ralgo {} EPS={} alpha={} q1={} max_iterations={} step={}",
+                if *reset_step { "P" } else { "B" },
+                eps,
+                ralgo_params.alpha,
+                ralgo_params.q1,
+                ralgo_params.max_iterations,
+                ralgo_params.step_strategy
+            ),
+            Stage::Restoration => write!(f, "restoration"),
+            Stage::Polishing(_) => write!(f, "polishing"),
+            Stage::BestOf { count } => write!(f, "best count={count}"),
+        }
+    }
+}
+
+fn parse_value<T: FromStr>(stage: &str, key: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value '{value}' of '{key}' in stage '{stage}'"))
+}
+
+/// Stage from `name key=value ...`, missing keys have default values.
+impl FromStr for Stage {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let mut words = line.split_whitespace();
+        let name = words.next().ok_or("Empty stage")?;
+
+        let mut stage = match name {
+            "heuristic" => Stage::Heuristic { iterations: 100 },
+            "random" => Stage::RandomStarts {
+                launches: 100,
+                density: 0.7,
+                seed: 0,
+            },
+            "ralgo" => Stage::Ralgo {
+                reset_step: true,
+                eps: 0.0,
+                ralgo_params: RalgoParams::default()
+                    .with_alpha(1.5)
+                    .with_q1(1.0)
+                    .with_max_iterations(100_000)
+                    .with_step_strategy(StepStrategy::relative()),
+            },
+            "restoration" => Stage::Restoration,
+            "polishing" => Stage::Polishing(PolishingParams::default()),
+            "best" => Stage::BestOf { count: 1 },
+            _ => return Err(format!("Unknown stage '{name}'")),
+        };
+
+        for word in words {
+            let (key, value) = word.split_once('=').ok_or(format!(
+                "Expected key=value, found '{word}' in stage '{name}'"
+            ))?;
+
+            match (&mut stage, key) {
+                (Stage::Heuristic { iterations }, "iterations") => {
+                    *iterations = parse_value(name, key, value)?
+                }
+                (Stage::RandomStarts { launches, .. }, "launches") => {
+                    *launches = parse_value(name, key, value)?
+                }
+                (Stage::RandomStarts { density, .. }, "density") => {
+                    *density = parse_value(name, key, value)?
+                }
+                (Stage::RandomStarts { seed, .. }, "seed") => {
+                    *seed = parse_value(name, key, value)?
+                }
+                (Stage::Ralgo { reset_step, .. }, "reset_step") => {
+                    *reset_step = parse_value(name, key, value)?
+                }
+                (Stage::Ralgo { eps, .. }, "eps") => *eps = parse_value(name, key, value)?,
+                (Stage::Ralgo { ralgo_params, .. }, "alpha") => {
+                    *ralgo_params = ralgo_params.with_alpha(parse_value(name, key, value)?)
+                }
+                (Stage::Ralgo { ralgo_params, .. }, "q1") => {
+                    *ralgo_params = ralgo_params.with_q1(parse_value(name, key, value)?)
+                }
+                (Stage::Ralgo { ralgo_params, .. }, "max_iterations") => {
+                    *ralgo_params = ralgo_params.with_max_iterations(parse_value(name, key, value)?)
+                }
+                (Stage::Ralgo { ralgo_params, .. }, "step") => {
+                    *ralgo_params = ralgo_params.with_step_strategy(
+                        value
+                            .parse()
+                            .map_err(|error| format!("{error} in stage '{name}'"))?,
+                    )
+                }
+                (Stage::Polishing(params), "active_tolerance") => {
+                    *params = params.with_active_tolerance(parse_value(name, key, value)?)
+                }
+                (Stage::BestOf { count }, "count") => *count = parse_value(name, key, value)?,
+                _ => return Err(format!("Unknown key '{key}' in stage '{name}'")),
+            }
+        }
+
+        Ok(stage)
+    }
+}
+
+/// Packing being improved by the pipeline.
+#[derive(Debug, Clone)]
+pub struct Candidate {
+    /// Working packing, the next stage starts from it. It may be invalid or worse than
+    /// `best` after a stage, e.g. r-algorithm leaves contacts slightly overlapping.
+    pub main_circle_radius: FloatType,
+    pub circles: Vec<Circle>,
+    pub is_valid: bool,
+    /// Best valid `R` and packing the candidate had so far.
+    pub best: Option<(FloatType, Vec<Circle>)>,
+    /// Start stage of the candidate, e.g. `random #3`.
+    pub origin: String,
+    /// Stage that gave `best`.
+    pub best_stage: String,
+}
+
+impl Candidate {
+    pub fn new(main_circle_radius: FloatType, circles: Vec<Circle>, origin: String) -> Self {
+        let is_valid = is_valid_pack(main_circle_radius, &circles);
+        Candidate {
+            main_circle_radius,
+            best: is_valid.then(|| (main_circle_radius, circles.clone())),
+            circles,
+            is_valid,
+            best_stage: origin.clone(),
+            origin,
+        }
+    }
+
+    pub fn best_main_circle_radius(&self) -> Option<FloatType> {
+        self.best
+            .as_ref()
+            .map(|(main_circle_radius, _)| *main_circle_radius)
+    }
+
+    /// Working packing replaced with the stage result, which becomes the best one when
+    /// it is valid and has smaller `R`.
+    fn update(self, main_circle_radius: FloatType, circles: Vec<Circle>, stage: &Stage) -> Self {
+        let is_valid = is_valid_pack(main_circle_radius, &circles);
+        let (best, best_stage) = if is_valid
+            && self
+                .best_main_circle_radius()
+                .is_none_or(|best| main_circle_radius < best)
+        {
+            (
+                Some((main_circle_radius, circles.clone())),
+                stage.to_string(),
+            )
+        } else {
+            (self.best, self.best_stage)
+        };
+
+        Candidate {
+            main_circle_radius,
+            circles,
+            is_valid,
+            best,
+            best_stage,
+            ..self
+        }
+    }
+
+    /// Candidate with its best packing as the working one, `None` without valid packing.
+    fn into_best(self) -> Option<Self> {
+        let (main_circle_radius, circles) = self.best.clone()?;
+        Some(Candidate {
+            main_circle_radius,
+            circles,
+            is_valid: true,
+            ..self
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct StageReport {
+    pub stage: String,
+    pub candidates: usize,
+    /// Candidates which working packing is valid after the stage.
+    pub valid_candidates: usize,
+    /// Best valid `R` found so far, `FloatType::MAX` when there is none.
+    pub best_main_circle_radius: FloatType,
+    pub time: FloatType,
+}
+
+#[derive(Debug)]
+pub struct PipelineResult {
+    /// Candidate with the best valid packing found by any stage, `None` when pipeline
+    /// didn't find any.
+    pub best: Option<Candidate>,
+    pub reports: Vec<StageReport>,
+}
+
+impl PipelineResult {
+    pub fn print(&self) {
+        for report in &self.reports {
+            println!(
+                "{}: candidates = {}, valid = {}, best R = {}, time = {:.3}s",
+                report.stage,
+                report.candidates,
+                report.valid_candidates,
+                report.best_main_circle_radius,
+                report.time
+            );
+        }
+
+        match &self.best {
+            Some(best) => println!(
+                "Best R = {} from '{}', given by '{}'",
+                best.main_circle_radius, best.origin, best.best_stage
+            ),
+            None => println!("No valid packing found"),
+        }
+    }
+}
+
+/// Composable solver: stages are applied in order to the list of candidates.
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    pub stages: Vec<Stage>,
+}
+
+/// Heuristic and random starts, r-algorithm, restoration, polishing and the best of them.
+impl Default for Pipeline {
+    fn default() -> Self {
+        "heuristic; random; ralgo; restoration; polishing; best"
+            .parse()
+            .unwrap()
+    }
+}
+
+/// Pipeline from stages separated with `;` or new lines, e.g.
+/// `random launches=500 seed=1; ralgo reset_step=false alpha=2 step=relative,levels:8; best count=1`.
+impl FromStr for Pipeline {
+    type Err = String;
+
+    fn from_str(description: &str) -> Result<Self, Self::Err> {
+        let stages = description
+            .split(|c| c == ';' || c == '\n')
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(|line| line.parse())
+            .collect::<Result<Vec<Stage>, String>>()?;
+
+        Ok(Pipeline { stages })
+    }
+}
+
+/// Descriptions of stages separated with `; `.
+impl Display for Pipeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, stage) in self.stages.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{stage}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Pipeline {
+    /// Compaction of given packings without start stages, to be used with `run_from`.
+    /// A valid start stays the best packing until a stage finds a smaller `R`, so the
+    /// result is never worse than the start.
+    pub fn refinement() -> Self {
+        "ralgo reset_step=false; restoration; polishing; best"
+            .parse()
+            .unwrap()
+    }
+
+    pub fn with_stage(mut self, stage: Stage) -> Self {
+        self.stages.push(stage);
+        self
+    }
+
+    /// Whether runs differ by seed, i.e. the pipeline has random starts.
+    pub fn is_randomized(&self) -> bool {
+        self.stages
+            .iter()
+            .any(|stage| matches!(stage, Stage::RandomStarts { .. }))
+    }
+
+    /// Seed of the first random starts stage, `None` when the pipeline isn't randomized.
+    pub fn seed(&self) -> Option<u64> {
+        self.stages.iter().find_map(|stage| match stage {
+            Stage::RandomStarts { seed, .. } => Some(*seed),
+            _ => None,
+        })
+    }
+
+    /// Parameters of the first r-algorithm stage.
+    pub fn ralgo_params(&self) -> Option<&RalgoParams> {
+        self.stages.iter().find_map(|stage| match stage {
+            Stage::Ralgo { ralgo_params, .. } => Some(ralgo_params),
+            _ => None,
+        })
+    }
+
+    /// Same pipeline with seeds of random starts shifted by `offset`, for restarts.
+    pub fn with_seed_offset(&self, offset: u64) -> Self {
+        let stages = self
+            .stages
+            .iter()
+            .map(|stage| match stage {
+                Stage::RandomStarts {
+                    launches,
+                    density,
+                    seed,
+                } => Stage::RandomStarts {
+                    launches: *launches,
+                    density: *density,
+                    seed: seed.wrapping_add(offset),
+                },
+                _ => stage.clone(),
+            })
+            .collect();
+
+        Pipeline { stages }
+    }
+
+    fn apply(
+        stage: &Stage,
+        radiuses: &Vec<FloatType>,
+        candidates: Vec<Candidate>,
+    ) -> Vec<Candidate> {
+        match stage {
+            Stage::Heuristic { iterations } => {
+                let (main_circle_radius, circles) = find_answer(&mut radiuses.clone(), *iterations);
+                let mut candidates = candidates;
+                candidates.push(Candidate::new(
+                    main_circle_radius,
+                    circles,
+                    stage.to_string(),
+                ));
+                candidates
+            }
+            Stage::RandomStarts {
+                launches,
+                density,
+                seed,
+            } => {
+                let mut rng = StdRng::seed_from_u64(*seed);
+                let mut candidates = candidates;
+                for launch in 1..=*launches {
+                    let (main_circle_radius, circles) = random_start(radiuses, *density, &mut rng);
+                    candidates.push(Candidate::new(
+                        main_circle_radius,
+                        circles,
+                        format!("random #{launch}"),
+                    ));
+                }
+                candidates
+            }
+            Stage::Ralgo {
+                reset_step,
+                eps,
+                ralgo_params,
+            } => candidates
+                .into_par_iter()
+                .map(|candidate| {
+                    let (main_circle_radius, circles) = dichotomy_step_ralgo(
+                        candidate.main_circle_radius,
+                        &candidate.circles,
+                        *reset_step,
+                        *eps,
+                        ralgo_params,
+                    );
+                    candidate.update(main_circle_radius, circles, stage)
+                })
+                .collect(),
+            Stage::Restoration => candidates
+                .into_par_iter()
+                .map(|candidate| {
+                    if candidate.is_valid {
+                        return candidate;
+                    }
+                    let (main_circle_radius, circles) = restore_pack(&candidate.circles);
+                    candidate.update(main_circle_radius, circles, stage)
+                })
+                .collect(),
+            // the best valid packing is polished, the working one may be worse or invalid
+            Stage::Polishing(params) => candidates
+                .into_par_iter()
+                .map(|candidate| {
+                    let Some((main_circle_radius, circles)) = &candidate.best else {
+                        return candidate;
+                    };
+                    let polished = polish(*main_circle_radius, circles, params);
+                    if !polished.converged {
+                        return candidate;
+                    }
+                    candidate.update(polished.main_circle_radius, polished.circles, stage)
+                })
+                .collect(),
+            Stage::BestOf { count } => {
+                let mut candidates = candidates;
+                candidates.sort_by(|a, b| {
+                    match (a.best_main_circle_radius(), b.best_main_circle_radius()) {
+                        (Some(a), Some(b)) => a.total_cmp(&b),
+                        (Some(_), None) => std::cmp::Ordering::Less,
+                        (None, Some(_)) => std::cmp::Ordering::Greater,
+                        (None, None) => a.main_circle_radius.total_cmp(&b.main_circle_radius),
+                    }
+                });
+                candidates.truncate(*count);
+                candidates
+            }
+        }
+    }
+
+    pub fn run(&self, radiuses: &Vec<FloatType>) -> PipelineResult {
+        self.run_from(radiuses, Vec::new())
+    }
+
+    /// Run stages on the given candidates, e.g. packings loaded from solution files.
+    pub fn run_from(
+        &self,
+        radiuses: &Vec<FloatType>,
+        candidates: Vec<Candidate>,
+    ) -> PipelineResult {
+        let mut candidates = candidates;
+        let mut reports = Vec::new();
+
+        for stage in &self.stages {
+            let time;
+            (time, candidates) = measure_time(|| Pipeline::apply(stage, radiuses, candidates));
+
+            reports.push(StageReport {
+                stage: stage.to_string(),
+                candidates: candidates.len(),
+                valid_candidates: candidates.iter().filter(|c| c.is_valid).count(),
+                best_main_circle_radius: candidates
+                    .iter()
+                    .filter_map(|c| c.best_main_circle_radius())
+                    .fold(FloatType::MAX, FloatType::min),
+                time,
+            });
+        }
+
+        let best = candidates
+            .into_iter()
+            .filter_map(|c| c.into_best())
+            .min_by(|a, b| a.main_circle_radius.total_cmp(&b.main_circle_radius));
+
+        PipelineResult { best, reports }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::point::Point;
+
+    fn two_circles(distance: FloatType) -> Vec<Circle> {
+        vec![
+            Circle::new(
+                1.0,
+                Point {
+                    x: -distance,
+                    y: 0.0,
+                },
+            ),
+            Circle::new(
+                1.0,
+                Point {
+                    x: distance,
+                    y: 0.0,
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn ralgo_stage_description_has_all_parameters() {
+        let stage: Stage = "ralgo max_iterations=500 step=relative,levels:4"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            stage.to_string(),
+            "ralgo P EPS=0 alpha=1.5 q1=1 max_iterations=500 step=4 levels from 0.4R to 0.00009765625R"
+        );
+    }
+
+    #[test]
+    fn best_valid_packing_is_kept() {
+        let stage = Stage::Restoration;
+        let candidate = Candidate::new(2.5, two_circles(1.2), "start".to_string());
+        assert_eq!(candidate.best_main_circle_radius(), Some(2.5));
+
+        // overlapping circles with smaller R, then a valid packing with larger R
+        let candidate = candidate.update(1.9, two_circles(0.9), &stage);
+        assert!(!candidate.is_valid);
+        let candidate = candidate.update(3.0, two_circles(1.5), &stage);
+        assert!(candidate.is_valid);
+        assert_eq!(candidate.best_main_circle_radius(), Some(2.5));
+        assert_eq!(candidate.best_stage, "start");
+
+        let candidate = candidate.update(2.2, two_circles(1.1), &stage);
+        assert_eq!(candidate.best_main_circle_radius(), Some(2.2));
+        assert_eq!(candidate.best_stage, stage.to_string());
+    }
+
+    #[test]
+    fn invalid_working_packing_doesnt_lose_the_result() {
+        let start = Candidate::new(2.5, two_circles(1.2), "start".to_string()).update(
+            1.9,
+            two_circles(0.9),
+            &Stage::Restoration,
+        );
+
+        let result = "best"
+            .parse::<Pipeline>()
+            .unwrap()
+            .run_from(&vec![1.0, 1.0], vec![start]);
+
+        let best = result.best.expect("start packing is valid");
+        assert!(best.is_valid);
+        assert_eq!(best.main_circle_radius, 2.5);
+        assert_eq!(best.circles[1].center.unwrap().x, 1.2);
+        assert_eq!(result.reports[0].best_main_circle_radius, 2.5);
+    }
+
+    #[test]
+    fn refinement_finds_exact_radius_of_three_circles() {
+        // three unit circles touching each other, loosened by a relative 1e-6
+        let exact = 1.0 + 2.0 / (3.0 as FloatType).sqrt();
+        let scale = 1.0 + 1e-6;
+        let circles = (0..3)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as FloatType / 3.0;
+                let distance = (exact - 1.0) * scale;
+                Circle::new(
+                    1.0,
+                    Point {
+                        x: distance * angle.cos(),
+                        y: distance * angle.sin(),
+                    },
+                )
+            })
+            .collect();
+        let start = Candidate::new(exact * scale, circles, "start".to_string());
+        assert!(start.is_valid);
+
+        let result = Pipeline::refinement().run_from(&vec![1.0; 3], vec![start]);
+
+        let best = result.best.expect("start packing is valid");
+        assert!(best.is_valid);
+        assert!(best.main_circle_radius <= exact * scale);
+        assert!(
+            (best.main_circle_radius - exact).abs() < 1e-12,
+            "R = {}, exact = {exact}",
+            best.main_circle_radius
+        );
+    }
+}
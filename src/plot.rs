@@ -1,33 +1,489 @@
 use crate::circle;
+use crate::ellipse::{Ellipse, EllipseContainer};
+use crate::obstacle::Obstacle;
 use crate::point;
 use crate::utils::FloatType;
+use crate::workspace::workspace;
+use plotters::coord::Shift;
 use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use std::{fs, path::Path, str::FromStr};
+
+use self::{pdf::PdfBackend, tikz::write_tikz};
+
+mod pdf;
+mod tikz;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlotFormat {
+    Png,
+    Svg,
+    Pdf,
+    /// Animation, every drawn packing is a frame.
+    Gif,
+    /// Standalone LaTeX document with TikZ picture.
+    Tex,
+}
+
+impl PlotFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "png" => Some(PlotFormat::Png),
+            "svg" => Some(PlotFormat::Svg),
+            "pdf" => Some(PlotFormat::Pdf),
+            "gif" => Some(PlotFormat::Gif),
+            "tex" => Some(PlotFormat::Tex),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CircleLabel {
+    /// 1-based index of the circle in the packing.
+    Index,
+    Radius,
+}
+
+impl FromStr for CircleLabel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "index" => Ok(CircleLabel::Index),
+            "radius" => Ok(CircleLabel::Radius),
+            _ => Err(format!("Unknown label '{s}', expected index or radius")),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct PlotParams {
+    /// Output file, the format is given by the extension (`.png`, `.svg`, `.pdf`, `.gif`
+    /// or `.tex`).
+    pub path: String,
+    pub size: (u32, u32),
+    /// Draw axes with labels and mesh around the packing.
+    pub mesh: bool,
+    pub labels: Option<CircleLabel>,
+    /// Fill circles with colours from smallest to largest radius.
+    pub color_by_radius: bool,
+    /// Draw segments between centers of touching circles (and to the touching point of
+    /// the main circle), when the gap is at most this part of the main circle radius.
+    pub contacts: Option<FloatType>,
+    pub font: String,
+    pub font_size: u32,
+    /// Text in the top left corner.
+    pub caption: Option<String>,
+    /// Drawn region is 1.2 of this radius around the main circle center, main circle
+    /// radius by default. Fixed value keeps the scale of animation frames.
+    pub view_radius: Option<FloatType>,
+}
+
+impl Default for PlotParams {
+    fn default() -> Self {
+        PlotParams {
+            path: workspace().plot.to_string_lossy().to_string(),
+            size: (1000, 1000),
+            mesh: true,
+            labels: None,
+            color_by_radius: false,
+            contacts: None,
+            font: "sans-serif".to_string(),
+            font_size: 30,
+            caption: None,
+            view_radius: None,
+        }
+    }
+}
+
+impl PlotParams {
+    pub fn with_path(self, path: impl Into<String>) -> Self {
+        PlotParams {
+            path: path.into(),
+            ..self
+        }
+    }
+
+    pub fn with_size(self, width: u32, height: u32) -> Self {
+        PlotParams {
+            size: (width, height),
+            ..self
+        }
+    }
+
+    pub fn with_mesh(self, mesh: bool) -> Self {
+        PlotParams { mesh, ..self }
+    }
+
+    pub fn with_labels(self, labels: Option<CircleLabel>) -> Self {
+        PlotParams { labels, ..self }
+    }
+
+    pub fn with_color_by_radius(self, color_by_radius: bool) -> Self {
+        PlotParams {
+            color_by_radius,
+            ..self
+        }
+    }
+
+    pub fn with_contacts(self, contacts: Option<FloatType>) -> Self {
+        PlotParams { contacts, ..self }
+    }
+
+    pub fn with_font(self, font: impl Into<String>, font_size: u32) -> Self {
+        PlotParams {
+            font: font.into(),
+            font_size,
+            ..self
+        }
+    }
+
+    pub fn with_caption(self, caption: Option<String>) -> Self {
+        PlotParams { caption, ..self }
+    }
+
+    pub fn with_view_radius(self, view_radius: Option<FloatType>) -> Self {
+        PlotParams {
+            view_radius,
+            ..self
+        }
+    }
+}
 
 pub fn draw_plot(main_circle_radius: FloatType, circles: &Vec<circle::Circle>) {
-    let plot_size: i32 = 1000;
-    let root =
-        BitMapBackend::new("circle.png", (plot_size as u32, plot_size as u32)).into_drawing_area();
-    root.fill(&WHITE).ok();
+    draw_plot_with_obstacles(main_circle_radius, circles, &[]);
+}
 
-    let spec_size = main_circle_radius * 1.2;
+pub fn draw_plot_with_obstacles(
+    main_circle_radius: FloatType,
+    circles: &Vec<circle::Circle>,
+    obstacles: &[Obstacle],
+) {
+    draw_plot_in_container(
+        main_circle_radius,
+        &point::Point::empty(),
+        circles,
+        obstacles,
+    );
+}
 
-    let mut chart = ChartBuilder::on(&root)
-        .set_all_label_area_size(85)
-        .build_cartesian_2d(-spec_size..spec_size, -spec_size..spec_size)
+pub fn draw_plot_in_container(
+    main_circle_radius: FloatType,
+    main_circle_center: &point::Point,
+    circles: &Vec<circle::Circle>,
+    obstacles: &[Obstacle],
+) {
+    if let Err(error) = draw_plot_with_params(
+        main_circle_radius,
+        main_circle_center,
+        circles,
+        obstacles,
+        &PlotParams::default(),
+    ) {
+        println!("Failed to draw plot: {error}");
+    }
+}
+
+/// Draw the packing to `params.path` as PNG, SVG, PDF, single-frame GIF or TikZ picture.
+pub fn draw_plot_with_params(
+    main_circle_radius: FloatType,
+    main_circle_center: &point::Point,
+    circles: &Vec<circle::Circle>,
+    obstacles: &[Obstacle],
+    params: &PlotParams,
+) -> Result<(), String> {
+    let path = Path::new(&params.path);
+    let format = PlotFormat::from_path(path).ok_or(format!(
+        "{}: unknown plot format, expected .png, .svg, .pdf, .gif or .tex",
+        params.path
+    ))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {error}", params.path))?;
+    }
+
+    match format {
+        PlotFormat::Png => render(
+            BitMapBackend::new(path, params.size).into_drawing_area(),
+            main_circle_radius,
+            main_circle_center,
+            circles,
+            obstacles,
+            params,
+        ),
+        PlotFormat::Svg => render(
+            SVGBackend::new(path, params.size).into_drawing_area(),
+            main_circle_radius,
+            main_circle_center,
+            circles,
+            obstacles,
+            params,
+        ),
+        PlotFormat::Pdf => render(
+            PdfBackend::new(path, params.size).into_drawing_area(),
+            main_circle_radius,
+            main_circle_center,
+            circles,
+            obstacles,
+            params,
+        ),
+        PlotFormat::Gif => render(
+            BitMapBackend::gif(path, params.size, 0)
+                .map_err(|error| format!("{}: {error}", params.path))?
+                .into_drawing_area(),
+            main_circle_radius,
+            main_circle_center,
+            circles,
+            obstacles,
+            params,
+        ),
+        PlotFormat::Tex => write_tikz(
+            main_circle_radius,
+            main_circle_center,
+            circles,
+            obstacles,
+            params,
+        )
+        .map_err(|error| format!("{}: {error}", params.path)),
+    }
+}
+
+/// Colour of the circle on the scale from the smallest to the largest radius.
+fn radius_color(radius: FloatType, min_radius: FloatType, max_radius: FloatType) -> RGBColor {
+    // all radii are equal: the middle of the colour map
+    if max_radius > min_radius {
+        ViridisRGB::get_color_normalized(radius, min_radius, max_radius)
+    } else {
+        ViridisRGB::get_color(0.5)
+    }
+}
+
+fn radius_range(circles: &Vec<circle::Circle>) -> (FloatType, FloatType) {
+    circles
+        .iter()
+        .fold((FloatType::MAX, FloatType::MIN), |(min, max), c| {
+            (min.min(c.radius), max.max(c.radius))
+        })
+}
+
+pub fn label_text(label: CircleLabel, index: usize, circle: &circle::Circle) -> String {
+    match label {
+        CircleLabel::Index => (index + 1).to_string(),
+        CircleLabel::Radius => format_radius(circle.radius),
+    }
+}
+
+/// Segments between centers of circles with gap at most `tolerance * R`, and from centers
+/// to the main circle for circles touching it.
+fn contact_edges(
+    main_circle_radius: FloatType,
+    main_circle_center: &point::Point,
+    circles: &Vec<circle::Circle>,
+    tolerance: FloatType,
+) -> Vec<(point::Point, point::Point)> {
+    let gap = tolerance * main_circle_radius;
+    let mut edges = Vec::new();
+
+    for (i, a) in circles.iter().enumerate() {
+        let ca = a.center.unwrap();
+        let (dx, dy) = (ca.x - main_circle_center.x, ca.y - main_circle_center.y);
+        let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+        if main_circle_radius - distance - a.radius <= gap && distance > 0.0 {
+            let scale = main_circle_radius / distance;
+            edges.push((
+                ca,
+                point::Point {
+                    x: main_circle_center.x + dx * scale,
+                    y: main_circle_center.y + dy * scale,
+                },
+            ));
+        }
+
+        for b in circles.iter().skip(i + 1) {
+            let cb = b.center.unwrap();
+            let distance = ((ca.x - cb.x).powi(2) + (ca.y - cb.y).powi(2)).sqrt();
+            if distance - a.radius - b.radius <= gap {
+                edges.push((ca, cb));
+            }
+        }
+    }
+
+    edges
+}
+
+/// Draw frames `(main circle radius, circles, caption)` with the same view radius (the
+/// largest main circle radius): as GIF animation with `frame_delay` milliseconds between
+/// frames for `.gif` path, otherwise as numbered files `name-0001.png`, `name-0002.png`, ...
+/// next to `params.path`.
+pub fn draw_animation(
+    frames: &Vec<(FloatType, Vec<circle::Circle>, String)>,
+    params: &PlotParams,
+    frame_delay: u32,
+) -> Result<(), String> {
+    let params = params.clone().with_view_radius(Some(
+        params.view_radius.unwrap_or(
+            frames
+                .iter()
+                .map(|(main_circle_radius, _, _)| *main_circle_radius)
+                .fold(0.0, FloatType::max),
+        ),
+    ));
+    let path = Path::new(&params.path);
+
+    if PlotFormat::from_path(path) != Some(PlotFormat::Gif) {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path.extension().unwrap_or_default().to_string_lossy();
+
+        for (i, (main_circle_radius, circles, caption)) in frames.iter().enumerate() {
+            let frame_params = params
+                .clone()
+                .with_path(
+                    path.with_file_name(format!("{stem}-{:04}.{extension}", i + 1))
+                        .display()
+                        .to_string(),
+                )
+                .with_caption(Some(caption.clone()));
+            draw_plot_with_params(
+                *main_circle_radius,
+                &point::Point::empty(),
+                circles,
+                &[],
+                &frame_params,
+            )?;
+        }
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {error}", params.path))?;
+    }
+    let root = BitMapBackend::gif(path, params.size, frame_delay)
+        .map_err(|error| format!("{}: {error}", params.path))?
+        .into_drawing_area();
+
+    for (main_circle_radius, circles, caption) in frames {
+        render(
+            root.clone(),
+            *main_circle_radius,
+            &point::Point::empty(),
+            circles,
+            &[],
+            &params.clone().with_caption(Some(caption.clone())),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn render<DB: DrawingBackend>(
+    root: DrawingArea<DB, Shift>,
+    main_circle_radius: FloatType,
+    main_circle_center: &point::Point,
+    circles: &Vec<circle::Circle>,
+    obstacles: &[Obstacle],
+    params: &PlotParams,
+) -> Result<(), String> {
+    root.fill(&WHITE)
+        .map_err(|error| format!("{}: {error}", params.path))?;
+    // packing is drawn in the centered square, so circles stay round
+    let (width, height) = root.dim_in_pixel();
+    let side = width.min(height);
+    let area = root
+        .clone()
+        .shrink(((width - side) / 2, (height - side) / 2), (side, side));
+
+    draw_packing(
+        &area,
+        if params.mesh { 3 * params.font_size } else { 0 },
+        main_circle_radius,
+        main_circle_center,
+        circles,
+        obstacles,
+        params,
+    );
+    if let Some(caption) = &params.caption {
+        root.draw_text(
+            caption,
+            &TextStyle::from((params.font.as_str(), params.font_size)).color(&BLACK),
+            (10, 10),
+        )
+        .map_err(|error| format!("{}: {error}", params.path))?;
+    }
+    root.present()
+        .map_err(|error| format!("{}: {error}", params.path))
+}
+
+/// Draw bins of the same radius on a grid, one packing per cell, to `bins_plot` of the
+/// workspace.
+pub fn draw_bins_plot(
+    bin_radius: FloatType,
+    bins: &Vec<Vec<circle::Circle>>,
+) -> Result<(), String> {
+    let path = &workspace().bins_plot;
+    let cell_size: u32 = 500;
+    let columns = (bins.len() as FloatType).sqrt().ceil().max(1.0) as usize;
+    let rows = ((bins.len() + columns - 1) / columns).max(1);
+
+    let root = BitMapBackend::new(path, (cell_size * columns as u32, cell_size * rows as u32))
+        .into_drawing_area();
+    root.fill(&WHITE)
+        .map_err(|error| format!("{}: {error}", path.display()))?;
+
+    for (area, circles) in root.split_evenly((rows, columns)).iter().zip(bins.iter()) {
+        draw_packing(
+            area,
+            40,
+            bin_radius,
+            &point::Point::empty(),
+            circles,
+            &[],
+            &PlotParams::default().with_font("sans-serif", 15),
+        );
+    }
+
+    root.present()
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn format_radius(radius: FloatType) -> String {
+    let text = format!("{radius:.3}");
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn draw_packing<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    label_area_size: u32,
+    main_circle_radius: FloatType,
+    main_circle_center: &point::Point,
+    circles: &Vec<circle::Circle>,
+    obstacles: &[Obstacle],
+    params: &PlotParams,
+) {
+    let spec_size = params.view_radius.unwrap_or(main_circle_radius) * 1.2;
+
+    let mut chart = ChartBuilder::on(area)
+        .set_all_label_area_size(label_area_size)
+        .build_cartesian_2d(
+            (main_circle_center.x - spec_size)..(main_circle_center.x + spec_size),
+            (main_circle_center.y - spec_size)..(main_circle_center.y + spec_size),
+        )
         .unwrap();
 
-    chart
-        .configure_mesh()
-        .x_labels(10)
-        .y_labels(10)
-        .label_style(TextStyle::from(("bebas neue", 30)))
-        .draw()
-        .ok();
+    if params.mesh {
+        chart
+            .configure_mesh()
+            .x_labels(10)
+            .y_labels(10)
+            .label_style(TextStyle::from((params.font.as_str(), params.font_size)))
+            .draw()
+            .ok();
+    }
 
     let root = chart.plotting_area();
+    let plotting_size = root.dim_in_pixel().0;
 
     let convert_radius = |radius: FloatType| {
-        return (radius * (plot_size - 2 * 85) as FloatType) / (2.0 * spec_size);
+        return (radius * plotting_size as FloatType) / (2.0 * spec_size);
     };
 
     let set_circle = |c: &circle::Circle| {
@@ -52,11 +508,144 @@ pub fn draw_plot(main_circle_radius: FloatType, circles: &Vec<circle::Circle>) {
             );
     };
 
-    let main_circle = circle::Circle::new(main_circle_radius, point::Point { x: 0.0, y: 0.0 });
+    let (min_radius, max_radius) = radius_range(circles);
+    let set_colored_circle = |c: &circle::Circle| {
+        let color = radius_color(c.radius, min_radius, max_radius);
+
+        return EmptyElement::at((c.center.unwrap().x, c.center.unwrap().y))
+            + Circle::new((0, 0), convert_radius(c.radius), color.mix(0.5).filled())
+            + Circle::new(
+                (0, 0),
+                convert_radius(c.radius),
+                ShapeStyle {
+                    color: color.to_rgba(),
+                    filled: false,
+                    stroke_width: 2,
+                },
+            );
+    };
+
+    let obstacle_style = ShapeStyle {
+        color: RED.mix(0.3),
+        filled: true,
+        stroke_width: 2,
+    };
+    obstacles.iter().for_each(|obstacle| match obstacle {
+        Obstacle::Circle { center, radius } => {
+            root.draw(
+                &(EmptyElement::at((center.x, center.y))
+                    + Circle::new((0, 0), convert_radius(*radius), obstacle_style)),
+            )
+            .ok();
+        }
+        _ => {
+            let vertices = obstacle
+                .vertices()
+                .iter()
+                .map(|p| (p.x, p.y))
+                .collect::<Vec<_>>();
+            root.draw(&Polygon::new(vertices, obstacle_style)).ok();
+        }
+    });
+
+    let main_circle = circle::Circle::new(main_circle_radius, *main_circle_center);
     root.draw(&set_circle(&main_circle)).ok();
     circles.iter().for_each(|c| {
-        root.draw(&set_circle(&c)).ok();
+        if params.color_by_radius {
+            root.draw(&set_colored_circle(&c)).ok();
+        } else {
+            root.draw(&set_circle(&c)).ok();
+        }
     });
 
-    root.present().ok();
+    if let Some(tolerance) = params.contacts {
+        let style = ShapeStyle {
+            color: RED.mix(0.8),
+            filled: false,
+            stroke_width: 2,
+        };
+        for (a, b) in contact_edges(main_circle_radius, main_circle_center, circles, tolerance) {
+            root.draw(&PathElement::new(vec![(a.x, a.y), (b.x, b.y)], style))
+                .ok();
+        }
+    }
+
+    if let Some(labels) = params.labels {
+        circles.iter().enumerate().for_each(|(i, c)| {
+            // labels don't get larger than the circle
+            let font_size = (params.font_size as FloatType).min(convert_radius(c.radius) * 0.8);
+            if font_size < 6.0 {
+                return;
+            }
+
+            let text = label_text(labels, i, c);
+            let style = TextStyle::from((params.font.as_str(), font_size))
+                .color(&BLACK)
+                .pos(Pos::new(HPos::Center, VPos::Center));
+            root.draw(&Text::new(
+                text,
+                (c.center.unwrap().x, c.center.unwrap().y),
+                style,
+            ))
+            .ok();
+        });
+    }
+}
+
+/// Draw ellipses in the container to `ellipse_plot` of the workspace.
+pub fn draw_ellipses_plot(
+    main_radius: FloatType,
+    ellipses: &Vec<Ellipse>,
+    container: &EllipseContainer,
+) -> Result<(), String> {
+    let path = &workspace().ellipse_plot;
+    let plot_size: u32 = 1000;
+    let root = BitMapBackend::new(path, (plot_size, plot_size)).into_drawing_area();
+    root.fill(&WHITE)
+        .map_err(|error| format!("{}: {error}", path.display()))?;
+
+    let spec_size = main_radius * container.aspect_ratio.max(1.0) * 1.2;
+
+    let mut chart = ChartBuilder::on(&root)
+        .set_all_label_area_size(85)
+        .build_cartesian_2d(-spec_size..spec_size, -spec_size..spec_size)
+        .unwrap();
+
+    chart
+        .configure_mesh()
+        .x_labels(10)
+        .y_labels(10)
+        .label_style(TextStyle::from(("bebas neue", 30)))
+        .draw()
+        .ok();
+
+    let style = ShapeStyle {
+        color: BLUE.mix(0.6),
+        filled: false,
+        stroke_width: 2,
+    };
+
+    let outline = |ellipse: &Ellipse| {
+        let mut points: Vec<(FloatType, FloatType)> = (0..=200)
+            .map(|k| {
+                let t = 2.0 * std::f64::consts::PI as FloatType * k as FloatType / 200.0;
+                let (point, _) = ellipse.boundary_point(t);
+                (point.x, point.y)
+            })
+            .collect();
+        points.push(points[0]);
+        PathElement::new(points, style)
+    };
+
+    let main_ellipse = Ellipse::new(
+        container.aspect_ratio * main_radius,
+        main_radius,
+        point::Point::empty(),
+        0.0,
+    );
+    chart.draw_series([outline(&main_ellipse)]).ok();
+    chart.draw_series(ellipses.iter().map(outline)).ok();
+
+    root.present()
+        .map_err(|error| format!("{}: {error}", path.display()))
 }
@@ -0,0 +1,304 @@
+use std::{fs, io, path::PathBuf};
+
+use plotters_backend::{
+    text_anchor::{HPos, VPos},
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingBackend, DrawingErrorKind,
+};
+
+/// Bezier control point distance for a quarter of the unit circle.
+const KAPPA: f64 = 0.552_284_749_831;
+
+/// Single-page vector PDF backend, one pixel is one point. Text is set in the standard
+/// Helvetica font, so no font files are needed.
+pub struct PdfBackend {
+    path: PathBuf,
+    size: (u32, u32),
+    content: String,
+    /// Opacities of the graphics states `/GS0`, `/GS1`, ...
+    opacities: Vec<f64>,
+    saved: bool,
+}
+
+impl PdfBackend {
+    pub fn new(path: impl Into<PathBuf>, size: (u32, u32)) -> Self {
+        PdfBackend {
+            path: path.into(),
+            size,
+            content: String::new(),
+            opacities: Vec::new(),
+            saved: false,
+        }
+    }
+
+    fn y(&self, y: i32) -> f64 {
+        self.size.1 as f64 - y as f64
+    }
+
+    /// Save graphics state and set colour and opacity of strokes or fills.
+    fn begin(&mut self, color: BackendColor, stroke_width: u32, fill: bool) {
+        let index = match self.opacities.iter().position(|a| *a == color.alpha) {
+            Some(index) => index,
+            None => {
+                self.opacities.push(color.alpha);
+                self.opacities.len() - 1
+            }
+        };
+        let (r, g, b) = color.rgb;
+        self.content += &format!(
+            "q /GS{index} gs {:.3} {:.3} {:.3} {} {stroke_width} w\n",
+            r as f64 / 255.0,
+            g as f64 / 255.0,
+            b as f64 / 255.0,
+            if fill { "rg" } else { "RG" }
+        );
+    }
+
+    fn end(&mut self, fill: bool) {
+        self.content += if fill { "f Q\n" } else { "S Q\n" };
+    }
+
+    fn path(&mut self, points: &[BackendCoord]) {
+        for (i, (x, y)) in points.iter().enumerate() {
+            self.content += &format!("{x} {} {}\n", self.y(*y), if i == 0 { "m" } else { "l" });
+        }
+    }
+
+    fn write(&mut self) -> io::Result<()> {
+        let (width, height) = self.size;
+        let mut objects = vec![
+            "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+            "<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_string(),
+            format!(
+                "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {width} {height}] \
+                 /Contents 4 0 R /Resources << /Font << /F1 5 0 R >> /ExtGState << {} >> >> >>",
+                (0..self.opacities.len())
+                    .map(|i| format!("/GS{i} {} 0 R", 6 + i))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            format!(
+                "<< /Length {} >>\nstream\n{}endstream",
+                self.content.len(),
+                self.content
+            ),
+            "<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_string(),
+        ];
+        objects.extend(
+            self.opacities
+                .iter()
+                .map(|a| format!("<< /Type /ExtGState /CA {a} /ca {a} >>")),
+        );
+
+        let mut pdf = "%PDF-1.4\n".to_string();
+        let mut offsets = Vec::new();
+        for (i, object) in objects.iter().enumerate() {
+            offsets.push(pdf.len());
+            pdf += &format!("{} 0 obj\n{object}\nendobj\n", i + 1);
+        }
+
+        let xref = pdf.len();
+        pdf += &format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+        for offset in offsets {
+            pdf += &format!("{offset:010} 00000 n \n");
+        }
+        pdf += &format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        );
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, pdf)
+    }
+}
+
+impl DrawingBackend for PdfBackend {
+    type ErrorType = io::Error;
+
+    fn get_size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    fn ensure_prepared(&mut self) -> Result<(), DrawingErrorKind<io::Error>> {
+        Ok(())
+    }
+
+    fn present(&mut self) -> Result<(), DrawingErrorKind<io::Error>> {
+        self.write().map_err(DrawingErrorKind::DrawingError)?;
+        self.saved = true;
+        Ok(())
+    }
+
+    fn draw_pixel(
+        &mut self,
+        point: BackendCoord,
+        color: BackendColor,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        self.draw_rect(point, (point.0 + 1, point.1 + 1), &color, true)
+    }
+
+    fn draw_line<S: BackendStyle>(
+        &mut self,
+        from: BackendCoord,
+        to: BackendCoord,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        self.draw_path([from, to], style)
+    }
+
+    fn draw_rect<S: BackendStyle>(
+        &mut self,
+        upper_left: BackendCoord,
+        bottom_right: BackendCoord,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        self.begin(style.color(), style.stroke_width(), fill);
+        self.content += &format!(
+            "{} {} {} {} re\n",
+            upper_left.0,
+            self.y(bottom_right.1),
+            bottom_right.0 - upper_left.0,
+            bottom_right.1 - upper_left.1
+        );
+        self.end(fill);
+        Ok(())
+    }
+
+    fn draw_path<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        path: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        self.begin(style.color(), style.stroke_width(), false);
+        self.path(&path.into_iter().collect::<Vec<BackendCoord>>());
+        self.end(false);
+        Ok(())
+    }
+
+    fn draw_circle<S: BackendStyle>(
+        &mut self,
+        center: BackendCoord,
+        radius: u32,
+        style: &S,
+        fill: bool,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        self.begin(style.color(), style.stroke_width(), fill);
+
+        let (x, y, r) = (center.0 as f64, self.y(center.1), radius as f64);
+        let k = KAPPA * r;
+        self.content += &format!(
+            "{} {y} m\n\
+             {} {} {} {} {x} {} c\n\
+             {} {} {} {} {} {y} c\n\
+             {} {} {} {} {x} {} c\n\
+             {} {} {} {} {} {y} c\n",
+            x + r,
+            x + r,
+            y + k,
+            x + k,
+            y + r,
+            y + r,
+            x - k,
+            y + r,
+            x - r,
+            y + k,
+            x - r,
+            x - r,
+            y - k,
+            x - k,
+            y - r,
+            y - r,
+            x + k,
+            y - r,
+            x + r,
+            y - k,
+            x + r
+        );
+        self.end(fill);
+        Ok(())
+    }
+
+    fn fill_polygon<S: BackendStyle, I: IntoIterator<Item = BackendCoord>>(
+        &mut self,
+        vert: I,
+        style: &S,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        if style.color().alpha == 0.0 {
+            return Ok(());
+        }
+        self.begin(style.color(), 0, true);
+        self.path(&vert.into_iter().collect::<Vec<BackendCoord>>());
+        self.content += "h ";
+        self.end(true);
+        Ok(())
+    }
+
+    fn draw_text<TStyle: BackendTextStyle>(
+        &mut self,
+        text: &str,
+        style: &TStyle,
+        pos: BackendCoord,
+    ) -> Result<(), DrawingErrorKind<io::Error>> {
+        let color = style.color();
+        if color.alpha == 0.0 {
+            return Ok(());
+        }
+
+        let (width, height) = self.estimate_text_size(text, style)?;
+        let x = match style.anchor().h_pos {
+            HPos::Left => pos.0 as f64,
+            HPos::Center => pos.0 as f64 - width as f64 / 2.0,
+            HPos::Right => pos.0 as f64 - width as f64,
+        };
+        // baseline is about 0.2 of the font size above the bottom of the text box
+        let baseline = match style.anchor().v_pos {
+            VPos::Top => pos.1 as f64 + 0.8 * height as f64,
+            VPos::Center => pos.1 as f64 + 0.3 * height as f64,
+            VPos::Bottom => pos.1 as f64 - 0.2 * height as f64,
+        };
+        let escaped = text
+            .replace('\\', "\\\\")
+            .replace('(', "\\(")
+            .replace(')', "\\)");
+
+        self.begin(color, 0, true);
+        self.content += &format!(
+            "BT /F1 {:.1} Tf {x:.1} {:.1} Td ({escaped}) Tj ET ",
+            style.size(),
+            self.size.1 as f64 - baseline
+        );
+        self.content += "Q\n";
+        Ok(())
+    }
+
+    /// Helvetica digits are 0.556 of the font size wide, which is close enough for labels.
+    fn estimate_text_size<TStyle: BackendTextStyle>(
+        &self,
+        text: &str,
+        style: &TStyle,
+    ) -> Result<(u32, u32), DrawingErrorKind<io::Error>> {
+        Ok((
+            (0.556 * style.size() * text.chars().count() as f64).round() as u32,
+            style.size().round() as u32,
+        ))
+    }
+}
+
+impl Drop for PdfBackend {
+    fn drop(&mut self) {
+        if !self.saved {
+            self.write().ok();
+        }
+    }
+}
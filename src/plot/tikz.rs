@@ -0,0 +1,229 @@
+use std::{fmt::Write as _, fs, io};
+
+use crate::{circle::Circle, obstacle::Obstacle, point::Point, utils::FloatType};
+
+use super::{contact_edges, label_text, radius_color, radius_range, PlotParams};
+
+/// Tick step with 1, 2 or 5 as the first digit, so `range` has about 10 ticks.
+fn tick_step(range: FloatType) -> FloatType {
+    let rough = range / 10.0;
+    let power = (10.0 as FloatType).powf(rough.log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * power)
+        .find(|step| *step >= rough)
+        .unwrap_or(10.0 * power)
+}
+
+/// Tick value without float noise, e.g. `0.3` instead of `0.30000000000000004`.
+fn tick_label(value: FloatType, step: FloatType) -> String {
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    format!("{value:.decimals$}")
+}
+
+/// Font command of LaTeX for the size in points.
+fn font(size: FloatType) -> String {
+    format!("\\fontsize{{{size:.1}}}{{{:.1}}}\\selectfont", 1.2 * size)
+}
+
+/// Standalone LaTeX document with TikZ picture of the packing, written to `params.path`.
+/// One pixel of `params.size` is one point, coordinates of the picture are the packing
+/// ones. Caption below the main circle is `params.caption` or `R`.
+pub fn write_tikz(
+    main_circle_radius: FloatType,
+    main_circle_center: &Point,
+    circles: &Vec<Circle>,
+    obstacles: &[Obstacle],
+    params: &PlotParams,
+) -> io::Result<()> {
+    let spec_size = params.view_radius.unwrap_or(main_circle_radius) * 1.2;
+    let side = params.size.0.min(params.size.1) as FloatType;
+    let unit = side / (2.0 * spec_size);
+    let (cx, cy) = (main_circle_center.x, main_circle_center.y);
+
+    let mut tex = String::new();
+    writeln!(
+        tex,
+        "% Packing of {} circles, R = {main_circle_radius:?}",
+        circles.len()
+    )
+    .unwrap();
+    tex += "\\documentclass[tikz, border=2pt]{standalone}\n\\begin{document}\n";
+    writeln!(tex, "\\begin{{tikzpicture}}[x={unit:.6}pt, y={unit:.6}pt]").unwrap();
+
+    if params.mesh {
+        let step = tick_step(2.0 * spec_size);
+        let (low, high) = (
+            ((cx - spec_size) / step).ceil() as i64,
+            ((cx + spec_size) / step).floor() as i64,
+        );
+        let (bottom, top) = (
+            ((cy - spec_size) / step).ceil() as i64,
+            ((cy + spec_size) / step).floor() as i64,
+        );
+        writeln!(
+            tex,
+            "\\draw[gray!30, very thin, step={step}] ({:.6}, {:.6}) grid ({:.6}, {:.6});",
+            cx - spec_size,
+            cy - spec_size,
+            cx + spec_size,
+            cy + spec_size
+        )
+        .unwrap();
+        writeln!(
+            tex,
+            "\\draw[gray] ({:.6}, {:.6}) rectangle ({:.6}, {:.6});",
+            cx - spec_size,
+            cy - spec_size,
+            cx + spec_size,
+            cy + spec_size
+        )
+        .unwrap();
+        for k in low..=high {
+            writeln!(
+                tex,
+                "\\node[below, font={}] at ({:.6}, {:.6}) {{{}}};",
+                font(params.font_size as FloatType),
+                k as FloatType * step,
+                cy - spec_size,
+                tick_label(k as FloatType * step, step)
+            )
+            .unwrap();
+        }
+        for k in bottom..=top {
+            writeln!(
+                tex,
+                "\\node[left, font={}] at ({:.6}, {:.6}) {{{}}};",
+                font(params.font_size as FloatType),
+                cx - spec_size,
+                k as FloatType * step,
+                tick_label(k as FloatType * step, step)
+            )
+            .unwrap();
+        }
+    }
+
+    for obstacle in obstacles {
+        match obstacle {
+            Obstacle::Circle { center, radius } => writeln!(
+                tex,
+                "\\fill[red, fill opacity=0.3] ({:.6}, {:.6}) circle[radius={radius:.6}];",
+                center.x, center.y
+            )
+            .unwrap(),
+            _ => writeln!(
+                tex,
+                "\\fill[red, fill opacity=0.3] {} -- cycle;",
+                obstacle
+                    .vertices()
+                    .iter()
+                    .map(|p| format!("({:.6}, {:.6})", p.x, p.y))
+                    .collect::<Vec<String>>()
+                    .join(" -- ")
+            )
+            .unwrap(),
+        }
+    }
+
+    writeln!(
+        tex,
+        "\\draw[blue!60, line width=1pt] ({cx:.6}, {cy:.6}) circle[radius={main_circle_radius:.6}];"
+    )
+    .unwrap();
+
+    let (min_radius, max_radius) = radius_range(circles);
+    for (i, c) in circles.iter().enumerate() {
+        let center = c.center.expect("Valid center");
+        if params.color_by_radius {
+            let color = radius_color(c.radius, min_radius, max_radius);
+            writeln!(
+                tex,
+                "\\definecolor{{circle{}}}{{RGB}}{{{}, {}, {}}}",
+                i + 1,
+                color.0,
+                color.1,
+                color.2
+            )
+            .unwrap();
+            writeln!(
+                tex,
+                "\\filldraw[fill=circle{0}, fill opacity=0.5, draw=circle{0}, line width=1pt] \
+                 ({1:.6}, {2:.6}) circle[radius={3:.6}];",
+                i + 1,
+                center.x,
+                center.y,
+                c.radius
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                tex,
+                "\\draw[blue!60, line width=1pt] ({:.6}, {:.6}) circle[radius={:.6}];",
+                center.x, center.y, c.radius
+            )
+            .unwrap();
+            writeln!(
+                tex,
+                "\\fill[blue!60] ({:.6}, {:.6}) circle[radius=1.5pt];",
+                center.x, center.y
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(tolerance) = params.contacts {
+        for (a, b) in contact_edges(main_circle_radius, main_circle_center, circles, tolerance) {
+            writeln!(
+                tex,
+                "\\draw[red!80, line width=0.8pt] ({:.6}, {:.6}) -- ({:.6}, {:.6});",
+                a.x, a.y, b.x, b.y
+            )
+            .unwrap();
+        }
+    }
+
+    if let Some(labels) = params.labels {
+        for (i, c) in circles.iter().enumerate() {
+            // labels don't get larger than the circle
+            let size = (params.font_size as FloatType).min(c.radius * unit * 0.8);
+            if size < 3.0 {
+                continue;
+            }
+            let center = c.center.expect("Valid center");
+            writeln!(
+                tex,
+                "\\node[font={}] at ({:.6}, {:.6}) {{{}}};",
+                font(size),
+                center.x,
+                center.y,
+                label_text(labels, i, c)
+            )
+            .unwrap();
+        }
+    }
+
+    let caption = params
+        .caption
+        .clone()
+        .unwrap_or(format!("$R = {main_circle_radius:.10}$"));
+    // below tick labels of the mesh
+    let caption_shift = if params.mesh {
+        4.0 + 1.5 * params.font_size as FloatType
+    } else {
+        4.0
+    };
+    writeln!(
+        tex,
+        "\\node[below={caption_shift:.1}pt, font={}] at ({cx:.6}, {:.6}) {{{caption}}};",
+        font(params.font_size as FloatType),
+        cy - spec_size
+    )
+    .unwrap();
+
+    tex += "\\end{tikzpicture}\n\\end{document}\n";
+
+    if let Some(parent) = std::path::Path::new(&params.path).parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&params.path, tex)
+}
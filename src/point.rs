@@ -1,13 +1,23 @@
-use crate::utils::FloatType;
-
-#[derive(Clone, Copy, Debug)]
-pub struct Point {
-    pub x: FloatType,
-    pub y: FloatType,
-}
-
-impl Point {
-    pub fn empty() -> Self {
-        Self { x: 0.0, y: 0.0 }
-    }
-}
\ No newline at end of file
+use crate::{real::Real, utils::FloatType};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Point<T = FloatType> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T: Real> Point<T> {
+    pub fn empty() -> Self {
+        Self {
+            x: T::zero(),
+            y: T::zero(),
+        }
+    }
+
+    pub fn cast<U: Real>(&self) -> Point<U> {
+        Point {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+        }
+    }
+}
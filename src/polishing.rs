@@ -0,0 +1,509 @@
+use std::collections::HashSet;
+
+use nalgebra::{DMatrix, DVector};
+
+use crate::{
+    circle::Circle, packing::is_valid_pack, packomania::matching_decimal_digits_after_point,
+    point::Point, real::Real, utils::FloatType,
+};
+
+#[derive(Debug, Clone, Copy)]
+pub struct PolishingParams {
+    /// Constraint is considered active when the gap is smaller than this part of `R`.
+    pub active_tolerance: FloatType,
+    pub max_newton_iterations: usize,
+    pub max_active_set_changes: usize,
+    /// Newton's method stops when KKT residual is smaller than this number
+    /// of machine epsilons (of the number type used) times `R^2`.
+    pub residual_tolerance: FloatType,
+}
+
+impl Default for PolishingParams {
+    fn default() -> Self {
+        PolishingParams {
+            active_tolerance: 1e-4,
+            max_newton_iterations: 30,
+            max_active_set_changes: 50,
+            residual_tolerance: 8.0,
+        }
+    }
+}
+
+impl PolishingParams {
+    pub fn with_active_tolerance(&self, active_tolerance: FloatType) -> Self {
+        PolishingParams {
+            active_tolerance,
+            ..*self
+        }
+    }
+
+    pub fn with_max_newton_iterations(&self, max_newton_iterations: usize) -> Self {
+        PolishingParams {
+            max_newton_iterations,
+            ..*self
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct PolishingResult<T = FloatType> {
+    /// Valid packing: polished contacts are separated by a few ulps.
+    pub main_circle_radius: T,
+    pub circles: Vec<Circle<T>>,
+    pub initial_main_circle_radius: T,
+    pub active_constraints: usize,
+    pub newton_iterations: u32,
+    /// Norm of KKT residual of the active constraints system.
+    pub residual: FloatType,
+    pub converged: bool,
+}
+
+impl<T: Real> PolishingResult<T> {
+    pub fn print(&self) {
+        println!(
+            "Polishing: R = {} -> {}, active = {}, newton iterations = {}, residual = {:e}",
+            self.initial_main_circle_radius,
+            self.main_circle_radius,
+            self.active_constraints,
+            self.newton_iterations,
+            self.residual
+        );
+    }
+
+    /// Decimal digits after the point of `R` matching `record` after polishing minus the ones
+    /// matching before it.
+    pub fn digits_gained(&self, record: &str) -> isize {
+        let matching =
+            |value: &T| matching_decimal_digits_after_point(record, &value.to_string()) as isize;
+
+        matching(&self.main_circle_radius) - matching(&self.initial_main_circle_radius)
+    }
+}
+
+/// Circles pair of every constraint: containment of every circle first, then
+/// non-overlapping of pairs `(i, j)`, `j > i`. `None` is for containment.
+fn constraint_pairs(n: usize) -> Vec<Option<(usize, usize)>> {
+    let mut pairs = vec![None; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            pairs.push(Some((i, j)));
+        }
+    }
+    pairs
+}
+
+/// Constraint `value <= 0` in the same squared form as `calcfg` uses and its sparse
+/// gradient by `x = [x_1..x_n, y_1..y_n, R]`.
+fn constraint<T: Real>(
+    x: &Vec<T>,
+    radiuses: &Vec<T>,
+    pair: Option<(usize, usize)>,
+    k: usize,
+) -> (T, Vec<(usize, T)>) {
+    let n = radiuses.len();
+    let two = T::from_f64(2.0);
+
+    match pair {
+        None => {
+            let free_radius = x[2 * n].clone() - radiuses[k].clone();
+            (
+                x[k].square() + x[n + k].square() - free_radius.square(),
+                vec![
+                    (k, two.clone() * x[k].clone()),
+                    (n + k, two.clone() * x[n + k].clone()),
+                    (2 * n, -two * free_radius),
+                ],
+            )
+        }
+        Some((i, j)) => {
+            let (dx, dy) = (
+                x[i].clone() - x[j].clone(),
+                x[n + i].clone() - x[n + j].clone(),
+            );
+            (
+                (radiuses[i].clone() + radiuses[j].clone()).square() - dx.square() - dy.square(),
+                vec![
+                    (i, -two.clone() * dx.clone()),
+                    (n + i, -two.clone() * dy.clone()),
+                    (j, two.clone() * dx),
+                    (n + j, two * dy),
+                ],
+            )
+        }
+    }
+}
+
+/// Gap of the constraint in distance units, so that active tolerance doesn't depend on scale.
+fn constraint_gap<T: Real>(
+    x: &Vec<T>,
+    radiuses: &Vec<T>,
+    pair: Option<(usize, usize)>,
+    k: usize,
+) -> T {
+    let n = radiuses.len();
+    match pair {
+        None => x[2 * n].clone() - radiuses[k].clone() - (x[k].square() + x[n + k].square()).sqrt(),
+        Some((i, j)) => {
+            ((x[i].clone() - x[j].clone()).square()
+                + (x[n + i].clone() - x[n + j].clone()).square())
+            .sqrt()
+                - radiuses[i].clone()
+                - radiuses[j].clone()
+        }
+    }
+}
+
+/// Lagrangian Hessian of the squared constraints, it doesn't depend on `x`.
+fn lagrangian_hessian(
+    n: usize,
+    pairs: &Vec<Option<(usize, usize)>>,
+    active: &Vec<usize>,
+    multipliers: &Vec<FloatType>,
+) -> DMatrix<FloatType> {
+    let mut hessian = DMatrix::<FloatType>::zeros(2 * n + 1, 2 * n + 1);
+
+    for (index, &k) in active.iter().enumerate() {
+        let mu = multipliers[index];
+        match pairs[k] {
+            None => {
+                hessian[(k, k)] += 2.0 * mu;
+                hessian[(n + k, n + k)] += 2.0 * mu;
+                hessian[(2 * n, 2 * n)] -= 2.0 * mu;
+            }
+            Some((i, j)) => {
+                for offset in [0, n] {
+                    hessian[(offset + i, offset + i)] -= 2.0 * mu;
+                    hessian[(offset + j, offset + j)] -= 2.0 * mu;
+                    hessian[(offset + i, offset + j)] += 2.0 * mu;
+                    hessian[(offset + j, offset + i)] += 2.0 * mu;
+                }
+            }
+        }
+    }
+
+    hessian
+}
+
+fn to_f64_vector<T: Real>(values: &Vec<T>) -> Vec<FloatType> {
+    values.iter().map(|value| value.to_f64()).collect()
+}
+
+/// Newton's method for KKT conditions of `min R` with active constraints as equalities:
+/// `e_R + J^T μ = 0`, `g_A(x) = 0`. Rotation of the whole packing and rattlers make the
+/// system singular, so every step is the least squares solution found with SVD.
+///
+/// Residuals are computed in `T`, while the linear systems are solved in `f64`, so for
+/// multiprecision types it works as iterative refinement beyond `f64` precision.
+fn newton<T: Real>(
+    x: &mut Vec<T>,
+    radiuses: &Vec<T>,
+    active: &Vec<usize>,
+    params: &PolishingParams,
+) -> (u32, FloatType, Vec<T>) {
+    if active.is_empty() {
+        return (0, FloatType::MAX, Vec::new());
+    }
+
+    let n = radiuses.len();
+    let size = 2 * n + 1;
+    let pairs = constraint_pairs(n);
+    let tolerance = params.residual_tolerance * T::epsilon().to_f64() * x[2 * n].to_f64().powi(2);
+
+    let jacobian = |constraints: &Vec<(T, Vec<(usize, T)>)>| {
+        let mut jacobian = DMatrix::<FloatType>::zeros(active.len(), size);
+        for (row, (_, gradient)) in constraints.iter().enumerate() {
+            for (index, value) in gradient.iter() {
+                jacobian[(row, *index)] = value.to_f64();
+            }
+        }
+        jacobian
+    };
+
+    // least squares multipliers estimate
+    let constraints: Vec<_> = active
+        .iter()
+        .map(|&k| constraint(x, radiuses, pairs[k], k))
+        .collect();
+    let mut objective_gradient = DVector::<FloatType>::zeros(size);
+    objective_gradient[2 * n] = 1.0;
+    let mut multipliers: Vec<T> = jacobian(&constraints)
+        .transpose()
+        .svd(true, true)
+        .solve(&(-objective_gradient), 1e-14)
+        .unwrap_or(DVector::zeros(active.len()))
+        .iter()
+        .map(|mu| T::from_f64(*mu))
+        .collect();
+
+    let mut residual = FloatType::MAX;
+    for iteration in 0..params.max_newton_iterations as u32 {
+        let constraints: Vec<_> = active
+            .iter()
+            .map(|&k| constraint(x, radiuses, pairs[k], k))
+            .collect();
+
+        // -(e_R + J^T μ) and -g_A(x)
+        let mut stationarity: Vec<T> = vec![T::zero(); size];
+        stationarity[2 * n] = T::from_f64(1.0);
+        for ((_, gradient), mu) in constraints.iter().zip(multipliers.iter()) {
+            for (index, value) in gradient.iter() {
+                stationarity[*index] = stationarity[*index].clone() + mu.clone() * value.clone();
+            }
+        }
+        let rhs = DVector::from_iterator(
+            size + active.len(),
+            stationarity
+                .iter()
+                .chain(constraints.iter().map(|(value, _)| value))
+                .map(|value| -value.to_f64()),
+        );
+
+        residual = rhs.norm();
+        if residual < tolerance {
+            return (iteration, residual, multipliers);
+        }
+
+        let jacobian = jacobian(&constraints);
+        let mut kkt = DMatrix::<FloatType>::zeros(size + active.len(), size + active.len());
+        kkt.view_mut((0, 0), (size, size))
+            .copy_from(&lagrangian_hessian(
+                n,
+                &pairs,
+                active,
+                &to_f64_vector(&multipliers),
+            ));
+        kkt.view_mut((0, size), (size, active.len()))
+            .copy_from(&jacobian.transpose());
+        kkt.view_mut((size, 0), (active.len(), size))
+            .copy_from(&jacobian);
+
+        let Ok(step) = kkt.svd(true, true).solve(&rhs, 1e-14) else {
+            return (iteration, residual, multipliers);
+        };
+
+        for i in 0..size {
+            x[i] = x[i].clone() + T::from_f64(step[i]);
+        }
+        for (index, mu) in multipliers.iter_mut().enumerate() {
+            *mu = mu.clone() + T::from_f64(step[size + index]);
+        }
+    }
+
+    (params.max_newton_iterations as u32, residual, multipliers)
+}
+
+/// `x + alpha * (y - x)`.
+fn interpolate<T: Real>(x: &Vec<T>, y: &Vec<T>, alpha: FloatType) -> Vec<T> {
+    let alpha = T::from_f64(alpha);
+    x.iter()
+        .zip(y.iter())
+        .map(|(a, b)| a.clone() + alpha.clone() * (b.clone() - a.clone()))
+        .collect()
+}
+
+/// Polish packing found by r-algorithm: identify touching contacts and solve the
+/// resulting equality constrained problem with Newton's method. Constraints with negative
+/// multipliers are released and violated ones are added until the active set is stable.
+///
+/// Works in any `Real` type, e.g. `BigFloat` gives `R` beyond `f64` precision.
+pub fn polish<T: Real>(
+    main_circle_radius: T,
+    circles: &Vec<Circle<T>>,
+    params: &PolishingParams,
+) -> PolishingResult<T> {
+    let n = circles.len();
+    let radiuses: Vec<T> = circles.iter().map(|c| c.radius.clone()).collect();
+    let mut x: Vec<T> = [
+        circles
+            .iter()
+            .map(|c| c.center.as_ref().expect("Valid center").x.clone())
+            .collect::<Vec<T>>(),
+        circles
+            .iter()
+            .map(|c| c.center.as_ref().expect("Valid center").y.clone())
+            .collect(),
+        vec![main_circle_radius.clone()],
+    ]
+    .concat();
+
+    let pairs = constraint_pairs(n);
+    let gap = |z: &Vec<T>, k: usize| constraint_gap(z, &radiuses, pairs[k], k);
+
+    let active_gap = T::from_f64(params.active_tolerance) * main_circle_radius.clone();
+    let mut active: Vec<usize> = (0..pairs.len())
+        .filter(|&k| gap(&x, k) < active_gap)
+        .collect();
+
+    let unchanged = |newton_iterations: u32, residual: FloatType| PolishingResult {
+        main_circle_radius: main_circle_radius.clone(),
+        circles: circles.clone(),
+        initial_main_circle_radius: main_circle_radius.clone(),
+        active_constraints: 0,
+        newton_iterations,
+        residual,
+        converged: false,
+    };
+
+    let tolerance = params.residual_tolerance * T::epsilon().to_f64();
+    let (mut total_iterations, mut residual) = (0, FloatType::MAX);
+    let mut converged = false;
+    let mut visited = HashSet::new();
+    for _ in 0..=params.max_active_set_changes {
+        let mut y = x.clone();
+        let (iterations, new_residual, multipliers) = newton(&mut y, &radiuses, &active, params);
+        total_iterations += iterations;
+        residual = new_residual;
+        let newton_converged = residual < tolerance * y[2 * n].to_f64().powi(2);
+
+        let mut sorted_active = active.clone();
+        sorted_active.sort();
+        if !visited.insert(sorted_active) {
+            break;
+        }
+
+        let most_negative_multiplier = multipliers
+            .iter()
+            .enumerate()
+            .filter(|(_, mu)| mu.to_f64() < -tolerance)
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(index, _)| index);
+
+        // Newton's method only finds a stationary point, when it increases the radius
+        // some contact is spurious: release the one with the most negative multiplier,
+        // or the loosest one when all multipliers are nonnegative
+        if y[2 * n] > x[2 * n] {
+            let spurious = most_negative_multiplier.or_else(|| {
+                (0..active.len()).max_by(|&a, &b| gap(&x, active[a]).total_cmp(&gap(&x, active[b])))
+            });
+            match spurious {
+                Some(index) => {
+                    active.remove(index);
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        if !newton_converged {
+            match most_negative_multiplier {
+                Some(index) => {
+                    active.remove(index);
+                    continue;
+                }
+                None => break,
+            }
+        }
+
+        // move towards Newton's solution until the first blocking constraint and add it
+        let inactive: Vec<usize> = (0..pairs.len()).filter(|k| !active.contains(k)).collect();
+        let min_gap = |z: &Vec<T>| {
+            inactive
+                .iter()
+                .map(|&k| (gap(z, k), k))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+        };
+        if let Some((blocking_gap, _)) = min_gap(&y) {
+            if blocking_gap < T::zero() {
+                let (mut low, mut high) = (0.0 as FloatType, 1.0 as FloatType);
+                for _ in 0..50 {
+                    let middle = (low + high) / 2.0;
+                    if min_gap(&interpolate(&x, &y, middle)).unwrap().0 < T::zero() {
+                        high = middle;
+                    } else {
+                        low = middle;
+                    }
+                }
+                let (_, blocking) = min_gap(&interpolate(&x, &y, high)).unwrap();
+                x = interpolate(&x, &y, low);
+                active.push(blocking);
+                continue;
+            }
+        }
+
+        x = y;
+        match most_negative_multiplier {
+            Some(index) => {
+                active.remove(index);
+            }
+            None => {
+                converged = true;
+                break;
+            }
+        }
+    }
+
+    if !converged {
+        return unchanged(total_iterations, residual);
+    }
+
+    let polished_radius = x[2 * n].clone();
+
+    // contacts are touching now, scale packing up by a few ulps to make it strictly valid
+    let mut scale = T::from_f64(4.0) * T::epsilon();
+    let (valid_radius, valid_circles) = loop {
+        let factor = T::from_f64(1.0) + scale.clone();
+        let valid_circles: Vec<Circle<T>> = (0..n)
+            .map(|i| {
+                Circle::new(
+                    radiuses[i].clone(),
+                    Point {
+                        x: x[i].clone() * factor.clone(),
+                        y: x[n + i].clone() * factor.clone(),
+                    },
+                )
+            })
+            .collect();
+        let valid_radius = polished_radius.clone() * factor;
+
+        if is_valid_pack(valid_radius.clone(), &valid_circles) {
+            break (valid_radius, valid_circles);
+        }
+        if scale.to_f64() > params.active_tolerance {
+            return unchanged(total_iterations, residual);
+        }
+        scale = scale.clone() + scale;
+    };
+
+    PolishingResult {
+        main_circle_radius: valid_radius,
+        circles: valid_circles,
+        initial_main_circle_radius: main_circle_radius,
+        active_constraints: active.len(),
+        newton_iterations: total_iterations,
+        residual,
+        converged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn polishing_finds_exact_radius_of_three_circles() {
+        // three unit circles in the main circle of radius 1 + 2 / sqrt(3), scaled up a bit
+        let exact: FloatType = 1.0 + 2.0 / (3.0 as FloatType).sqrt();
+        let scale = 1.0 + 1e-6;
+        let distance = (exact - 1.0) * scale;
+        let circles: Vec<Circle> = (0..3)
+            .map(|i| {
+                let angle = 2.0 * std::f64::consts::PI * i as FloatType / 3.0 + 0.3;
+                Circle::new(
+                    1.0,
+                    Point {
+                        x: distance * angle.cos(),
+                        y: distance * angle.sin(),
+                    },
+                )
+            })
+            .collect();
+
+        let result = polish(exact * scale, &circles, &PolishingParams::default());
+
+        assert!(result.converged);
+        assert!(is_valid_pack(result.main_circle_radius, &result.circles));
+        assert!((result.main_circle_radius - exact).abs() < 1e-13);
+        // 1 + 2 / sqrt(3) to 30 digits, R was accurate to 5 digits before polishing
+        assert!(result.digits_gained("2.154700538379251529018297561004") >= 8);
+    }
+}
@@ -1,55 +1,160 @@
-use nalgebra::DVector;
+use nalgebra::{convert, convert_unchecked, DVector, RealField};
 
-use crate::{ralgo::utils::concat_gradients, utils::FloatType};
+use crate::{
+    container::ContainerCenter,
+    obstacle::Obstacle,
+    point::Point,
+    ralgo::utils::{
+        concat_gradients, dvector_to_center, point_from_float_type, point_to_float_type,
+    },
+};
 
-pub fn calcfg(
-    x: &DVector<FloatType>,
-    radiuses: &DVector<FloatType>,
-) -> (FloatType, DVector<FloatType>) {
+/// Circles coordinates in `x` are relative to the main circle center.
+pub fn calcfg<T: RealField + Copy>(
+    x: &DVector<T>,
+    radiuses: &DVector<T>,
+    container_center: &ContainerCenter,
+    obstacles: &[Obstacle],
+) -> (T, DVector<T>) {
     let number_of_circles = radiuses.len();
 
     let cx = x.rows(0, number_of_circles);
     let cy = x.rows(number_of_circles, number_of_circles);
+    let center = dvector_to_center(x, number_of_circles, container_center);
     let main_circle_radius = x[x.len() - 1];
 
-    let mut gx = DVector::<FloatType>::zeros(number_of_circles);
-    let mut gy = DVector::<FloatType>::zeros(number_of_circles);
-    let mut gr = 1 as FloatType;
+    let mut gx = DVector::<T>::zeros(number_of_circles);
+    let mut gy = DVector::<T>::zeros(number_of_circles);
+    let mut gc = Point {
+        x: T::zero(),
+        y: T::zero(),
+    };
+    let mut gr = T::one();
 
     let mut f = main_circle_radius;
-    const P1: FloatType = 2000.0;
-    const P2: FloatType = 1000.0;
-    const EPS: FloatType = 1e-24;
+    let p1: T = convert(2000.0);
+    let p2: T = convert(1000.0);
+    let eps: T = convert(1e-24);
 
     for i in 0..number_of_circles {
         let mut temp =
-            cx[i].powi(2) + cy[i].powi(2) - (main_circle_radius - radiuses[i]).powi(2) + EPS;
-        if temp > 0.0 {
-            f += P1 * temp;
-            gx[i] += P1 * cx[i];
-            gy[i] += P1 * cy[i];
-            gr -= P2;
+            cx[i].powi(2) + cy[i].powi(2) - (main_circle_radius - radiuses[i]).powi(2) + eps;
+        if temp > T::zero() {
+            f += p1 * temp;
+            gx[i] += p1 * cx[i];
+            gy[i] += p1 * cy[i];
+            gr -= p2;
+        }
+
+        // obstacles are fixed in absolute coordinates, so they also depend on the main circle center
+        for obstacle in obstacles {
+            let absolute_center = Point {
+                x: cx[i] + center.x,
+                y: cy[i] + center.y,
+            };
+            let (temp, g) = obstacle.penalty(
+                &point_to_float_type(&absolute_center),
+                convert_unchecked(radiuses[i]),
+            );
+            let (temp, g): (T, Point<T>) = (convert(temp), point_from_float_type(&g));
+            if temp + eps > T::zero() {
+                f += p1 * (temp + eps);
+                gx[i] += p1 * g.x;
+                gy[i] += p1 * g.y;
+                gc.x += p1 * g.x;
+                gc.y += p1 * g.y;
+            }
         }
 
         for j in (i + 1)..number_of_circles {
             temp = -(cx[i] - cx[j]).powi(2) - (cy[i] - cy[j]).powi(2)
                 + (radiuses[i] + radiuses[j]).powi(2)
-                + EPS;
-            if temp > 0.0 {
-                f += P1 * temp;
-                gx[i] -= P1 * (cx[i] - cx[j]);
-                gy[i] -= P1 * (cy[i] - cy[j]);
-                gx[j] += P1 * (cx[i] - cx[j]);
-                gy[j] += P1 * (cy[i] - cy[j]);
+                + eps;
+            if temp > T::zero() {
+                f += p1 * temp;
+                gx[i] -= p1 * (cx[i] - cx[j]);
+                gy[i] -= p1 * (cy[i] - cy[j]);
+                gx[j] += p1 * (cx[i] - cx[j]);
+                gy[j] += p1 * (cy[i] - cy[j]);
+            }
+        }
+    }
+
+    // free main circle could simply move away from obstacles, so they must stay inside it
+    if container_center.is_free() {
+        for (obstacle_center, obstacle_radius) in obstacles.iter().flat_map(|o| o.bounding_discs())
+        {
+            let obstacle_center = point_from_float_type::<T>(&obstacle_center);
+            let (dx, dy) = (obstacle_center.x - center.x, obstacle_center.y - center.y);
+            let temp = dx.powi(2) + dy.powi(2)
+                - (main_circle_radius - convert(obstacle_radius)).powi(2)
+                + eps;
+            if temp > T::zero() {
+                f += p1 * temp;
+                gc.x -= p1 * dx;
+                gc.y -= p1 * dy;
+                gr -= p2;
             }
         }
     }
 
     let temp = -main_circle_radius + radiuses.min();
-    if temp > 0.0 {
-        f += P2 * temp;
-        gr -= P2;
+    if temp > T::zero() {
+        f += p2 * temp;
+        gr -= p2;
+    }
+
+    let gc = if container_center.is_free() {
+        Some(gc)
+    } else {
+        None
+    };
+
+    return (f, concat_gradients(&gx, &gy, gc, gr));
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::DVector;
+
+    use super::calcfg;
+    use crate::{container::ContainerCenter, obstacle::Obstacle, point::Point, utils::FloatType};
+
+    /// Quadratic penalty terms have half of their gradient in `calcfg` (as circles
+    /// coordinates do), so central differences by the center must be twice the gradient.
+    fn check_center_gradient(obstacle: Obstacle) {
+        let radiuses: DVector<FloatType> = DVector::from_vec(vec![1.0]);
+        let container_center = ContainerCenter::Free(Point { x: 0.1, y: -0.2 });
+        // circle at (0, 0) relative to the center, center, R
+        let x = DVector::from_vec(vec![0.0, 0.0, 0.1, -0.2, 3.0]);
+        let obstacles = [obstacle];
+
+        let (_, g) = calcfg(&x, &radiuses, &container_center, &obstacles);
+        for index in [2, 3] {
+            let h = 1e-6;
+            let (mut forward, mut backward) = (x.clone(), x.clone());
+            forward[index] += h;
+            backward[index] -= h;
+            let difference = (calcfg(&forward, &radiuses, &container_center, &obstacles).0
+                - calcfg(&backward, &radiuses, &container_center, &obstacles).0)
+                / (2.0 * h);
+
+            assert!(g[index] != 0.0, "center term must be active");
+            assert!(
+                (difference - 2.0 * g[index]).abs() <= 1e-5 * difference.abs(),
+                "{difference} != 2 * {}",
+                g[index]
+            );
+        }
     }
 
-    return (f, concat_gradients(&gx, &gy, gr));
+    #[test]
+    fn center_gradient_of_obstacle_overlap() {
+        check_center_gradient(Obstacle::circle(Point { x: 0.6, y: 0.3 }, 1.0));
+    }
+
+    #[test]
+    fn center_gradient_of_obstacle_outside_container() {
+        check_center_gradient(Obstacle::circle(Point { x: 2.6, y: -0.9 }, 0.5));
+    }
 }
@@ -0,0 +1,110 @@
+use nalgebra::DVector;
+
+use crate::{
+    ellipse::{Ellipse, EllipseContainer},
+    point::Point,
+    utils::FloatType,
+};
+
+/// Index in `x` of the angle of every ellipse. Rotation doesn't change round ellipses, so
+/// they have no angle variable: r-algorithm would drift along its zero gradient forever.
+pub fn angle_variables(semi_axes: &Vec<(FloatType, FloatType)>) -> Vec<Option<usize>> {
+    let mut next = 2 * semi_axes.len();
+
+    semi_axes
+        .iter()
+        .map(|(semi_major, semi_minor)| {
+            (semi_major != semi_minor).then(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+/// Penalty function for ellipses packing, `x = [x_1..x_n, y_1..y_n, angles, R]` where angles
+/// are given only for ellipses that aren't round, see `angle_variables`.
+/// Overlap term `(1 - F)(a_i + a_j)^2` uses Perram-Wertheim contact function `F`,
+/// so for circles it is the same term as in `calcfg`.
+pub fn calcfg_ellipses(
+    x: &DVector<FloatType>,
+    semi_axes: &Vec<(FloatType, FloatType)>,
+    container: &EllipseContainer,
+) -> (FloatType, DVector<FloatType>) {
+    let n = semi_axes.len();
+    let main_radius = x[x.len() - 1];
+    let angles = angle_variables(semi_axes);
+
+    let ellipses: Vec<Ellipse> = (0..n)
+        .map(|i| {
+            Ellipse::new(
+                semi_axes[i].0,
+                semi_axes[i].1,
+                Point {
+                    x: x[i],
+                    y: x[n + i],
+                },
+                angles[i].map_or(0.0, |k| x[k]),
+            )
+        })
+        .collect();
+
+    let r = x.len() - 1;
+    let mut g = DVector::<FloatType>::zeros(x.len());
+    g[r] = 1.0;
+
+    let mut f = main_radius;
+    const P1: FloatType = 2000.0;
+    const P2: FloatType = 1000.0;
+    const EPS: FloatType = 1e-24;
+
+    for i in 0..n {
+        let t = ellipses[i].farthest_boundary_parameter(container, main_radius);
+        let (point, derivative) = ellipses[i].boundary_point(t);
+
+        let temp = container.outside_value(&point, main_radius) + EPS;
+        if temp > 0.0 {
+            let px = point.x / container.aspect_ratio.powi(2);
+            f += P1 * temp;
+            g[i] += P1 * px;
+            g[n + i] += P1 * point.y;
+            if let Some(k) = angles[i] {
+                g[k] += P1 * (px * derivative.x + point.y * derivative.y);
+            }
+            g[r] -= P2;
+        }
+
+        for j in (i + 1)..n {
+            let contact = ellipses[i].contact(&ellipses[j]);
+            let scale = (semi_axes[i].0 + semi_axes[j].0).powi(2);
+
+            let temp = (1.0 - contact.value) * scale + EPS;
+            if temp > 0.0 {
+                f += P1 * temp;
+                let gradient = -P1 * scale / 2.0 * contact.gradient_center;
+                g[i] += gradient.x;
+                g[n + i] += gradient.y;
+                g[j] -= gradient.x;
+                g[n + j] -= gradient.y;
+                if let Some(k) = angles[i] {
+                    g[k] -= P1 * scale / 2.0 * contact.gradient_angle_first;
+                }
+                if let Some(k) = angles[j] {
+                    g[k] -= P1 * scale / 2.0 * contact.gradient_angle_second;
+                }
+            }
+        }
+    }
+
+    let min_semi_minor = semi_axes
+        .iter()
+        .map(|(_, semi_minor)| *semi_minor)
+        .fold(FloatType::MAX, FloatType::min);
+    let temp = -main_radius + min_semi_minor;
+    if temp > 0.0 {
+        f += P2 * temp;
+        g[r] -= P2;
+    }
+
+    return (f, g);
+}
@@ -0,0 +1,18 @@
+use nalgebra::{DVector, RealField};
+
+use crate::{container::ContainerCenter, ralgo::calcfg::calcfg};
+
+use super::symmetry::Orbits;
+
+/// Penalty function of `calcfg` for `x = [orbit variables.., R]`: the full packing is
+/// evaluated and its gradient is reduced to the orbit representatives, so the gradient
+/// stays symmetric.
+pub fn calcfg_symmetric<T: RealField + Copy>(
+    x: &DVector<T>,
+    radiuses: &DVector<T>,
+    orbits: &Orbits,
+) -> (T, DVector<T>) {
+    let (f, g) = calcfg(&orbits.expand(x), radiuses, &ContainerCenter::origin(), &[]);
+
+    (f, orbits.reduce_gradient(&g))
+}
@@ -1,97 +1,342 @@
-use crate::{circle::Circle, utils::FloatType};
+use nalgebra::{convert, convert_unchecked, DVector, RealField};
+
+use crate::{
+    circle::Circle, container::ContainerCenter, obstacle::Obstacle, point::Point, real::Float,
+    scaling::Scaling, utils::FloatType,
+};
 
 use super::{
-    ralgo::{ralg5, ralgo_result_with_iterations},
+    calcfg::calcfg,
+    calcfg_symmetric::calcfg_symmetric,
+    ralgo::{ralg5_observed, ralgo_result_with_iterations},
     ralgo_params::RalgoParams,
     ralgo_result::RalgoResult,
-    utils::{circles_to_dvector, dvector_to_answer, get_last},
+    symmetry::Symmetry,
+    utils::{
+        circles_to_dvector_with_center, dvector_to_answer, dvector_to_answer_in_container, get_last,
+    },
 };
 
-pub fn dichotomy_step_ralgo(
-    main_circle_radiuse: FloatType,
-    circles: &Vec<Circle>,
+/// Run r-algorithm with step sizes of `ralgo_params.step_strategy` while it keeps decreasing
+/// the last component of `x` (main circle radius) by more than `eps` relatively. Parameters
+/// are converted to the float type of `x`. `observe` gets the number of the run (from 1),
+/// the iteration and point of every iteration of the run, and then `None` with the point
+/// kept after the run.
+pub fn dichotomy_step<T, F, O>(
+    mut x: DVector<T>,
+    calcfg: F,
     reset_step: bool,
     eps: FloatType,
     ralgo_params: &RalgoParams,
-) -> (FloatType, Vec<Circle>) {
-    let mut x = circles_to_dvector(circles, main_circle_radiuse);
-    let circles_radiuses =
-        nalgebra::DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
+    mut observe: O,
+) -> DVector<T>
+where
+    T: RealField + Copy,
+    F: Fn(&DVector<T>) -> (T, DVector<T>),
+    O: FnMut(usize, Option<usize>, &DVector<T>),
+{
+    let step_sizes = ralgo_params
+        .step_strategy
+        .step_sizes(convert_unchecked(get_last(&x)));
+    let mut step_size = step_sizes.initial;
+    let mut run = 0;
 
-    let mut step_size = 40.96;
-
-    while step_size >= 0.01 {
-        let y = ralg5(
+    while !step_sizes.is_finished(step_size) {
+        run += 1;
+        let y = ralg5_observed(
             x.clone(),
-            ralgo_params.alpha,
-            step_size.clone(),
-            ralgo_params.q1,
-            ralgo_params.epsx,
-            ralgo_params.epsg,
+            convert(ralgo_params.alpha),
+            convert(step_size),
+            convert(ralgo_params.q1),
+            convert(ralgo_params.epsx),
+            convert(ralgo_params.epsg),
             ralgo_params.max_iterations,
-            &circles_radiuses,
+            &calcfg,
+            |iteration, y: &DVector<T>, _| observe(run, Some(iteration), y),
         );
 
-        if (get_last(&x) - get_last(&y)) / get_last(&x) > eps {
+        let improved = (get_last(&x) - get_last(&y)) / get_last(&x) > convert(eps);
+        if improved {
             x = y;
-            if reset_step {
-                step_size = 40.96;
-            }
-        } else {
-            step_size /= 2.0;
         }
+        observe(run, None, &x);
+        step_size = step_sizes.next(step_size, improved, reset_step);
     }
 
-    return dvector_to_answer(&x, &circles_radiuses);
+    x
+}
+
+pub fn dichotomy_step_ralgo<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (T, Vec<Circle<T>>) {
+    dichotomy_step_ralgo_with_obstacles(
+        main_circle_radiuse,
+        circles,
+        &[],
+        reset_step,
+        eps,
+        ralgo_params,
+    )
+}
+
+pub fn dichotomy_step_ralgo_with_obstacles<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    obstacles: &[Obstacle],
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (T, Vec<Circle<T>>) {
+    let (main_circle_radius, _, circles) = dichotomy_step_ralgo_in_container(
+        main_circle_radiuse,
+        circles,
+        &ContainerCenter::origin(),
+        obstacles,
+        reset_step,
+        eps,
+        ralgo_params,
+    );
+
+    (main_circle_radius, circles)
+}
+
+/// Same as `dichotomy_step_ralgo_with_obstacles`, but main circle center can be moved away
+/// from (0, 0) or optimized together with circles. Returns radius and center of the main circle.
+pub fn dichotomy_step_ralgo_in_container<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    container_center: &ContainerCenter,
+    obstacles: &[Obstacle],
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> (T, Point<T>, Vec<Circle<T>>) {
+    // solve in canonical scale and map the answer back
+    let scaling = Scaling::for_circles(circles);
+    let (main_circle_radiuse, circles) = (
+        scaling.scale(main_circle_radiuse),
+        &scaling.scale_circles(circles),
+    );
+    let (container_center, obstacles) = (
+        &scaling.scale_container_center(container_center),
+        &scaling.scale_obstacles(obstacles)[..],
+    );
+
+    let x = circles_to_dvector_with_center(circles, main_circle_radiuse, container_center);
+    let circles_radiuses =
+        nalgebra::DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
+
+    let x = dichotomy_step(
+        x,
+        |x| calcfg(x, &circles_radiuses, container_center, obstacles),
+        reset_step,
+        eps,
+        ralgo_params,
+        |_, _, _| {},
+    );
+
+    let (main_circle_radius, main_circle_center, circles) =
+        dvector_to_answer_in_container(&x, &circles_radiuses, container_center);
+
+    return (
+        scaling.unscale(main_circle_radius),
+        scaling.unscale_point(&main_circle_center),
+        scaling.unscale_circles(&circles),
+    );
+}
+
+/// Same as `dichotomy_step_ralgo`, but the packing keeps `symmetry`: only orbit
+/// representatives are optimized, the other circles are their images. Positions of
+/// the other circles in `circles` are ignored. Fails when circles radii can't be split
+/// into orbits of the group.
+pub fn dichotomy_step_ralgo_symmetric<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    symmetry: &Symmetry,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> Result<(T, Vec<Circle<T>>), String> {
+    let orbits = symmetry.orbits(
+        &circles
+            .iter()
+            .map(|c| c.radius.to_f64() as FloatType)
+            .collect::<Vec<_>>(),
+    )?;
+
+    // solve in canonical scale and map the answer back
+    let scaling = Scaling::for_circles(circles);
+    let (main_circle_radiuse, circles) = (
+        scaling.scale(main_circle_radiuse),
+        &scaling.scale_circles(circles),
+    );
+
+    let x = orbits.circles_to_dvector(circles, main_circle_radiuse);
+    let circles_radiuses =
+        nalgebra::DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
+
+    let x = dichotomy_step(
+        x,
+        |x| calcfg_symmetric(x, &circles_radiuses, &orbits),
+        reset_step,
+        eps,
+        ralgo_params,
+        |_, _, _| {},
+    );
+
+    let (main_circle_radius, circles) = dvector_to_answer(&orbits.expand(&x), &circles_radiuses);
+
+    Ok((
+        scaling.unscale(main_circle_radius),
+        scaling.unscale_circles(&circles),
+    ))
+}
+
+pub fn dichotomy_step_ralgo_result_with_iterations<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> RalgoResult<T> {
+    dichotomy_step_ralgo_result_with_iterations_and_obstacles(
+        main_circle_radiuse,
+        circles,
+        &[],
+        reset_step,
+        eps,
+        ralgo_params,
+    )
+}
+
+pub fn dichotomy_step_ralgo_result_with_iterations_and_obstacles<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    obstacles: &[Obstacle],
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+) -> RalgoResult<T> {
+    dichotomy_step_ralgo_result_in_container(
+        main_circle_radiuse,
+        circles,
+        &ContainerCenter::origin(),
+        obstacles,
+        reset_step,
+        eps,
+        ralgo_params,
+    )
 }
 
-pub fn dichotomy_step_ralgo_result_with_iterations(
-    main_circle_radiuse: FloatType,
-    circles: &Vec<Circle>,
+pub fn dichotomy_step_ralgo_result_in_container<T: Float>(
+    main_circle_radiuse: T,
+    circles: &Vec<Circle<T>>,
+    container_center: &ContainerCenter,
+    obstacles: &[Obstacle],
     reset_step: bool,
     eps: FloatType,
     ralgo_params: &RalgoParams,
-) -> RalgoResult {
-    let mut x = circles_to_dvector(circles, main_circle_radiuse);
+) -> RalgoResult<T> {
+    // solve in canonical scale and map the answer back
+    let scaling = Scaling::for_circles(circles);
+    let (main_circle_radiuse, circles) = (
+        scaling.scale(main_circle_radiuse),
+        &scaling.scale_circles(circles),
+    );
+    let (container_center, obstacles) = (
+        &scaling.scale_container_center(container_center),
+        &scaling.scale_obstacles(obstacles)[..],
+    );
+
+    let mut x = circles_to_dvector_with_center(circles, main_circle_radiuse, container_center);
     let circles_radiuses =
         nalgebra::DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
 
-    let mut step_size = 40.96;
+    let step_sizes = ralgo_params
+        .step_strategy
+        .step_sizes(convert_unchecked(main_circle_radiuse));
+    let mut step_size = step_sizes.initial;
     let (mut ralgo_calls, mut total_iterations, mut total_calcfg_calls) = (0, 0, 0);
 
-    while step_size >= 0.01 {
+    while !step_sizes.is_finished(step_size) {
         let (iterations, calcfg_calls, y) = ralgo_result_with_iterations(
             x.clone(),
-            ralgo_params.alpha,
-            step_size.clone(),
-            ralgo_params.q1,
-            ralgo_params.epsx,
-            ralgo_params.epsg,
+            convert(ralgo_params.alpha),
+            convert(step_size),
+            convert(ralgo_params.q1),
+            convert(ralgo_params.epsx),
+            convert(ralgo_params.epsg),
             ralgo_params.max_iterations,
-            &circles_radiuses,
+            |x| calcfg(x, &circles_radiuses, container_center, obstacles),
         );
 
         ralgo_calls += 1;
         total_iterations += iterations;
         total_calcfg_calls += calcfg_calls;
 
-        if (get_last(&x) - get_last(&y)) / get_last(&x) > eps {
+        let improved = (get_last(&x) - get_last(&y)) / get_last(&x) > convert(eps);
+        if improved {
             x = y;
-            if reset_step {
-                step_size = 40.96;
-            }
-        } else {
-            step_size /= 2.0;
         }
+        step_size = step_sizes.next(step_size, improved, reset_step);
     }
 
-    let (main_circle_radius, circles) = dvector_to_answer(&x, &circles_radiuses);
+    let (main_circle_radius, main_circle_center, circles) =
+        dvector_to_answer_in_container(&x, &circles_radiuses, container_center);
 
-    RalgoResult::new(
-        ralgo_calls,
-        total_iterations,
-        total_calcfg_calls,
-        main_circle_radius,
-        circles,
+    scaling.unscale_result(
+        RalgoResult::new(
+            ralgo_calls,
+            total_iterations,
+            total_calcfg_calls,
+            main_circle_radius,
+            circles,
+        )
+        .with_main_circle_center(main_circle_center),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packing::is_valid_pack_in_container;
+
+    #[test]
+    fn free_center_shrinks_container_around_obstacle() {
+        // unit obstacle at the origin and one unit circle: R = 3 with the center fixed at
+        // the origin, R = 2 when the center moves between the obstacle and the circle
+        let obstacles = [Obstacle::circle(Point { x: 0.0, y: 0.0 }, 1.0)];
+        let circles = vec![Circle::new(1.0, Point { x: 2.5, y: 0.5 })];
+
+        let solve = |center: ContainerCenter| {
+            dichotomy_step_ralgo_in_container(
+                4.0 as FloatType,
+                &circles,
+                &center,
+                &obstacles,
+                false,
+                0.0,
+                &RalgoParams::default(),
+            )
+        };
+        let (fixed_radius, _, _) = solve(ContainerCenter::origin());
+        let (main_circle_radius, main_circle_center, circles) =
+            solve(ContainerCenter::Free(Point::empty()));
+
+        assert!((fixed_radius - 3.0).abs() < 1e-4);
+        assert!((main_circle_radius - 2.0).abs() < 1e-4);
+        assert!(is_valid_pack_in_container(
+            main_circle_radius * (1.0 + 1e-6),
+            &main_circle_center,
+            &circles,
+            &obstacles
+        ));
+        // obstacle stays inside the main circle
+        let distance = (main_circle_center.x.powi(2) + main_circle_center.y.powi(2)).sqrt();
+        assert!(distance + 1.0 <= main_circle_radius * (1.0 + 1e-6));
+    }
+}
@@ -1,6 +1,11 @@
 pub mod calcfg;
+pub mod calcfg_ellipses;
+pub mod calcfg_symmetric;
 pub mod dichotomy_step_ralgo;
 pub mod ralgo;
 pub mod ralgo_params;
 pub mod ralgo_result;
+pub mod step_strategy;
+pub mod symmetry;
+pub mod trajectory;
 pub mod utils;
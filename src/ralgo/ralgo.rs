@@ -1,41 +1,73 @@
-use nalgebra::{self, DMatrix, DVector};
-
-use crate::utils::FloatType;
-
-use super::calcfg::calcfg;
+use nalgebra::{self, convert, DMatrix, DVector, RealField};
+
+/// Shor's r-algorithm, generic over the float type of `x`: `f32` is enough for screening.
+pub fn ralg5<T, F>(
+    x: DVector<T>,
+    alpha: T,
+    h: T,
+    q1: T,
+    epsx: T,
+    epsg: T,
+    max_iterations: usize,
+    calcfg: F,
+) -> DVector<T>
+where
+    T: RealField + Copy,
+    F: Fn(&DVector<T>) -> (T, DVector<T>),
+{
+    ralg5_observed(
+        x,
+        alpha,
+        h,
+        q1,
+        epsx,
+        epsg,
+        max_iterations,
+        calcfg,
+        |_, _, _| {},
+    )
+}
 
-pub fn ralg5(
-    mut x: DVector<FloatType>,
-    alpha: FloatType,
-    mut h: FloatType,
-    q1: FloatType,
-    epsx: FloatType,
-    epsg: FloatType,
+/// Same as `ralg5`, but `observe` gets the (1-based) iteration, the best point and its
+/// function value after every iteration.
+pub fn ralg5_observed<T, F, O>(
+    mut x: DVector<T>,
+    alpha: T,
+    mut h: T,
+    q1: T,
+    epsx: T,
+    epsg: T,
     max_iterations: usize,
-    radiuses: &DVector<FloatType>,
-) -> DVector<FloatType> {
-    let mut b_matrix = DMatrix::<FloatType>::identity(x.len(), x.len());
+    calcfg: F,
+    mut observe: O,
+) -> DVector<T>
+where
+    T: RealField + Copy,
+    F: Fn(&DVector<T>) -> (T, DVector<T>),
+    O: FnMut(usize, &DVector<T>, T),
+{
+    let mut b_matrix = DMatrix::<T>::identity(x.len(), x.len());
 
     let mut result_x = x.clone();
-    let (mut result_f, mut g0) = calcfg(&result_x, radiuses);
+    let (mut result_f, mut g0) = calcfg(&result_x);
 
     if g0.norm() < epsg {
         return result_x;
     }
 
-    for _ in 0..max_iterations {
-        let mut g1: DVector<FloatType> = b_matrix.tr_mul(&g0);
+    for iteration in 1..=max_iterations {
+        let mut g1: DVector<T> = b_matrix.tr_mul(&g0);
 
-        let dx: DVector<FloatType> = (&b_matrix * &g1) / g1.norm();
+        let dx: DVector<T> = (&b_matrix * &g1) / g1.norm();
         let dx_norm = dx.norm();
 
-        let mut f: FloatType;
-        let (mut d, mut ls, mut ddx) = (1.0 as FloatType, 0_u32, 0.0 as FloatType);
-        while d > 0.0 {
-            x -= h * &dx;
+        let mut f: T;
+        let (mut d, mut ls, mut ddx) = (T::one(), 0_u32, T::zero());
+        while d > T::zero() {
+            x -= &dx * h;
             ddx += h * dx_norm;
 
-            (f, g1) = calcfg(&x, radiuses);
+            (f, g1) = calcfg(&x);
             if f < result_f {
                 (result_f, result_x) = (f, x.clone());
             }
@@ -46,7 +78,7 @@ pub fn ralg5(
 
             ls += 1;
             if ls % 3 == 0 {
-                h *= 1.1;
+                h *= convert(1.1);
             }
 
             if ls > 500 {
@@ -55,6 +87,7 @@ pub fn ralg5(
 
             d = dx.dot(&g1);
         }
+        observe(iteration, &result_x, result_f);
 
         if ls == 1 {
             h *= q1;
@@ -67,27 +100,31 @@ pub fn ralg5(
         let mut r = &b_matrix.transpose() * (&g1 - &g0);
         r /= r.norm();
 
-        b_matrix += (1.0 / alpha - 1.0) * &b_matrix * &r * &r.transpose();
+        b_matrix += &b_matrix * (T::one() / alpha - T::one()) * &r * &r.transpose();
         g0 = g1;
     }
 
     return result_x;
 }
 
-pub fn ralgo_result_with_iterations(
-    mut x: DVector<FloatType>,
-    alpha: FloatType,
-    mut h: FloatType,
-    q1: FloatType,
-    epsx: FloatType,
-    epsg: FloatType,
+pub fn ralgo_result_with_iterations<T, F>(
+    mut x: DVector<T>,
+    alpha: T,
+    mut h: T,
+    q1: T,
+    epsx: T,
+    epsg: T,
     max_iterations: usize,
-    radiuses: &DVector<FloatType>,
-) -> (u32, u32, DVector<FloatType>) {
-    let mut b_matrix = DMatrix::<FloatType>::identity(x.len(), x.len());
+    calcfg: F,
+) -> (u32, u32, DVector<T>)
+where
+    T: RealField + Copy,
+    F: Fn(&DVector<T>) -> (T, DVector<T>),
+{
+    let mut b_matrix = DMatrix::<T>::identity(x.len(), x.len());
 
     let mut result_x = x.clone();
-    let (mut result_f, mut g0) = calcfg(&result_x, radiuses);
+    let (mut result_f, mut g0) = calcfg(&result_x);
     let mut calcfg_calls = 1_u32;
 
     if g0.norm() < epsg {
@@ -95,18 +132,18 @@ pub fn ralgo_result_with_iterations(
     }
 
     for iter in 0..max_iterations as u32 {
-        let mut g1: DVector<FloatType> = b_matrix.tr_mul(&g0);
+        let mut g1: DVector<T> = b_matrix.tr_mul(&g0);
 
         let dx = &b_matrix * (&g1 / g1.norm());
         let dx_norm = dx.norm();
 
         let mut f;
-        let (mut d, mut ls, mut ddx) = (1.0 as FloatType, 0_u32, 0.0 as FloatType);
-        while d > 0.0 {
-            x.axpy(-h, &dx, 1.0);
+        let (mut d, mut ls, mut ddx) = (T::one(), 0_u32, T::zero());
+        while d > T::zero() {
+            x.axpy(-h, &dx, T::one());
             ddx += h * dx_norm;
 
-            (f, g1) = calcfg(&x, radiuses);
+            (f, g1) = calcfg(&x);
             calcfg_calls += 1;
             if f < result_f {
                 (result_f, result_x) = (f, x.clone());
@@ -118,7 +155,7 @@ pub fn ralgo_result_with_iterations(
 
             ls += 1;
             if ls % 3 == 0 {
-                h *= 1.1;
+                h *= convert(1.1);
             }
 
             if ls > 500 {
@@ -137,7 +174,7 @@ pub fn ralgo_result_with_iterations(
         }
 
         let r = b_matrix.tr_mul(&(&g1 - &g0)).normalize();
-        b_matrix += (1.0 / alpha - 1.0) * (&b_matrix * &r) * &r.transpose();
+        b_matrix += (&b_matrix * &r) * (T::one() / alpha - T::one()) * &r.transpose();
         g0 = g1;
     }
 
@@ -1,12 +1,15 @@
 use crate::utils::FloatType;
 
-#[derive(Debug)]
+use super::step_strategy::StepStrategy;
+
+#[derive(Debug, Clone, Copy)]
 pub struct RalgoParams {
     pub alpha: FloatType,
     pub q1: FloatType,
     pub epsx: FloatType,
     pub epsg: FloatType,
     pub max_iterations: usize,
+    pub step_strategy: StepStrategy,
 }
 
 impl Default for RalgoParams {
@@ -17,6 +20,7 @@ impl Default for RalgoParams {
             epsx: 1e-6,
             epsg: 1e-7,
             max_iterations: 3000,
+            step_strategy: StepStrategy::default(),
         }
     }
 }
@@ -38,15 +42,16 @@ impl RalgoParams {
     }
 
     pub fn with_epsx(&self, epsx: FloatType) -> Self {
-        RalgoParams {
-            epsx,
-            ..*self
-        }
+        RalgoParams { epsx, ..*self }
     }
 
     pub fn with_epsg(&self, epsg: FloatType) -> Self {
+        RalgoParams { epsg, ..*self }
+    }
+
+    pub fn with_step_strategy(&self, step_strategy: StepStrategy) -> Self {
         RalgoParams {
-            epsg,
+            step_strategy,
             ..*self
         }
     }
@@ -1,27 +1,36 @@
-use crate::{circle::Circle, utils::FloatType};
+use crate::{circle::Circle, point::Point, real::Real, utils::FloatType};
 
-pub struct RalgoResult {
+pub struct RalgoResult<T = FloatType> {
     pub ralgo_calls: u32,
     pub iterations: u32,
     pub calcfg_calls: u32,
-    pub main_circle_radius: FloatType,
-    pub circles: Vec<Circle>,
+    pub main_circle_radius: T,
+    pub main_circle_center: Point<T>,
+    pub circles: Vec<Circle<T>>,
 }
 
-impl RalgoResult {
+impl<T: Real> RalgoResult<T> {
     pub fn new(
         ralgo_calls: u32,
         iterations: u32,
         calcfg_calls: u32,
-        main_circle_radius: FloatType,
-        circles: Vec<Circle>,
+        main_circle_radius: T,
+        circles: Vec<Circle<T>>,
     ) -> Self {
         RalgoResult {
             ralgo_calls,
             iterations,
             calcfg_calls,
             main_circle_radius,
+            main_circle_center: Point::empty(),
             circles,
         }
     }
+
+    pub fn with_main_circle_center(self, main_circle_center: Point<T>) -> Self {
+        RalgoResult {
+            main_circle_center,
+            ..self
+        }
+    }
 }
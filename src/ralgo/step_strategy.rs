@@ -0,0 +1,318 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::utils::FloatType;
+
+/// Step size of r-algorithm, either absolute or derived from the instance.
+#[derive(Debug, Clone, Copy)]
+pub enum StepSize {
+    Fixed(FloatType),
+    /// Part of the main circle radius at the start of dichotomy, so the step follows
+    /// the problem scale: radii 1..N and normalised radii behave the same.
+    Relative(FloatType),
+}
+
+impl StepSize {
+    pub fn value(&self, main_circle_radius: FloatType) -> FloatType {
+        match self {
+            StepSize::Fixed(step) => *step,
+            StepSize::Relative(part) => part * main_circle_radius,
+        }
+    }
+}
+
+impl Display for StepSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StepSize::Fixed(step) => write!(f, "{step}"),
+            StepSize::Relative(part) => write!(f, "{part}R"),
+        }
+    }
+}
+
+/// How step changes between r-algorithm runs of dichotomy. Build it with the checked
+/// constructors, a factor not above 1 would never finish dichotomy, so
+/// `StepStrategy::step_sizes` cuts it to one run.
+#[derive(Debug, Clone, Copy)]
+pub enum StepSchedule {
+    /// Step is divided by `factor` after every run that doesn't decrease `R`.
+    Geometric { factor: FloatType },
+    /// Same as geometric, but every run that decreases `R` multiplies step by `increase`
+    /// (never above the initial step).
+    SuccessBased {
+        factor: FloatType,
+        increase: FloatType,
+    },
+    /// Exactly `levels` steps from the initial to the smallest one, geometrically spaced.
+    Levels { levels: usize },
+}
+
+impl StepSchedule {
+    pub fn geometric(factor: FloatType) -> Result<Self, String> {
+        if !(factor > 1.0) {
+            return Err(format!("Step factor must be greater than 1, got {factor}"));
+        }
+        Ok(StepSchedule::Geometric { factor })
+    }
+
+    pub fn success_based(factor: FloatType, increase: FloatType) -> Result<Self, String> {
+        if !(factor > 1.0) {
+            return Err(format!("Step factor must be greater than 1, got {factor}"));
+        }
+        if !(increase >= 1.0) {
+            return Err(format!("Step increase must be at least 1, got {increase}"));
+        }
+        Ok(StepSchedule::SuccessBased { factor, increase })
+    }
+
+    pub fn levels(levels: usize) -> Result<Self, String> {
+        if levels < 2 {
+            return Err(format!("At least 2 step levels are needed, got {levels}"));
+        }
+        Ok(StepSchedule::Levels { levels })
+    }
+}
+
+/// Step sizes used by `dichotomy_step`. Default is the original schedule: halving from
+/// 40.96 to 0.01, kept so results of the original drivers are reproduced. The command
+/// line drivers and pipeline use `StepStrategy::relative()`.
+#[derive(Debug, Clone, Copy)]
+pub struct StepStrategy {
+    pub initial_step: StepSize,
+    pub min_step: StepSize,
+    pub schedule: StepSchedule,
+}
+
+impl Default for StepStrategy {
+    fn default() -> Self {
+        StepStrategy {
+            initial_step: StepSize::Fixed(40.96),
+            min_step: StepSize::Fixed(0.01),
+            schedule: StepSchedule::Geometric { factor: 2.0 },
+        }
+    }
+}
+
+impl StepStrategy {
+    /// Initial step is 0.4 of `R` and the smallest one is 4096 times less, like the original
+    /// schedule has for radii 1..N with small N.
+    pub fn relative() -> Self {
+        StepStrategy {
+            initial_step: StepSize::Relative(0.4),
+            min_step: StepSize::Relative(0.4 / 4096.0),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_initial_step(&self, initial_step: StepSize) -> Self {
+        StepStrategy {
+            initial_step,
+            ..*self
+        }
+    }
+
+    pub fn with_min_step(&self, min_step: StepSize) -> Self {
+        StepStrategy { min_step, ..*self }
+    }
+
+    pub fn with_schedule(&self, schedule: StepSchedule) -> Self {
+        StepStrategy { schedule, ..*self }
+    }
+
+    /// Steps for main circle radius `R` at the start of dichotomy. Steps that can't be used are
+    /// clamped: without positive steps (e.g. relative ones for `R = 0`) there are no runs, and
+    /// a schedule that doesn't decrease the step (e.g. levels from the initial step not above
+    /// the smallest one) runs with the initial step only.
+    pub fn step_sizes(&self, main_circle_radius: FloatType) -> StepSizes {
+        let (initial, min) = (
+            self.initial_step.value(main_circle_radius),
+            self.min_step.value(main_circle_radius),
+        );
+        if !(initial > 0.0 && min > 0.0) {
+            return StepSizes {
+                initial,
+                min: FloatType::INFINITY,
+                factor: 2.0,
+                increase: 1.0,
+            };
+        }
+
+        let (factor, increase) = match self.schedule {
+            StepSchedule::Geometric { factor } => (factor, 1.0),
+            StepSchedule::SuccessBased { factor, increase } => (factor, increase),
+            StepSchedule::Levels { levels } => (
+                (initial / min).powf(1.0 / (levels.max(2) - 1) as FloatType),
+                1.0,
+            ),
+        };
+        if !(factor > 1.0) {
+            return StepSizes {
+                initial,
+                min: initial,
+                factor: 2.0,
+                increase: 1.0,
+            };
+        }
+
+        // levels are computed, so stop half a level lower and rounding doesn't drop the last one
+        let min = match self.schedule {
+            StepSchedule::Levels { .. } => min / factor.sqrt(),
+            _ => min,
+        };
+
+        StepSizes {
+            initial,
+            min,
+            factor,
+            increase,
+        }
+    }
+}
+
+/// Step sizes of `StepStrategy` for the particular instance.
+#[derive(Debug, Clone, Copy)]
+pub struct StepSizes {
+    pub initial: FloatType,
+    pub min: FloatType,
+    factor: FloatType,
+    increase: FloatType,
+}
+
+impl StepSizes {
+    /// NaN step, e.g. of NaN `R`, is finished too.
+    pub fn is_finished(&self, step: FloatType) -> bool {
+        !(step >= self.min)
+    }
+
+    /// Step of the next run. `reset_step` returns to the initial step after every success.
+    pub fn next(&self, step: FloatType, improved: bool, reset_step: bool) -> FloatType {
+        if !improved {
+            step / self.factor
+        } else if reset_step {
+            self.initial
+        } else {
+            (step * self.increase).min(self.initial)
+        }
+    }
+}
+
+impl Display for StepStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.schedule {
+            StepSchedule::Geometric { factor } => write!(f, "geometric /{factor}")?,
+            StepSchedule::SuccessBased { factor, increase } => {
+                write!(f, "success-based /{factor} *{increase}")?
+            }
+            StepSchedule::Levels { levels } => write!(f, "{levels} levels")?,
+        }
+        write!(f, " from {} to {}", self.initial_step, self.min_step)
+    }
+}
+
+/// `<sizes>[,<schedule>]`: sizes are `fixed` (default) or `relative`, schedule is
+/// `geometric:<factor>`, `success:<factor>:<increase>` or `levels:<count>`, e.g.
+/// `relative,levels:8`.
+impl FromStr for StepStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (sizes, schedule) = match s.trim().split_once(',') {
+            Some((sizes, schedule)) => (sizes.trim(), Some(schedule.trim())),
+            None => (s.trim(), None),
+        };
+
+        let strategy = match sizes {
+            "fixed" => StepStrategy::default(),
+            "relative" => StepStrategy::relative(),
+            _ => return Err(format!("Unknown step sizes '{sizes}' in '{s}'")),
+        };
+        let Some(schedule) = schedule else {
+            return Ok(strategy);
+        };
+
+        let invalid = || format!("Invalid schedule '{schedule}' in step strategy '{s}'");
+        let mut parts = schedule.split(':');
+        let name = parts.next().unwrap_or_default();
+        let values: Vec<&str> = parts.collect();
+        let number = |index: usize| {
+            values
+                .get(index)
+                .and_then(|value| value.parse::<FloatType>().ok())
+                .ok_or_else(invalid)
+        };
+
+        let schedule = match (name, values.len()) {
+            ("geometric", 1) => StepSchedule::geometric(number(0)?)?,
+            ("success", 2) => StepSchedule::success_based(number(0)?, number(1)?)?,
+            ("levels", 1) => StepSchedule::levels(values[0].parse().map_err(|_| invalid())?)?,
+            _ => {
+                return Err(format!(
+                    "Unknown schedule '{schedule}' in step strategy '{s}'"
+                ))
+            }
+        };
+
+        Ok(strategy.with_schedule(schedule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn steps(strategy: &StepStrategy, main_circle_radius: FloatType) -> Vec<FloatType> {
+        let step_sizes = strategy.step_sizes(main_circle_radius);
+        let mut steps = Vec::new();
+        let mut step = step_sizes.initial;
+        while !step_sizes.is_finished(step) {
+            steps.push(step);
+            step = step_sizes.next(step, false, false);
+        }
+        steps
+    }
+
+    #[test]
+    fn parsed_strategies() {
+        let strategy: StepStrategy = "relative,levels:8".parse().unwrap();
+        let levels = steps(&strategy, 10.0);
+        assert_eq!(levels.len(), 8);
+        assert!((levels[0] - 4.0).abs() < 1e-12);
+        assert!((levels[7] - 4.0 / 4096.0).abs() < 1e-12);
+
+        let strategy: StepStrategy = "fixed,success:4:2".parse().unwrap();
+        let step_sizes = strategy.step_sizes(10.0);
+        assert_eq!(step_sizes.initial, 40.96);
+        assert_eq!(step_sizes.next(1.0, true, false), 2.0);
+        assert_eq!(step_sizes.next(1.0, false, false), 0.25);
+
+        // relative steps follow the scale of the instance
+        let strategy = StepStrategy::relative();
+        assert_eq!(steps(&strategy, 1.0).len(), steps(&strategy, 1000.0).len());
+    }
+
+    #[test]
+    fn schedules_that_never_finish_are_rejected() {
+        assert!(StepSchedule::geometric(1.0).is_err());
+        assert!(StepSchedule::geometric(0.5).is_err());
+        assert!(StepSchedule::success_based(2.0, 0.5).is_err());
+        assert!(StepSchedule::levels(1).is_err());
+        assert!("relative,geometric:1".parse::<StepStrategy>().is_err());
+        assert!("relative,levels:x".parse::<StepStrategy>().is_err());
+        assert!("absolute".parse::<StepStrategy>().is_err());
+    }
+
+    #[test]
+    fn unusable_steps_are_clamped() {
+        assert!(steps(&StepStrategy::relative(), 0.0).is_empty());
+        assert!(steps(&StepStrategy::relative(), FloatType::NAN).is_empty());
+
+        let strategy = StepStrategy::default()
+            .with_initial_step(StepSize::Fixed(0.01))
+            .with_min_step(StepSize::Fixed(0.1))
+            .with_schedule(StepSchedule::levels(4).unwrap());
+        assert_eq!(steps(&strategy, 10.0), vec![0.01]);
+
+        let strategy =
+            StepStrategy::default().with_schedule(StepSchedule::Geometric { factor: 1.0 });
+        assert_eq!(steps(&strategy, 10.0), vec![40.96]);
+    }
+}
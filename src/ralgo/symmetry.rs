@@ -0,0 +1,470 @@
+use std::{f64::consts::PI, fmt::Display, str::FromStr};
+
+use nalgebra::{convert, DVector, RealField};
+
+use crate::{circle::Circle, point::Point, utils::FloatType};
+
+/// Symmetry group of the packing, its center is the main circle center (0, 0).
+#[derive(Debug, Clone, Copy)]
+pub enum Symmetry {
+    /// `C_k`: rotations by `2 pi j / k`.
+    Rotation { order: usize },
+    /// Reflection in the axis through (0, 0) at `angle` (radians) to the x axis.
+    Reflection { angle: FloatType },
+    /// `D_k`: rotations of `C_k` and reflections, one of the axes is at `angle`.
+    Dihedral { order: usize, angle: FloatType },
+}
+
+/// Angle in degrees without rounding noise of the conversion.
+fn degrees(angle: FloatType) -> FloatType {
+    (angle.to_degrees() * 1e9).round() / 1e9
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Symmetry::Rotation { order } => write!(f, "C{order}"),
+            Symmetry::Reflection { angle } => write!(f, "reflection {}°", degrees(*angle)),
+            Symmetry::Dihedral { order, angle } => write!(f, "D{order} {}°", degrees(*angle)),
+        }
+    }
+}
+
+/// `C<k>`, `D<k>[:<degrees>]` or `reflection[:<degrees>]`, e.g. `C5`, `D3:90`.
+impl FromStr for Symmetry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, angle) = match s.trim().split_once(':') {
+            Some((name, angle)) => (
+                name,
+                angle
+                    .trim()
+                    .parse::<FloatType>()
+                    .map_err(|_| format!("Invalid axis angle '{angle}' in symmetry '{s}'"))?
+                    .to_radians(),
+            ),
+            None => (s.trim(), 0.0),
+        };
+
+        let order = |digits: &str| match digits.parse::<usize>() {
+            Ok(order) if order >= 1 => Ok(order),
+            _ => Err(format!("Invalid order '{digits}' in symmetry '{s}'")),
+        };
+
+        match name.to_lowercase().as_str() {
+            "reflection" | "mirror" => Ok(Symmetry::Reflection { angle }),
+            name if name.starts_with('c') => Ok(Symmetry::Rotation {
+                order: order(&name[1..])?,
+            }),
+            name if name.starts_with('d') => Ok(Symmetry::Dihedral {
+                order: order(&name[1..])?,
+                angle,
+            }),
+            _ => Err(format!("Unknown symmetry '{s}'")),
+        }
+    }
+}
+
+/// Orthogonal map `(x, y) -> (a x + b y, c x + d y)`.
+#[derive(Debug, Clone, Copy)]
+struct Transform {
+    a: FloatType,
+    b: FloatType,
+    c: FloatType,
+    d: FloatType,
+}
+
+impl Transform {
+    fn rotation(angle: FloatType) -> Self {
+        Transform {
+            a: angle.cos(),
+            b: -angle.sin(),
+            c: angle.sin(),
+            d: angle.cos(),
+        }
+    }
+
+    fn reflection(angle: FloatType) -> Self {
+        Transform {
+            a: (2.0 * angle).cos(),
+            b: (2.0 * angle).sin(),
+            c: (2.0 * angle).sin(),
+            d: -(2.0 * angle).cos(),
+        }
+    }
+
+    fn apply<T: RealField + Copy>(&self, p: &Point<T>) -> Point<T> {
+        Point {
+            x: p.x * convert(self.a) + p.y * convert(self.b),
+            y: p.x * convert(self.c) + p.y * convert(self.d),
+        }
+    }
+
+    /// Inverse map, which is transposed for orthogonal one.
+    fn apply_inverse<T: RealField + Copy>(&self, p: &Point<T>) -> Point<T> {
+        Point {
+            x: p.x * convert(self.a) + p.y * convert(self.c),
+            y: p.x * convert(self.b) + p.y * convert(self.d),
+        }
+    }
+}
+
+impl Symmetry {
+    fn order(&self) -> usize {
+        match self {
+            Symmetry::Rotation { order } | Symmetry::Dihedral { order, .. } => *order,
+            Symmetry::Reflection { .. } => 1,
+        }
+    }
+
+    /// Rotations first, then reflections.
+    fn elements(&self) -> Vec<Transform> {
+        let order = self.order();
+        let rotations =
+            (0..order).map(|j| Transform::rotation(2.0 * PI * j as FloatType / order as FloatType));
+
+        match self {
+            Symmetry::Rotation { .. } => rotations.collect(),
+            Symmetry::Reflection { angle } => {
+                vec![Transform::rotation(0.0), Transform::reflection(*angle)]
+            }
+            Symmetry::Dihedral { angle, .. } => rotations
+                .clone()
+                .chain(rotations.map(|rotation| {
+                    let reflection = Transform::reflection(*angle);
+                    // rotation after reflection
+                    Transform {
+                        a: rotation.a * reflection.a + rotation.b * reflection.c,
+                        b: rotation.a * reflection.b + rotation.b * reflection.d,
+                        c: rotation.c * reflection.a + rotation.d * reflection.c,
+                        d: rotation.c * reflection.b + rotation.d * reflection.d,
+                    }
+                }))
+                .collect(),
+        }
+    }
+
+    fn axis(&self) -> Option<Point> {
+        match self {
+            Symmetry::Rotation { .. } => None,
+            Symmetry::Reflection { angle } | Symmetry::Dihedral { angle, .. } => Some(Point {
+                x: angle.cos(),
+                y: angle.sin(),
+            }),
+        }
+    }
+
+    /// Split circles into orbits of the group. Circles of the same radius fill full orbits
+    /// first, the rest go to reflection axis (orbits of `k` circles for `D_k`) and at most
+    /// one circle of the packing goes to the center.
+    pub fn orbits(&self, radiuses: &[FloatType]) -> Result<Orbits, String> {
+        let elements = self.elements();
+        let axis = self.axis();
+        let mut orbits = Vec::new();
+        let mut is_center_used = false;
+
+        let mut groups: Vec<(FloatType, Vec<usize>)> = Vec::new();
+        for (i, radius) in radiuses.iter().enumerate() {
+            match groups.iter_mut().find(|(r, _)| r == radius) {
+                Some((_, indices)) => indices.push(i),
+                None => groups.push((*radius, vec![i])),
+            }
+        }
+
+        for (radius, indices) in groups {
+            let (full_orbits, rest) = (
+                indices.len() / elements.len(),
+                indices.len() % elements.len(),
+            );
+
+            let mut indices = indices.into_iter();
+            let mut take = |kind: OrbitKind, size: usize| Orbit {
+                kind,
+                members: (&mut indices).take(size).zip(0..size).collect(),
+            };
+            for _ in 0..full_orbits {
+                orbits.push(take(OrbitKind::Free, elements.len()));
+            }
+
+            let axis_orbits = match axis {
+                Some(_) => rest / self.order(),
+                None => 0,
+            };
+            for _ in 0..axis_orbits {
+                orbits.push(take(OrbitKind::Axis, self.order()));
+            }
+
+            match rest - axis_orbits * self.order() {
+                0 => {}
+                1 if !is_center_used => {
+                    is_center_used = true;
+                    orbits.push(take(OrbitKind::Center, 1));
+                }
+                left => {
+                    return Err(format!(
+                        "{left} circles of radius {radius} don't form orbits of {self}"
+                    ))
+                }
+            }
+        }
+
+        Ok(Orbits {
+            elements,
+            axis: axis.unwrap_or(Point::empty()),
+            orbits,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OrbitKind {
+    /// Representative center `(x, y)` is free.
+    Free,
+    /// Representative center `t * axis`.
+    Axis,
+    /// Single circle at (0, 0).
+    Center,
+}
+
+impl OrbitKind {
+    fn variables(&self) -> usize {
+        match self {
+            OrbitKind::Free => 2,
+            OrbitKind::Axis => 1,
+            OrbitKind::Center => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Orbit {
+    kind: OrbitKind,
+    /// Circle index and group element which maps representative to the circle.
+    members: Vec<(usize, usize)>,
+}
+
+/// Orbits of the packing circles. Optimization vector holds only variables of orbit
+/// representatives: `[orbit variables.., R]`.
+#[derive(Debug, Clone)]
+pub struct Orbits {
+    elements: Vec<Transform>,
+    axis: Point,
+    orbits: Vec<Orbit>,
+}
+
+impl Orbits {
+    pub fn variables(&self) -> usize {
+        self.orbits.iter().map(|o| o.kind.variables()).sum()
+    }
+
+    /// Representatives are taken from the first circle of every orbit, the others are
+    /// ignored.
+    pub fn circles_to_dvector<T: RealField + Copy>(
+        &self,
+        circles: &Vec<Circle<T>>,
+        main_circle_radius: T,
+    ) -> DVector<T> {
+        let axis = Point::<T> {
+            x: convert(self.axis.x),
+            y: convert(self.axis.y),
+        };
+        let mut data = Vec::with_capacity(self.variables() + 1);
+
+        for orbit in &self.orbits {
+            let (i, element) = orbit.members[0];
+            let p = self.elements[element].apply_inverse(&circles[i].center.expect("Valid center"));
+            match orbit.kind {
+                OrbitKind::Free => data.extend([p.x, p.y]),
+                OrbitKind::Axis => data.push(p.x * axis.x + p.y * axis.y),
+                OrbitKind::Center => {}
+            }
+        }
+        data.push(main_circle_radius);
+
+        DVector::from_vec(data)
+    }
+
+    /// Full optimization vector `[x_1..x_n, y_1..y_n, R]` of `calcfg`.
+    pub fn expand<T: RealField + Copy>(&self, x: &DVector<T>) -> DVector<T> {
+        let n = self.orbits.iter().map(|o| o.members.len()).sum::<usize>();
+        let axis = Point::<T> {
+            x: convert(self.axis.x),
+            y: convert(self.axis.y),
+        };
+        let mut full = DVector::<T>::zeros(2 * n + 1);
+
+        let mut offset = 0;
+        for orbit in &self.orbits {
+            let representative = match orbit.kind {
+                OrbitKind::Free => Point {
+                    x: x[offset],
+                    y: x[offset + 1],
+                },
+                OrbitKind::Axis => Point {
+                    x: x[offset] * axis.x,
+                    y: x[offset] * axis.y,
+                },
+                OrbitKind::Center => Point {
+                    x: T::zero(),
+                    y: T::zero(),
+                },
+            };
+            offset += orbit.kind.variables();
+
+            for &(i, element) in &orbit.members {
+                let p = self.elements[element].apply(&representative);
+                (full[i], full[n + i]) = (p.x, p.y);
+            }
+        }
+        full[2 * n] = x[x.len() - 1];
+
+        full
+    }
+
+    /// Gradient by representatives variables from the gradient `g` of `calcfg` by all
+    /// circles: sum of gradients of orbit members mapped back to the representative.
+    pub fn reduce_gradient<T: RealField + Copy>(&self, g: &DVector<T>) -> DVector<T> {
+        let n = (g.len() - 1) / 2;
+        let axis = Point::<T> {
+            x: convert(self.axis.x),
+            y: convert(self.axis.y),
+        };
+        let mut reduced = DVector::<T>::zeros(self.variables() + 1);
+
+        let mut offset = 0;
+        for orbit in &self.orbits {
+            let mut sum = Point {
+                x: T::zero(),
+                y: T::zero(),
+            };
+            for &(i, element) in &orbit.members {
+                let p = self.elements[element].apply_inverse(&Point {
+                    x: g[i],
+                    y: g[n + i],
+                });
+                sum.x += p.x;
+                sum.y += p.y;
+            }
+
+            match orbit.kind {
+                OrbitKind::Free => (reduced[offset], reduced[offset + 1]) = (sum.x, sum.y),
+                OrbitKind::Axis => reduced[offset] = sum.x * axis.x + sum.y * axis.y,
+                OrbitKind::Center => {}
+            }
+            offset += orbit.kind.variables();
+        }
+        reduced[self.variables()] = g[2 * n];
+
+        reduced
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ralgo::calcfg_symmetric::calcfg_symmetric;
+
+    fn circle(radius: FloatType, x: FloatType, y: FloatType) -> Circle {
+        Circle::new(radius, Point { x, y })
+    }
+
+    /// Symmetric packings, circles of every orbit go in the order of group elements.
+    fn packings(distance: FloatType) -> Vec<(Symmetry, Vec<Circle>)> {
+        let rotated = |angle: FloatType| {
+            circle(
+                1.0,
+                distance * (angle + 0.4).cos(),
+                distance * (angle + 0.4).sin(),
+            )
+        };
+        let (x, y) = (0.75 * distance, 0.35 * distance);
+
+        vec![
+            (
+                "C3".parse().unwrap(),
+                vec![
+                    rotated(0.0),
+                    rotated(2.0 * PI / 3.0),
+                    rotated(4.0 * PI / 3.0),
+                    circle(0.5, 0.0, 0.0),
+                ],
+            ),
+            (
+                "reflection".parse().unwrap(),
+                vec![
+                    circle(1.0, x, y),
+                    circle(1.0, x, -y),
+                    circle(0.5, -distance, 0.0),
+                ],
+            ),
+            (
+                "D2:90".parse().unwrap(),
+                vec![
+                    circle(1.0, x, y),
+                    circle(1.0, -x, -y),
+                    circle(1.0, -x, y),
+                    circle(1.0, x, -y),
+                    circle(0.3, 0.0, 1.25 * distance),
+                    circle(0.3, 0.0, -1.25 * distance),
+                    circle(0.2, 0.0, 0.0),
+                ],
+            ),
+        ]
+    }
+
+    fn radiuses(circles: &Vec<Circle>) -> Vec<FloatType> {
+        circles.iter().map(|c| c.radius).collect()
+    }
+
+    #[test]
+    fn expanded_representatives_reproduce_symmetric_packing() {
+        for (symmetry, circles) in packings(2.0) {
+            let orbits = symmetry.orbits(&radiuses(&circles)).unwrap();
+            let x = orbits.circles_to_dvector(&circles, 4.0);
+            assert_eq!(x.len(), orbits.variables() + 1);
+
+            let full = orbits.expand(&x);
+            let n = circles.len();
+            for (i, c) in circles.iter().enumerate() {
+                let center = c.center.unwrap();
+                assert!(
+                    (full[i] - center.x).abs() < 1e-12 && (full[n + i] - center.y).abs() < 1e-12,
+                    "{symmetry}: circle {i} is at ({}, {}), expected {center:?}",
+                    full[i],
+                    full[n + i]
+                );
+            }
+            assert_eq!(full[2 * n], 4.0);
+        }
+    }
+
+    /// Coordinates have half of their gradient in `calcfg`, so central differences must
+    /// be twice the reduced gradient.
+    #[test]
+    fn reduced_gradient_matches_finite_differences() {
+        // circles overlap and stick out of the main circle, so every penalty is active
+        for (symmetry, circles) in packings(1.1) {
+            let orbits = symmetry.orbits(&radiuses(&circles)).unwrap();
+            let radiuses = DVector::from_vec(radiuses(&circles));
+            let x = orbits.circles_to_dvector(&circles, 1.5);
+
+            let (_, g) = calcfg_symmetric(&x, &radiuses, &orbits);
+            for k in 0..orbits.variables() {
+                let h = 1e-6;
+                let (mut forward, mut backward) = (x.clone(), x.clone());
+                forward[k] += h;
+                backward[k] -= h;
+                let difference = (calcfg_symmetric(&forward, &radiuses, &orbits).0
+                    - calcfg_symmetric(&backward, &radiuses, &orbits).0)
+                    / (2.0 * h);
+
+                assert!(
+                    (difference - 2.0 * g[k]).abs() <= 1e-5 * difference.abs().max(1.0),
+                    "{symmetry}: variable {k}: {difference} != 2 * {}",
+                    g[k]
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,170 @@
+use nalgebra::DVector;
+
+use crate::{circle::Circle, container::ContainerCenter, scaling::Scaling, utils::FloatType};
+
+use super::{
+    calcfg::calcfg,
+    dichotomy_step_ralgo::dichotomy_step,
+    ralgo_params::RalgoParams,
+    utils::{circles_to_dvector, dvector_to_answer},
+};
+
+/// Intermediate packing of `dichotomy_step_ralgo`.
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// 0 for the initial packing, then the number of the r-algorithm run.
+    pub step: usize,
+    /// Iteration of the r-algorithm run, `None` for the packing after the run.
+    pub iteration: Option<usize>,
+    pub main_circle_radius: FloatType,
+    /// Value of the penalty function in the scale of the instance.
+    pub objective: FloatType,
+    pub circles: Vec<Circle>,
+}
+
+impl Frame {
+    pub fn caption(&self) -> String {
+        let stage = match self.iteration {
+            Some(iteration) => format!("step {}, iteration {iteration}", self.step),
+            None if self.step == 0 => "start".to_string(),
+            None => format!("step {}", self.step),
+        };
+
+        format!(
+            "{stage}: R = {:.6}, f = {:.6}",
+            self.main_circle_radius, self.objective
+        )
+    }
+}
+
+/// Run `dichotomy_step_ralgo` recording the packing after every r-algorithm run and,
+/// when `every_iterations` is set, after every `k`-th iteration of the runs.
+pub fn dichotomy_step_ralgo_trajectory(
+    main_circle_radiuse: FloatType,
+    circles: &Vec<Circle>,
+    reset_step: bool,
+    eps: FloatType,
+    ralgo_params: &RalgoParams,
+    every_iterations: Option<usize>,
+) -> Result<Vec<Frame>, String> {
+    if every_iterations == Some(0) {
+        return Err("Frames can't be recorded every 0 iterations".to_string());
+    }
+
+    // the penalty is quadratic, so it is evaluated on the packing in the scale of the instance
+    let radiuses = DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
+    let objective = |main_circle_radius, circles: &Vec<Circle>| {
+        calcfg(
+            &circles_to_dvector(circles, main_circle_radius),
+            &radiuses,
+            &ContainerCenter::origin(),
+            &[],
+        )
+        .0
+    };
+    let mut frames = vec![Frame {
+        step: 0,
+        iteration: None,
+        main_circle_radius: main_circle_radiuse,
+        objective: objective(main_circle_radiuse, circles),
+        circles: circles.clone(),
+    }];
+
+    // solve in canonical scale and map the frames back
+    let scaling = Scaling::for_circles(circles);
+    let (main_circle_radiuse, circles) = (
+        scaling.scale(main_circle_radiuse),
+        &scaling.scale_circles(circles),
+    );
+
+    let circles_radiuses = DVector::from_vec(Vec::from_iter(circles.iter().map(|c| c.radius)));
+    dichotomy_step(
+        circles_to_dvector(circles, main_circle_radiuse),
+        |x| calcfg(x, &circles_radiuses, &ContainerCenter::origin(), &[]),
+        reset_step,
+        eps,
+        ralgo_params,
+        |step, iteration, x| {
+            let recorded = match iteration {
+                Some(iteration) => every_iterations.is_some_and(|k| iteration % k == 0),
+                None => true,
+            };
+            if !recorded {
+                return;
+            }
+
+            let (main_circle_radius, circles) = dvector_to_answer(x, &circles_radiuses);
+            let (main_circle_radius, circles) = (
+                scaling.unscale(main_circle_radius),
+                scaling.unscale_circles(&circles),
+            );
+            frames.push(Frame {
+                step,
+                iteration,
+                main_circle_radius,
+                objective: objective(main_circle_radius, &circles),
+                circles,
+            });
+        },
+    );
+
+    Ok(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{point::Point, ralgo::dichotomy_step_ralgo::dichotomy_step_ralgo};
+
+    fn circles() -> Vec<Circle> {
+        vec![
+            Circle::new(10.0, Point { x: -20.0, y: 5.0 }),
+            Circle::new(20.0, Point { x: 25.0, y: 0.0 }),
+            Circle::new(15.0, Point { x: 0.0, y: -30.0 }),
+        ]
+    }
+
+    #[test]
+    fn zero_iterations_between_frames_are_rejected() {
+        let result = dichotomy_step_ralgo_trajectory(
+            80.0,
+            &circles(),
+            false,
+            0.0,
+            &RalgoParams::default(),
+            Some(0),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn last_frame_is_result_of_dichotomy() {
+        let ralgo_params = RalgoParams::default().with_max_iterations(1000);
+        let frames =
+            dichotomy_step_ralgo_trajectory(80.0, &circles(), false, 0.0, &ralgo_params, Some(5))
+                .unwrap();
+        let (main_circle_radius, _) =
+            dichotomy_step_ralgo(80.0, &circles(), false, 0.0, &ralgo_params);
+
+        assert_eq!(frames[0].main_circle_radius, 80.0);
+        assert!(frames.iter().any(|frame| frame.iteration == Some(5)));
+        assert!(frames
+            .iter()
+            .all(|frame| frame.iteration.is_none_or(|iteration| iteration % 5 == 0)));
+
+        let last = frames.last().unwrap();
+        assert_eq!(last.iteration, None);
+        assert!((last.main_circle_radius - main_circle_radius).abs() < 1e-9 * main_circle_radius);
+
+        // objective is the penalty of the packing as it is drawn
+        let radiuses = DVector::from_vec(circles().iter().map(|c| c.radius).collect());
+        let expected = calcfg(
+            &circles_to_dvector(&last.circles, last.main_circle_radius),
+            &radiuses,
+            &ContainerCenter::origin(),
+            &[],
+        )
+        .0;
+        assert_eq!(last.objective, expected);
+    }
+}
@@ -1,66 +1,139 @@
-use nalgebra::DVector;
+use nalgebra::{convert, convert_unchecked, DVector, RealField};
 
-use crate::{circle, point, utils::FloatType};
+use crate::{circle, container::ContainerCenter, point};
 
-pub fn concat_gradients(
-    gx: &DVector<FloatType>,
-    gy: &DVector<FloatType>,
-    gr: FloatType,
-) -> DVector<FloatType> {
+pub fn concat_gradients<T: RealField + Copy>(
+    gx: &DVector<T>,
+    gy: &DVector<T>,
+    gc: Option<point::Point<T>>,
+    gr: T,
+) -> DVector<T> {
     let circles_number = gx.len();
+    let center_size = if gc.is_some() { 2 } else { 0 };
 
-    let mut gradient = DVector::<FloatType>::zeros(2 * circles_number + 1);
+    let mut gradient = DVector::<T>::zeros(2 * circles_number + center_size + 1);
     gradient.rows_mut(0, circles_number).copy_from(&gx);
     gradient
         .rows_mut(circles_number, circles_number)
         .copy_from(&gy);
-    gradient[2 * circles_number] = gr;
+    if let Some(gc) = gc {
+        gradient[2 * circles_number] = gc.x;
+        gradient[2 * circles_number + 1] = gc.y;
+    }
+    gradient[2 * circles_number + center_size] = gr;
 
     return gradient;
 }
 
-pub fn circles_to_dvector(
-    circles: &Vec<circle::Circle>,
-    main_circle_radiuse: FloatType,
-) -> DVector<FloatType> {
-    let data: Vec<FloatType> = ([
+/// Geometry outside of `ralgo` (main circle center, obstacles) is stored in `FloatType`.
+pub fn point_from_float_type<T: RealField + Copy>(p: &point::Point) -> point::Point<T> {
+    point::Point {
+        x: convert(p.x),
+        y: convert(p.y),
+    }
+}
+
+pub fn point_to_float_type<T: RealField + Copy>(p: &point::Point<T>) -> point::Point {
+    point::Point {
+        x: convert_unchecked(p.x),
+        y: convert_unchecked(p.y),
+    }
+}
+
+pub fn circles_to_dvector<T: RealField + Copy>(
+    circles: &Vec<circle::Circle<T>>,
+    main_circle_radiuse: T,
+) -> DVector<T> {
+    circles_to_dvector_with_center(circles, main_circle_radiuse, &ContainerCenter::origin())
+}
+
+/// Optimization vector `[x_1..x_n, y_1..y_n, (x_0, y_0), R]`, where circles coordinates
+/// are relative to the main circle center `(x_0, y_0)`. The center is included only when it is free.
+pub fn circles_to_dvector_with_center<T: RealField + Copy>(
+    circles: &Vec<circle::Circle<T>>,
+    main_circle_radiuse: T,
+    container_center: &ContainerCenter,
+) -> DVector<T> {
+    let center = point_from_float_type::<T>(&container_center.point());
+    let data: Vec<T> = ([
         Vec::from_iter(
             circles
                 .iter()
-                .map(|c| c.center.as_ref().expect("Valid center").x),
+                .map(|c| c.center.as_ref().expect("Valid center").x - center.x),
         ),
         Vec::from_iter(
             circles
                 .iter()
-                .map(|c| c.center.as_ref().expect("Valid center").y),
+                .map(|c| c.center.as_ref().expect("Valid center").y - center.y),
         ),
+        if container_center.is_free() {
+            vec![center.x, center.y]
+        } else {
+            vec![]
+        },
         vec![main_circle_radiuse],
     ])
     .concat();
     return DVector::from_vec(data);
 }
 
-pub fn dvector_to_answer(
-    x: &DVector<FloatType>,
-    circles_radiuses: &DVector<FloatType>,
-) -> (FloatType, Vec<circle::Circle>) {
+pub fn dvector_to_answer<T: RealField + Copy>(
+    x: &DVector<T>,
+    circles_radiuses: &DVector<T>,
+) -> (T, Vec<circle::Circle<T>>) {
     let main_circle_radiuse = x[x.len() - 1];
-    let mut circles: Vec<circle::Circle> = Vec::from_iter(
-        circles_radiuses
-            .iter()
-            .map(|radius| circle::Circle::with_radius(*radius)),
-    );
+    let circles_number = circles_radiuses.len();
+    let mut circles: Vec<circle::Circle<T>> =
+        Vec::from_iter(circles_radiuses.iter().map(|radius| circle::Circle {
+            center: None,
+            radius: *radius,
+        }));
 
-    for i in 0..x.len() / 2 {
+    for i in 0..circles_number {
         circles[i].center = Some(point::Point {
             x: x[i],
-            y: x[i + x.len() / 2],
+            y: x[i + circles_number],
         });
     }
 
     return (main_circle_radiuse, circles);
 }
 
-pub fn get_last(d: &DVector<FloatType>) -> FloatType {
+/// Main circle radius, its center and circles in absolute coordinates.
+pub fn dvector_to_answer_in_container<T: RealField + Copy>(
+    x: &DVector<T>,
+    circles_radiuses: &DVector<T>,
+    container_center: &ContainerCenter,
+) -> (T, point::Point<T>, Vec<circle::Circle<T>>) {
+    let (main_circle_radiuse, mut circles) = dvector_to_answer(x, circles_radiuses);
+    let center = dvector_to_center(x, circles.len(), container_center);
+
+    for circle in circles.iter_mut() {
+        if let Some(circle_center) = circle.center.as_mut() {
+            circle_center.x += center.x;
+            circle_center.y += center.y;
+        }
+    }
+
+    return (main_circle_radiuse, center, circles);
+}
+
+/// Main circle center stored in optimization vector (or fixed one).
+pub fn dvector_to_center<T: RealField + Copy>(
+    x: &DVector<T>,
+    circles_number: usize,
+    container_center: &ContainerCenter,
+) -> point::Point<T> {
+    if container_center.is_free() {
+        point::Point {
+            x: x[2 * circles_number],
+            y: x[2 * circles_number + 1],
+        }
+    } else {
+        point_from_float_type(&container_center.point())
+    }
+}
+
+pub fn get_last<T: RealField + Copy>(d: &DVector<T>) -> T {
     return d[d.len() - 1];
 }
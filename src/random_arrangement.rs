@@ -0,0 +1,76 @@
+use rand::Rng;
+
+use crate::{circle::Circle, point::Point, utils::FloatType};
+
+/// Uniform random point of the disc of `radius` centered at (0, 0).
+pub fn random_point(radius: FloatType, rng: &mut impl Rng) -> Point {
+    loop {
+        let (x, y) = (
+            rng.gen_range(-radius..=radius),
+            rng.gen_range(-radius..=radius),
+        );
+
+        if x.powi(2) + y.powi(2) <= radius.powi(2) {
+            return Point { x, y };
+        }
+    }
+}
+
+/// Circles of `radiuses` centered at random points of the disc of `main_circle_radius`,
+/// they may overlap each other and the border of the disc.
+pub fn generate_random_arrangement(
+    main_circle_radius: FloatType,
+    radiuses: &Vec<FloatType>,
+    rng: &mut impl Rng,
+) -> Vec<Circle> {
+    radiuses
+        .iter()
+        .map(|radius| Circle::new(*radius, random_point(main_circle_radius, rng)))
+        .collect()
+}
+
+/// Radius of the main circle centered at (0, 0) which contains all circles.
+pub fn enclosing_radius(circles: &Vec<Circle>) -> FloatType {
+    circles
+        .iter()
+        .map(|c| (c.center.unwrap().x.powi(2) + c.center.unwrap().y.powi(2)).sqrt() + c.radius)
+        .fold(0.0, FloatType::max)
+}
+
+/// Random arrangement in the disc whose area is the area of circles divided by `density`,
+/// with `R` of the main circle which contains it.
+pub fn random_start(
+    radiuses: &Vec<FloatType>,
+    density: FloatType,
+    rng: &mut impl Rng,
+) -> (FloatType, Vec<Circle>) {
+    let generation_radius =
+        (radiuses.iter().map(|r| r.powi(2)).sum::<FloatType>() / density).sqrt();
+    let circles = generate_random_arrangement(generation_radius, radiuses, rng);
+
+    (enclosing_radius(&circles), circles)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn random_start_is_enclosed() {
+        let radiuses = vec![1.0, 2.0, 0.5, 3.0];
+        let mut rng = StdRng::seed_from_u64(0);
+        let (main_circle_radius, circles) = random_start(&radiuses, 0.5, &mut rng);
+
+        let generation_radius = (14.25 as FloatType / 0.5).sqrt();
+        for (circle, radius) in circles.iter().zip(&radiuses) {
+            let center = circle.center.unwrap();
+            let distance = (center.x.powi(2) + center.y.powi(2)).sqrt();
+            assert_eq!(circle.radius, *radius);
+            assert!(distance <= generation_radius);
+            assert!(distance + radius <= main_circle_radius);
+        }
+        assert_eq!(main_circle_radius, enclosing_radius(&circles));
+    }
+}
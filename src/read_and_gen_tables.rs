@@ -6,7 +6,10 @@ use rust_xlsxwriter::{
     ChartType, Color, Format, Workbook, Worksheet,
 };
 
-use crate::utils::FloatType;
+use crate::{
+    utils::FloatType,
+    workspace::{save_workbook, workspace},
+};
 
 #[derive(Clone, Copy, Debug)]
 struct BlockData {
@@ -127,9 +130,11 @@ pub fn read_and_gen_random_single_case_iterations(
         .set_background_color(Color::RGB(0xaccc9f))
         .set_num_format("0.#####");
 
-    let mut workbook: Xlsx<_> = open_workbook(format!(
-        "results/random/random-single-result-test-{test_number}.xlsx"
-    ))?;
+    let mut workbook: Xlsx<_> = open_workbook(
+        workspace()
+            .random_results
+            .join(format!("random-single-result-test-{test_number}.xlsx")),
+    )?;
     let mut output_workbook = Workbook::new();
 
     fn write_block(
@@ -626,11 +631,12 @@ pub fn read_and_gen_random_single_case_iterations(
         output_worksheet.autofit();
     }
 
-    output_workbook
-        .save(format!(
-            "./results/random/total result random-single-result-test-{test_number}-t.xlsx"
-        ))
-        .ok();
+    save_workbook(
+        &mut output_workbook,
+        workspace().random_results.join(format!(
+            "total result random-single-result-test-{test_number}-t.xlsx"
+        )),
+    )?;
 
     Ok(())
 }
@@ -647,9 +653,11 @@ pub fn read_and_gen_heuristic(alpha_q1_pairs: &Vec<(FloatType, FloatType)>) -> R
     output_worksheet.write(0, 5, "iterations_variant_2").ok();
 
     for (table_row, (alpha, q1)) in alpha_q1_pairs.iter().enumerate() {
-        let mut workbook: Xlsx<_> = open_workbook(format!(
-            "results/heuristic/result-multi-alpha={alpha}-q1={q1}.xlsx"
-        ))?;
+        let mut workbook: Xlsx<_> = open_workbook(
+            workspace()
+                .heuristic_results
+                .join(format!("result-multi-alpha={alpha}-q1={q1}.xlsx")),
+        )?;
 
         let sum_on_range = |range: &Range<DataType>,
                             row_start: usize,
@@ -694,9 +702,10 @@ pub fn read_and_gen_heuristic(alpha_q1_pairs: &Vec<(FloatType, FloatType)>) -> R
         }
     }
 
-    output_workbook
-        .save(format!("./results/heuristic/total-heuristic.xlsx"))
-        .ok();
+    save_workbook(
+        &mut output_workbook,
+        workspace().heuristic_results.join("total-heuristic.xlsx"),
+    )?;
 
     Ok(())
 }
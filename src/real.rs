@@ -0,0 +1,181 @@
+use std::{
+    cmp::Ordering,
+    fmt::{Debug, Display},
+    ops::{Add, Div, Mul, Neg, Sub},
+    str::FromStr,
+};
+
+use dashu_base::SquareRoot;
+use dashu_float::{round::mode::HalfEven, FBig};
+use nalgebra::RealField;
+
+/// Number type geometry, validation and polishing are generic over.
+pub trait Real:
+    Clone
+    + Debug
+    + Display
+    + PartialOrd
+    + Send
+    + Sync
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    fn from_f64(value: f64) -> Self;
+
+    fn to_f64(&self) -> f64;
+
+    fn parse(value: &str) -> Option<Self>;
+
+    fn sqrt(&self) -> Self;
+
+    /// Total order like `f64::total_cmp`, so sorting doesn't panic on NaN.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Relative precision of the type.
+    fn epsilon() -> Self;
+
+    fn zero() -> Self {
+        Self::from_f64(0.0)
+    }
+
+    fn square(&self) -> Self {
+        self.clone() * self.clone()
+    }
+
+    fn abs(&self) -> Self {
+        if *self < Self::zero() {
+            -self.clone()
+        } else {
+            self.clone()
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other > self {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+macro_rules! impl_real_for_primitive {
+    ($t:ty) => {
+        impl Real for $t {
+            fn from_f64(value: f64) -> Self {
+                value as $t
+            }
+
+            fn to_f64(&self) -> f64 {
+                *self as f64
+            }
+
+            fn parse(value: &str) -> Option<Self> {
+                value.trim().parse().ok()
+            }
+
+            fn sqrt(&self) -> Self {
+                <$t>::sqrt(*self)
+            }
+
+            fn total_cmp(&self, other: &Self) -> Ordering {
+                <$t>::total_cmp(self, other)
+            }
+
+            fn epsilon() -> Self {
+                <$t>::EPSILON
+            }
+        }
+    };
+}
+
+impl_real_for_primitive!(f32);
+impl_real_for_primitive!(f64);
+
+/// Hardware float the r-algorithm can run in: `f32` for quick screening, `f64` by default.
+/// `nalgebra` matrices need `RealField`, so `BigFloat` is used only after r-algorithm.
+pub trait Float: Real + RealField + Copy {}
+
+impl<T: Real + RealField + Copy> Float for T {}
+
+/// Binary digits of `BigFloat` significand, about 38 decimal digits.
+pub const BIG_FLOAT_PRECISION: usize = 128;
+
+/// Multiprecision float for final refinement, packomania reports radii with 30 digits.
+#[derive(Clone, Debug, PartialEq, PartialOrd)]
+pub struct BigFloat(FBig<HalfEven, 2>);
+
+impl BigFloat {
+    fn new(value: FBig<HalfEven, 2>) -> Self {
+        BigFloat(value.with_precision(BIG_FLOAT_PRECISION).value())
+    }
+
+    fn powi(&self, exponent: i32) -> Self {
+        BigFloat::new(self.0.powi(exponent.into()))
+    }
+}
+
+impl Display for BigFloat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0.to_decimal().value(), f)
+    }
+}
+
+macro_rules! impl_big_float_operator {
+    ($trait:ident, $method:ident) => {
+        impl $trait for BigFloat {
+            type Output = BigFloat;
+
+            fn $method(self, other: BigFloat) -> BigFloat {
+                BigFloat::new($trait::$method(self.0, other.0))
+            }
+        }
+    };
+}
+
+impl_big_float_operator!(Add, add);
+impl_big_float_operator!(Sub, sub);
+impl_big_float_operator!(Mul, mul);
+impl_big_float_operator!(Div, div);
+
+impl Neg for BigFloat {
+    type Output = BigFloat;
+
+    fn neg(self) -> BigFloat {
+        BigFloat(-self.0)
+    }
+}
+
+impl Real for BigFloat {
+    fn from_f64(value: f64) -> Self {
+        BigFloat::new(FBig::try_from(value).expect("Finite float"))
+    }
+
+    fn to_f64(&self) -> f64 {
+        self.0.to_f64().value()
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        let decimal = dashu_float::DBig::from_str(value.trim()).ok()?;
+        let binary = decimal
+            .with_base_and_precision::<2>(BIG_FLOAT_PRECISION)
+            .value()
+            .with_rounding::<HalfEven>();
+        Some(BigFloat::new(binary))
+    }
+
+    fn sqrt(&self) -> Self {
+        BigFloat::new(self.0.sqrt())
+    }
+
+    fn total_cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+
+    fn epsilon() -> Self {
+        BigFloat::from_f64(2.0).powi(-(BIG_FLOAT_PRECISION as i32 - 1))
+    }
+}
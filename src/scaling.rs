@@ -0,0 +1,104 @@
+use nalgebra::convert;
+
+use crate::{
+    circle::Circle,
+    container::ContainerCenter,
+    obstacle::Obstacle,
+    point::Point,
+    ralgo::ralgo_result::RalgoResult,
+    real::{Float, Real},
+    utils::FloatType,
+};
+
+/// `sqrt(sum r_i^2)` of canonical instance. Penalties of `calcfg` and default step sizes
+/// are tuned for radii 1..N with N about 30, which have this size.
+const CANONICAL_SIZE: FloatType = 100.0;
+
+/// Homothety with center (0, 0) that maps an instance to the canonical scale before
+/// solving. Factor is a power of two, so scaling and mapping results back are exact
+/// and valid packings stay valid.
+#[derive(Debug, Clone, Copy)]
+pub struct Scaling {
+    pub factor: FloatType,
+}
+
+impl Scaling {
+    pub fn identity() -> Self {
+        Scaling { factor: 1.0 }
+    }
+
+    /// Scaling of instance with given sizes of items (radii of circles).
+    pub fn canonical(sizes: impl Iterator<Item = FloatType>) -> Self {
+        let size = sizes.map(|s| s.powi(2)).sum::<FloatType>().sqrt();
+        if !size.is_normal() {
+            return Scaling::identity();
+        }
+
+        Scaling {
+            factor: (2.0 as FloatType).powi((CANONICAL_SIZE / size).log2().round() as i32),
+        }
+    }
+
+    pub fn for_circles<T: Real>(circles: &Vec<Circle<T>>) -> Self {
+        Scaling::canonical(circles.iter().map(|c| c.radius.to_f64() as FloatType))
+    }
+
+    pub fn scale<T: Float>(&self, value: T) -> T {
+        value * convert(self.factor)
+    }
+
+    pub fn unscale<T: Float>(&self, value: T) -> T {
+        value / convert(self.factor)
+    }
+
+    pub fn scale_point<T: Float>(&self, point: &Point<T>) -> Point<T> {
+        Point {
+            x: self.scale(point.x),
+            y: self.scale(point.y),
+        }
+    }
+
+    pub fn unscale_point<T: Float>(&self, point: &Point<T>) -> Point<T> {
+        Point {
+            x: self.unscale(point.x),
+            y: self.unscale(point.y),
+        }
+    }
+
+    pub fn scale_circles<T: Float>(&self, circles: &Vec<Circle<T>>) -> Vec<Circle<T>> {
+        circles
+            .iter()
+            .map(|c| Circle {
+                center: c.center.as_ref().map(|center| self.scale_point(center)),
+                radius: self.scale(c.radius),
+            })
+            .collect()
+    }
+
+    pub fn unscale_circles<T: Float>(&self, circles: &Vec<Circle<T>>) -> Vec<Circle<T>> {
+        circles
+            .iter()
+            .map(|c| Circle {
+                center: c.center.as_ref().map(|center| self.unscale_point(center)),
+                radius: self.unscale(c.radius),
+            })
+            .collect()
+    }
+
+    pub fn scale_container_center(&self, container_center: &ContainerCenter) -> ContainerCenter {
+        container_center.with_point(self.scale_point(&container_center.point()))
+    }
+
+    pub fn scale_obstacles(&self, obstacles: &[Obstacle]) -> Vec<Obstacle> {
+        obstacles.iter().map(|o| o.scaled(self.factor)).collect()
+    }
+
+    pub fn unscale_result<T: Float>(&self, result: RalgoResult<T>) -> RalgoResult<T> {
+        RalgoResult {
+            main_circle_radius: self.unscale(result.main_circle_radius),
+            main_circle_center: self.unscale_point(&result.main_circle_center),
+            circles: self.unscale_circles(&result.circles),
+            ..result
+        }
+    }
+}
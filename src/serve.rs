@@ -0,0 +1,227 @@
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    instance::{
+        json::to_json,
+        read_known_best,
+        value::{Value, ValueKind},
+    },
+    solution::{Solution, SolutionFormat},
+    utils::FloatType,
+    workspace::workspace,
+};
+
+/// Viewer page, it has no external scripts or styles, so works offline.
+const VIEWER: &str = include_str!("serve/viewer.html");
+
+/// Time to wait for the request, a client which sends nothing mustn't block the server.
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn value(kind: ValueKind) -> Value {
+    Value::new(kind, 0)
+}
+
+fn number_or_null(number: Option<FloatType>) -> Value {
+    value(number.map_or(ValueKind::Null, ValueKind::Number))
+}
+
+/// Best known `R` of instance named by test number: jury answer of the workspace or the
+/// packomania record.
+fn known_best(instance: &str) -> Option<FloatType> {
+    let test_number: u32 = instance.parse().ok()?;
+    read_known_best(workspace().answer_file(test_number))
+        .or_else(|_| read_known_best(workspace().packomania_file(test_number)))
+        .ok()
+}
+
+/// Solution files of the directory, sorted by name.
+fn solution_files(directory: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| SolutionFormat::from_path(Path::new(name)).is_some())
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+/// Summary of every solution file for the list of the viewer.
+fn solutions_json(directory: &str) -> String {
+    let summaries = solution_files(directory)
+        .into_iter()
+        .map(|name| {
+            let mut entries = vec![("name".to_string(), value(ValueKind::String(name.clone())))];
+            match Solution::read(Path::new(directory).join(&name)) {
+                Ok(solution) => entries.extend([
+                    (
+                        "instance".to_string(),
+                        value(ValueKind::String(solution.instance.clone())),
+                    ),
+                    (
+                        "solver".to_string(),
+                        value(ValueKind::String(solution.solver.clone())),
+                    ),
+                    (
+                        "circles".to_string(),
+                        value(ValueKind::Number(solution.circles.len() as FloatType)),
+                    ),
+                    (
+                        "main_circle_radius".to_string(),
+                        value(ValueKind::Number(solution.main_circle_radius)),
+                    ),
+                    (
+                        "known_best".to_string(),
+                        number_or_null(known_best(&solution.instance)),
+                    ),
+                    (
+                        "is_valid".to_string(),
+                        value(ValueKind::Bool(solution.is_valid())),
+                    ),
+                    ("time".to_string(), number_or_null(solution.time)),
+                ]),
+                Err(error) => entries.push((
+                    "error".to_string(),
+                    value(ValueKind::String(error.to_string())),
+                )),
+            }
+            value(ValueKind::Object(entries))
+        })
+        .collect();
+
+    to_json(&value(ValueKind::Array(summaries)), 0)
+}
+
+/// Solution document of `Solution::to_value` with `known_best` and `is_valid`.
+fn solution_json(directory: &str, name: &str) -> Result<String, String> {
+    // only files of the directory itself are served
+    if !solution_files(directory).iter().any(|file| file == name) {
+        return Err(format!("Unknown solution '{name}'"));
+    }
+
+    let solution =
+        Solution::read(Path::new(directory).join(name)).map_err(|error| error.to_string())?;
+    let mut document = solution.to_value();
+    if let ValueKind::Object(entries) = &mut document.kind {
+        entries.push((
+            "known_best".to_string(),
+            number_or_null(known_best(&solution.instance)),
+        ));
+        entries.push((
+            "is_valid".to_string(),
+            value(ValueKind::Bool(solution.is_valid())),
+        ));
+    }
+
+    Ok(to_json(&document, 0))
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = match bytes[i] {
+            b'%' => bytes
+                .get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match (escaped, bytes[i]) {
+            (Some(byte), _) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (None, b'+') => decoded.push(b' '),
+            (None, byte) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn query_parameter(query: &str, key: &str) -> Option<String> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| percent_decode(v))
+}
+
+fn handle(mut stream: TcpStream, directory: &str) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // headers aren't used, but have to be read before the answer
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    let (status, content_type, body) = match (method, path) {
+        ("GET", "/") => ("200 OK", "text/html; charset=utf-8", VIEWER.to_string()),
+        ("GET", "/api/solutions") => ("200 OK", "application/json", solutions_json(directory)),
+        ("GET", "/api/solution") => {
+            match solution_json(
+                directory,
+                &query_parameter(query, "name").unwrap_or_default(),
+            ) {
+                Ok(json) => ("200 OK", "application/json", json),
+                Err(error) => ("404 Not Found", "text/plain; charset=utf-8", error),
+            }
+        }
+        ("GET", _) => (
+            "404 Not Found",
+            "text/plain; charset=utf-8",
+            "Not found".to_string(),
+        ),
+        _ => (
+            "405 Method Not Allowed",
+            "text/plain; charset=utf-8",
+            "Only GET is supported".to_string(),
+        ),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {status}\r\nContent-Type: {content_type}\r\nContent-Length: {}\r\n\
+         Cache-Control: no-store\r\nConnection: close\r\n\r\n",
+        body.len()
+    )?;
+    stream.write_all(body.as_bytes())
+}
+
+/// Serve the viewer of solution files (`.json` and packomania `.txt`) of `directory` on
+/// `address`, e.g. `127.0.0.1:8080`. Files are read on every request, so new results
+/// appear after reloading the page. Runs until the process is stopped.
+pub fn serve(address: &str, directory: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    println!("Viewer of {directory} is available at http://{address}/");
+
+    for stream in listener.incoming() {
+        if let Err(error) = stream.and_then(|stream| handle(stream, directory)) {
+            println!("Failed to answer request: {error}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,432 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{
+    circle::Circle,
+    instance::{
+        json::{parse_json, to_json},
+        text::parse_packomania,
+        value::{ParseError, Value, ValueKind},
+        InstanceError,
+    },
+    packing::is_valid_pack,
+    pipeline::Pipeline,
+    point::Point,
+    ralgo::{dichotomy_step_ralgo::dichotomy_step_ralgo, ralgo_params::RalgoParams},
+    utils::FloatType,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SolutionFormat {
+    /// Everything: packing, solver settings, seed and timing. Numbers are written
+    /// in the shortest form that reads back exactly.
+    Json,
+    /// `R` and `radius x/R y/R` lines, as packomania files are. Only the packing is
+    /// kept and normalization may change centers by an ulp.
+    Packomania,
+}
+
+impl SolutionFormat {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "json" => Some(SolutionFormat::Json),
+            "txt" => Some(SolutionFormat::Packomania),
+            _ => None,
+        }
+    }
+}
+
+/// Packing found by a solver together with the way it was found.
+#[derive(Debug, Clone)]
+pub struct Solution {
+    pub main_circle_radius: FloatType,
+    pub circles: Vec<Circle>,
+    /// Name of the instance, e.g. test number.
+    pub instance: String,
+    pub solver: String,
+    /// Solver settings in the order they were added.
+    pub settings: Vec<(String, String)>,
+    pub seed: Option<u64>,
+    /// Solving time in seconds.
+    pub time: Option<FloatType>,
+    /// Main circle radius after every step of the solver, e.g. pipeline stage.
+    pub trace: Vec<FloatType>,
+}
+
+fn required<'a>(key: &str, of: &'a Value, what: &str) -> Result<&'a Value, ParseError> {
+    of.get(key)
+        .ok_or_else(|| ParseError::new(of.line, format!("Missing '{key}' of {what}")))
+}
+
+impl Solution {
+    pub fn new(main_circle_radius: FloatType, circles: Vec<Circle>) -> Self {
+        Solution {
+            main_circle_radius,
+            circles,
+            instance: String::new(),
+            solver: String::new(),
+            settings: Vec::new(),
+            seed: None,
+            time: None,
+            trace: Vec::new(),
+        }
+    }
+
+    pub fn with_instance(self, instance: impl Into<String>) -> Self {
+        Solution {
+            instance: instance.into(),
+            ..self
+        }
+    }
+
+    pub fn with_solver(self, solver: impl Into<String>) -> Self {
+        Solution {
+            solver: solver.into(),
+            ..self
+        }
+    }
+
+    pub fn with_setting(mut self, key: impl Into<String>, value: impl Display) -> Self {
+        self.settings.push((key.into(), value.to_string()));
+        self
+    }
+
+    pub fn with_ralgo_params(self, ralgo_params: &RalgoParams) -> Self {
+        self.with_setting("alpha", ralgo_params.alpha)
+            .with_setting("q1", ralgo_params.q1)
+            .with_setting("epsx", ralgo_params.epsx)
+            .with_setting("epsg", ralgo_params.epsg)
+            .with_setting("max_iterations", ralgo_params.max_iterations)
+            .with_setting("step_strategy", ralgo_params.step_strategy)
+    }
+
+    /// Description of the pipeline with the seed of its random starts and parameters of its
+    /// r-algorithm stage.
+    pub fn with_pipeline(self, pipeline: &Pipeline) -> Self {
+        let solution = self.with_setting("pipeline", pipeline);
+        let solution = match pipeline.ralgo_params() {
+            Some(ralgo_params) => solution.with_ralgo_params(ralgo_params),
+            None => solution,
+        };
+
+        match pipeline.seed() {
+            Some(seed) => solution.with_seed(seed),
+            None => solution,
+        }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Self {
+        Solution {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    pub fn with_time(self, time: FloatType) -> Self {
+        Solution {
+            time: Some(time),
+            ..self
+        }
+    }
+
+    pub fn with_trace(self, trace: Vec<FloatType>) -> Self {
+        Solution { trace, ..self }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        is_valid_pack(self.main_circle_radius, &self.circles)
+    }
+
+    /// Continue compaction of the stored packing with `dichotomy_step_ralgo`.
+    pub fn warm_start(
+        &self,
+        reset_step: bool,
+        eps: FloatType,
+        ralgo_params: &RalgoParams,
+    ) -> (FloatType, Vec<Circle>) {
+        dichotomy_step_ralgo(
+            self.main_circle_radius,
+            &self.circles,
+            reset_step,
+            eps,
+            ralgo_params,
+        )
+    }
+
+    pub fn to_value(&self) -> Value {
+        let value = |kind| Value::new(kind, 0);
+        let number = |number| value(ValueKind::Number(number));
+        let string = |string: &str| value(ValueKind::String(string.to_string()));
+
+        let mut entries = vec![
+            ("instance".to_string(), string(&self.instance)),
+            ("solver".to_string(), string(&self.solver)),
+            (
+                "main_circle_radius".to_string(),
+                number(self.main_circle_radius),
+            ),
+            (
+                "circles".to_string(),
+                value(ValueKind::Array(
+                    self.circles
+                        .iter()
+                        .map(|c| {
+                            let center = c.center.expect("Valid center");
+                            value(ValueKind::Object(vec![
+                                ("radius".to_string(), number(c.radius)),
+                                ("x".to_string(), number(center.x)),
+                                ("y".to_string(), number(center.y)),
+                            ]))
+                        })
+                        .collect(),
+                )),
+            ),
+            (
+                "settings".to_string(),
+                value(ValueKind::Object(
+                    self.settings
+                        .iter()
+                        .map(|(key, setting)| (key.clone(), string(setting)))
+                        .collect(),
+                )),
+            ),
+        ];
+        if let Some(seed) = self.seed {
+            // as a string, numbers above 2^53 don't survive a double
+            entries.push(("seed".to_string(), string(&seed.to_string())));
+        }
+        if let Some(time) = self.time {
+            entries.push(("time".to_string(), number(time)));
+        }
+        if !self.trace.is_empty() {
+            entries.push((
+                "trace".to_string(),
+                value(ValueKind::Array(self.trace.iter().map(|r| number(*r)).collect())),
+            ));
+        }
+
+        value(ValueKind::Object(entries))
+    }
+
+    /// Solution from the document of `to_value`, only `main_circle_radius` and `circles`
+    /// are required.
+    pub fn from_value(value: &Value) -> Result<Self, ParseError> {
+        value.as_object("solution")?;
+        let optional_string = |key: &str| -> Result<String, ParseError> {
+            Ok(match value.get(key) {
+                Some(string) => string.as_str(key)?.to_string(),
+                None => String::new(),
+            })
+        };
+
+        let circles = required("circles", value, "solution")?
+            .as_array("circles")?
+            .iter()
+            .map(|circle| {
+                let field =
+                    |key: &str| required(key, circle, "circle").and_then(|v| v.as_number(key));
+                Ok(Circle::new(
+                    field("radius")?,
+                    Point {
+                        x: field("x")?,
+                        y: field("y")?,
+                    },
+                ))
+            })
+            .collect::<Result<Vec<Circle>, ParseError>>()?;
+
+        let settings = match value.get("settings") {
+            None => Vec::new(),
+            Some(settings) => settings
+                .as_object("settings")?
+                .iter()
+                .map(|(key, setting)| {
+                    Ok(match &setting.kind {
+                        ValueKind::String(string) => (key.clone(), string.clone()),
+                        ValueKind::Number(number) => (key.clone(), number.to_string()),
+                        ValueKind::Bool(b) => (key.clone(), b.to_string()),
+                        _ => {
+                            return Err(ParseError::new(
+                                setting.line,
+                                format!("Invalid setting '{key}'"),
+                            ))
+                        }
+                    })
+                })
+                .collect::<Result<Vec<(String, String)>, ParseError>>()?,
+        };
+
+        let seed = match value.get("seed") {
+            None => None,
+            Some(seed) => {
+                let string = seed.as_str("seed")?;
+                Some(string.parse().map_err(|_| {
+                    ParseError::new(seed.line, format!("Invalid seed '{string}'"))
+                })?)
+            }
+        };
+
+        Ok(Solution {
+            main_circle_radius: required("main_circle_radius", value, "solution")?
+                .as_number("main_circle_radius")?,
+            circles,
+            instance: optional_string("instance")?,
+            solver: optional_string("solver")?,
+            settings,
+            seed,
+            time: value
+                .get("time")
+                .map(|time| time.as_number("time"))
+                .transpose()?,
+            trace: match value.get("trace") {
+                None => Vec::new(),
+                Some(trace) => trace
+                    .as_array("trace")?
+                    .iter()
+                    .map(|r| r.as_number("trace"))
+                    .collect::<Result<Vec<FloatType>, ParseError>>()?,
+            },
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        to_json(&self.to_value(), 0)
+    }
+
+    pub fn to_packomania(&self) -> String {
+        let mut text = format!("{:?}\n", self.main_circle_radius);
+        for circle in &self.circles {
+            let center = circle.center.expect("Valid center");
+            text += &format!(
+                "{:?} {:?} {:?}\n",
+                circle.radius,
+                center.x / self.main_circle_radius,
+                center.y / self.main_circle_radius
+            );
+        }
+        text
+    }
+
+    pub fn parse(text: &str, format: SolutionFormat) -> Result<Self, ParseError> {
+        match format {
+            SolutionFormat::Json => Solution::from_value(&parse_json(text)?),
+            SolutionFormat::Packomania => {
+                let (main_circle_radius, circles) = parse_packomania(text)?;
+                Ok(Solution::new(main_circle_radius, circles).with_solver("packomania"))
+            }
+        }
+    }
+
+    /// Read solution in the format given by the file extension (`.json` or `.txt`).
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, InstanceError> {
+        let path = path.as_ref();
+        let format = SolutionFormat::from_path(path).ok_or(InstanceError::UnknownFormat {
+            path: path.display().to_string(),
+        })?;
+
+        Solution::read_as(path, format)
+    }
+
+    pub fn read_as(path: impl AsRef<Path>, format: SolutionFormat) -> Result<Self, InstanceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| InstanceError::Io {
+            path: path.display().to_string(),
+            error,
+        })?;
+
+        Solution::parse(&text, format).map_err(|error| InstanceError::Parse {
+            path: path.display().to_string(),
+            error,
+        })
+    }
+
+    /// Write solution in the format given by the file extension, creating missing
+    /// directories.
+    pub fn write(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = SolutionFormat::from_path(path).ok_or(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{}: unknown solution format, expected .json or .txt",
+                path.display()
+            ),
+        ))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(
+            path,
+            match format {
+                SolutionFormat::Json => self.to_json(),
+                SolutionFormat::Packomania => self.to_packomania(),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solution() -> Solution {
+        Solution::new(
+            1.0 + 2.0 / (3.0 as FloatType).sqrt(),
+            vec![
+                Circle::new(1.0, Point { x: 0.1, y: -1.0 / 3.0 }),
+                Circle::new(0.7, Point { x: -1e-17, y: (2.0 as FloatType).sqrt() }),
+            ],
+        )
+        .with_instance("7")
+        .with_solver("pipeline")
+        .with_setting("alpha", 1.5)
+        .with_seed(u64::MAX)
+        .with_time(0.25)
+        .with_trace(vec![3.0, 2.5])
+    }
+
+    fn assert_same_packing(a: &Solution, b: &Solution, eps: FloatType) {
+        assert!((a.main_circle_radius - b.main_circle_radius).abs() <= eps);
+        assert_eq!(a.circles.len(), b.circles.len());
+        for (a, b) in a.circles.iter().zip(&b.circles) {
+            let (center_a, center_b) = (a.center.unwrap(), b.center.unwrap());
+            assert_eq!(a.radius, b.radius);
+            assert!((center_a.x - center_b.x).abs() <= eps, "{center_a:?} {center_b:?}");
+            assert!((center_a.y - center_b.y).abs() <= eps, "{center_a:?} {center_b:?}");
+        }
+    }
+
+    #[test]
+    fn json_round_trip_is_exact() {
+        let json = solution().to_json();
+        let read = Solution::parse(&json, SolutionFormat::Json).unwrap();
+
+        assert_same_packing(&read, &solution(), 0.0);
+        assert_eq!(read.seed, Some(u64::MAX));
+        assert_eq!(read.settings, vec![("alpha".to_string(), "1.5".to_string())]);
+        assert_eq!(read.trace, vec![3.0, 2.5]);
+        assert_eq!(read.to_json(), json);
+    }
+
+    #[test]
+    fn packomania_round_trip_keeps_packing() {
+        let text = solution().to_packomania();
+        let read = Solution::parse(&text, SolutionFormat::Packomania).unwrap();
+
+        assert_same_packing(&read, &solution(), 4.0 * FloatType::EPSILON);
+        assert_eq!(read.to_packomania(), text);
+    }
+
+    #[test]
+    fn seed_must_be_integer_string() {
+        let json = solution().to_json().replace(
+            &format!("\"seed\": \"{}\"", u64::MAX),
+            "\"seed\": 42",
+        );
+        assert!(Solution::parse(&json, SolutionFormat::Json).is_err());
+
+        let json = solution()
+            .to_json()
+            .replace(&format!("\"{}\"", u64::MAX), "\"-1\"");
+        assert!(Solution::parse(&json, SolutionFormat::Json).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use nalgebra::DVector;
+
+use crate::{
+    circle::Circle,
+    ralgo::{
+        ralgo_result::RalgoResult,
+        utils::{circles_to_dvector, dvector_to_answer},
+    },
+    utils::FloatType,
+};
+
+use super::{
+    constraints::{constraints, constraints_number, lower_bounds, max_violation},
+    lbfgsb::{lbfgsb, LbfgsParams},
+    Solver,
+};
+
+/// Augmented Lagrangian method, every subproblem is solved with `lbfgsb`.
+#[derive(Debug)]
+pub struct AugmentedLagrangianSolver {
+    pub initial_penalty: FloatType,
+    pub penalty_growth: FloatType,
+    pub max_outer_iterations: usize,
+    /// Stop when all constraints are violated by less than this value.
+    pub tolerance: FloatType,
+    pub margin: FloatType,
+    pub lbfgs_params: LbfgsParams,
+}
+
+impl Default for AugmentedLagrangianSolver {
+    fn default() -> Self {
+        AugmentedLagrangianSolver {
+            initial_penalty: 10.0,
+            penalty_growth: 10.0,
+            max_outer_iterations: 30,
+            tolerance: 1e-12,
+            margin: 1e-10,
+            lbfgs_params: LbfgsParams::default(),
+        }
+    }
+}
+
+impl AugmentedLagrangianSolver {
+    pub fn with_initial_penalty(&self, initial_penalty: FloatType) -> Self {
+        AugmentedLagrangianSolver {
+            initial_penalty,
+            ..*self
+        }
+    }
+
+    pub fn with_max_outer_iterations(&self, max_outer_iterations: usize) -> Self {
+        AugmentedLagrangianSolver {
+            max_outer_iterations,
+            ..*self
+        }
+    }
+
+    pub fn with_lbfgs_params(&self, lbfgs_params: LbfgsParams) -> Self {
+        AugmentedLagrangianSolver {
+            lbfgs_params,
+            ..*self
+        }
+    }
+}
+
+/// `R + sum((max(0, λ + ρg)^2 - λ^2) / 2ρ)`.
+fn augmented_lagrangian(
+    x: &DVector<FloatType>,
+    radiuses: &DVector<FloatType>,
+    multipliers: &Vec<FloatType>,
+    penalty: FloatType,
+    margin: FloatType,
+) -> (FloatType, DVector<FloatType>) {
+    let mut f = x[x.len() - 1];
+    let mut g = DVector::<FloatType>::zeros(x.len());
+    g[x.len() - 1] = 1.0;
+
+    for (constraint, multiplier) in constraints(x, radiuses, margin).iter().zip(multipliers) {
+        let shifted = (multiplier + penalty * constraint.value).max(0.0);
+        f += (shifted.powi(2) - multiplier.powi(2)) / (2.0 * penalty);
+        if shifted > 0.0 {
+            for (index, value) in constraint.gradient.iter() {
+                g[*index] += shifted * value;
+            }
+        }
+    }
+
+    (f, g)
+}
+
+impl Solver for AugmentedLagrangianSolver {
+    fn name(&self) -> String {
+        "Augmented Lagrangian".to_string()
+    }
+
+    fn solve_canonical(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult {
+        let radiuses = DVector::from_iterator(circles.len(), circles.iter().map(|c| c.radius));
+        let lower = lower_bounds(&radiuses);
+        let mut x = circles_to_dvector(circles, main_circle_radius);
+
+        let mut multipliers = vec![0.0; constraints_number(circles.len())];
+        let mut penalty = self.initial_penalty;
+        let mut previous_violation = FloatType::MAX;
+        let (mut outer_iterations, mut total_iterations, mut total_calcfg_calls) = (0, 0, 0);
+
+        for _ in 0..self.max_outer_iterations {
+            let (iterations, calcfg_calls, y) = lbfgsb(x, &lower, &self.lbfgs_params, |x| {
+                augmented_lagrangian(x, &radiuses, &multipliers, penalty, self.margin)
+            });
+            x = y;
+
+            outer_iterations += 1;
+            total_iterations += iterations;
+            total_calcfg_calls += calcfg_calls;
+
+            let constraints = constraints(&x, &radiuses, self.margin);
+            for (multiplier, constraint) in multipliers.iter_mut().zip(constraints.iter()) {
+                *multiplier = (*multiplier + penalty * constraint.value).max(0.0);
+            }
+
+            let violation = max_violation(&constraints);
+            if violation < self.tolerance {
+                break;
+            }
+            if violation > 0.25 * previous_violation {
+                penalty *= self.penalty_growth;
+            }
+            previous_violation = violation;
+        }
+
+        let (main_circle_radius, circles) = dvector_to_answer(&x, &radiuses);
+
+        RalgoResult::new(
+            outer_iterations,
+            total_iterations,
+            total_calcfg_calls,
+            main_circle_radius,
+            circles,
+        )
+    }
+}
@@ -0,0 +1,76 @@
+use nalgebra::DVector;
+
+use crate::utils::FloatType;
+
+/// Constraint `value <= 0` with sparse gradient by `x = [x_1..x_n, y_1..y_n, R]`.
+pub struct Constraint {
+    pub value: FloatType,
+    pub gradient: Vec<(usize, FloatType)>,
+}
+
+/// Number of constraints for `n` circles: one containment constraint per circle
+/// and one non-overlapping constraint per pair.
+pub fn constraints_number(n: usize) -> usize {
+    n + n * n.saturating_sub(1) / 2
+}
+
+/// Packing constraints in the same squared form as `calcfg` uses, in a stable order:
+/// containment of circle `i`, then non-overlapping of pairs `(i, j)`, `j > i`.
+/// `margin` is added to every value so that solvers stop slightly inside the feasible set.
+pub fn constraints(
+    x: &DVector<FloatType>,
+    radiuses: &DVector<FloatType>,
+    margin: FloatType,
+) -> Vec<Constraint> {
+    let n = radiuses.len();
+    let main_circle_radius = x[2 * n];
+    let mut result = Vec::with_capacity(constraints_number(n));
+
+    for i in 0..n {
+        let (cx, cy) = (x[i], x[n + i]);
+        let free_radius = main_circle_radius - radiuses[i];
+        result.push(Constraint {
+            value: cx.powi(2) + cy.powi(2) - free_radius.powi(2) + margin,
+            gradient: vec![
+                (i, 2.0 * cx),
+                (n + i, 2.0 * cy),
+                (2 * n, -2.0 * free_radius),
+            ],
+        });
+    }
+
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let (dx, dy) = (x[i] - x[j], x[n + i] - x[n + j]);
+            result.push(Constraint {
+                value: (radiuses[i] + radiuses[j]).powi(2) - dx.powi(2) - dy.powi(2) + margin,
+                gradient: vec![
+                    (i, -2.0 * dx),
+                    (n + i, -2.0 * dy),
+                    (j, 2.0 * dx),
+                    (n + j, 2.0 * dy),
+                ],
+            });
+        }
+    }
+
+    result
+}
+
+/// Main circle radius can't be smaller than the biggest circle.
+pub fn lower_bounds(radiuses: &DVector<FloatType>) -> DVector<FloatType> {
+    let n = radiuses.len();
+    let mut lower = DVector::from_element(2 * n + 1, FloatType::NEG_INFINITY);
+    lower[2 * n] = radiuses.max();
+    lower
+}
+
+pub fn project(x: &DVector<FloatType>, lower: &DVector<FloatType>) -> DVector<FloatType> {
+    x.zip_map(lower, |value, bound| value.max(bound))
+}
+
+pub fn max_violation(constraints: &[Constraint]) -> FloatType {
+    constraints
+        .iter()
+        .fold(0.0, |max, constraint| max.max(constraint.value))
+}
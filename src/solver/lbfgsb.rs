@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use nalgebra::DVector;
+
+use crate::utils::FloatType;
+
+use super::constraints::project;
+
+#[derive(Debug, Clone, Copy)]
+pub struct LbfgsParams {
+    /// Number of stored correction pairs.
+    pub memory: usize,
+    pub epsx: FloatType,
+    pub epsg: FloatType,
+    pub max_iterations: usize,
+}
+
+impl Default for LbfgsParams {
+    fn default() -> Self {
+        LbfgsParams {
+            memory: 10,
+            epsx: 1e-12,
+            epsg: 1e-9,
+            max_iterations: 1000,
+        }
+    }
+}
+
+impl LbfgsParams {
+    pub fn with_memory(&self, memory: usize) -> Self {
+        LbfgsParams { memory, ..*self }
+    }
+
+    pub fn with_max_iterations(&self, max_iterations: usize) -> Self {
+        LbfgsParams {
+            max_iterations,
+            ..*self
+        }
+    }
+
+    pub fn with_epsx(&self, epsx: FloatType) -> Self {
+        LbfgsParams { epsx, ..*self }
+    }
+
+    pub fn with_epsg(&self, epsg: FloatType) -> Self {
+        LbfgsParams { epsg, ..*self }
+    }
+}
+
+/// Limited-memory BFGS with lower bounds: variables at the bound with gradient pointing
+/// outside are fixed, the rest follow two-loop recursion direction, and the step is
+/// projected back onto the bounds during Armijo backtracking (or extension when the full
+/// step is accepted right away).
+/// Returns number of iterations, number of `calcfg` calls and the best point.
+pub fn lbfgsb<F>(
+    x: DVector<FloatType>,
+    lower: &DVector<FloatType>,
+    params: &LbfgsParams,
+    calcfg: F,
+) -> (u32, u32, DVector<FloatType>)
+where
+    F: Fn(&DVector<FloatType>) -> (FloatType, DVector<FloatType>),
+{
+    const ARMIJO: FloatType = 1e-4;
+    const MAX_EXTENSIONS: usize = 30;
+
+    let mut x = project(&x, lower);
+    let (mut f, mut g) = calcfg(&x);
+    let mut calcfg_calls = 1_u32;
+    let mut corrections: VecDeque<(DVector<FloatType>, DVector<FloatType>)> = VecDeque::new();
+
+    for iter in 0..params.max_iterations as u32 {
+        let projected_gradient = &x - project(&(&x - &g), lower);
+        if projected_gradient.norm() < params.epsg {
+            return (iter, calcfg_calls, x);
+        }
+
+        let active = DVector::from_iterator(
+            x.len(),
+            (0..x.len()).map(|i| x[i] <= lower[i] && g[i] > 0.0),
+        );
+        let free_gradient = g.zip_map(&active, |gi, is_active| if is_active { 0.0 } else { gi });
+
+        // two-loop recursion
+        let mut q = free_gradient.clone();
+        let mut alphas = Vec::with_capacity(corrections.len());
+        for (s, y) in corrections.iter().rev() {
+            let alpha = s.dot(&q) / y.dot(s);
+            q.axpy(-alpha, y, 1.0);
+            alphas.push(alpha);
+        }
+        if let Some((s, y)) = corrections.back() {
+            q *= s.dot(y) / y.dot(y);
+        }
+        for ((s, y), alpha) in corrections.iter().zip(alphas.iter().rev()) {
+            let beta = y.dot(&q) / y.dot(s);
+            q.axpy(alpha - beta, s, 1.0);
+        }
+
+        let mut direction = -q.zip_map(&active, |qi, is_active| if is_active { 0.0 } else { qi });
+        if direction.dot(&g) >= 0.0 {
+            corrections.clear();
+            direction = -free_gradient;
+        }
+        if corrections.is_empty() {
+            direction /= direction.norm().max(1.0);
+        }
+
+        let mut step: FloatType = 1.0;
+        let (mut new_x, mut new_f, mut new_g);
+        loop {
+            new_x = project(&(&x + step * &direction), lower);
+            (new_f, new_g) = calcfg(&new_x);
+            calcfg_calls += 1;
+
+            if new_f <= f + ARMIJO * g.dot(&(&new_x - &x)) || step < 1e-20 {
+                break;
+            }
+            step /= 2.0;
+        }
+
+        // stale curvature pairs can make the full step too short, so extend it while it helps
+        if step == 1.0 {
+            for _ in 0..MAX_EXTENSIONS {
+                let extended_x = project(&(&x + 2.0 * step * &direction), lower);
+                let (extended_f, extended_g) = calcfg(&extended_x);
+                calcfg_calls += 1;
+
+                if extended_f >= new_f {
+                    break;
+                }
+                step *= 2.0;
+                (new_x, new_f, new_g) = (extended_x, extended_f, extended_g);
+            }
+        }
+
+        let (s, y) = (&new_x - &x, &new_g - &g);
+        if s.norm() < params.epsx || new_f > f {
+            let x = if new_f < f { new_x } else { x };
+            return (iter + 1, calcfg_calls, x);
+        }
+
+        if s.dot(&y) > 1e-12 * y.norm_squared() {
+            if corrections.len() == params.memory {
+                corrections.pop_front();
+            }
+            corrections.push_back((s, y));
+        }
+
+        (x, f, g) = (new_x, new_f, new_g);
+    }
+
+    return (params.max_iterations as u32, calcfg_calls, x);
+}
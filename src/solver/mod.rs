@@ -0,0 +1,29 @@
+use crate::{circle::Circle, ralgo::ralgo_result::RalgoResult, scaling::Scaling, utils::FloatType};
+
+pub mod augmented_lagrangian;
+pub mod constraints;
+pub mod lbfgsb;
+pub mod projected_gradient;
+pub mod ralgo_solver;
+pub mod smoothed_penalty;
+
+/// Local optimizer that minimizes the main circle radius starting from given arrangement.
+///
+/// Telemetry is reported with `RalgoResult`: `ralgo_calls` is the number of outer stages
+/// (dichotomy steps, Lagrange multipliers updates, smoothing levels, step changes),
+/// `iterations` is the total number of inner iterations and `calcfg_calls` is the number
+/// of objective (or projection) evaluations.
+pub trait Solver: Sync {
+    fn name(&self) -> String;
+
+    /// Solve the instance already mapped to the canonical scale, see `Scaling`.
+    fn solve_canonical(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult;
+
+    fn solve(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult {
+        let scaling = Scaling::for_circles(circles);
+        scaling.unscale_result(self.solve_canonical(
+            scaling.scale(main_circle_radius),
+            &scaling.scale_circles(circles),
+        ))
+    }
+}
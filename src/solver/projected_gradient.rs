@@ -0,0 +1,183 @@
+use crate::{
+    circle::Circle, packing::is_valid_pack, point::Point, ralgo::ralgo_result::RalgoResult,
+    utils::FloatType,
+};
+
+use super::Solver;
+
+/// Projected gradient for `min R`: the gradient step only shrinks the main circle (circles
+/// centers are scaled with it), then circles are projected back onto the feasible set with alternating projections onto
+/// single constraints (pushing overlapping pairs apart and pulling circles inside).
+/// Failed projection halves the step, successful one increases it.
+#[derive(Debug)]
+pub struct ProjectedGradientSolver {
+    /// Initial step relative to the main circle radius.
+    pub initial_step: FloatType,
+    pub min_step: FloatType,
+    pub projection_sweeps: usize,
+    pub max_iterations: usize,
+}
+
+impl Default for ProjectedGradientSolver {
+    fn default() -> Self {
+        ProjectedGradientSolver {
+            initial_step: 0.05,
+            min_step: 1e-13,
+            projection_sweeps: 2000,
+            max_iterations: 10_000,
+        }
+    }
+}
+
+impl ProjectedGradientSolver {
+    pub fn with_initial_step(&self, initial_step: FloatType) -> Self {
+        ProjectedGradientSolver {
+            initial_step,
+            ..*self
+        }
+    }
+
+    pub fn with_projection_sweeps(&self, projection_sweeps: usize) -> Self {
+        ProjectedGradientSolver {
+            projection_sweeps,
+            ..*self
+        }
+    }
+
+    pub fn with_max_iterations(&self, max_iterations: usize) -> Self {
+        ProjectedGradientSolver {
+            max_iterations,
+            ..*self
+        }
+    }
+}
+
+/// Returns number of sweeps done and whether the result is a valid packing.
+fn project_circles(
+    main_circle_radius: FloatType,
+    circles: &mut Vec<Circle>,
+    sweeps: usize,
+) -> (u32, bool) {
+    // circles are moved a bit further than needed so that strict validation passes
+    const GAP: FloatType = 1e-12;
+
+    for sweep in 1..=sweeps as u32 {
+        let mut moved = false;
+
+        for i in 0..circles.len() {
+            for j in (i + 1)..circles.len() {
+                let (ci, cj) = (circles[i].center.unwrap(), circles[j].center.unwrap());
+                let (dx, dy) = (ci.x - cj.x, ci.y - cj.y);
+                let distance = (dx.powi(2) + dy.powi(2)).sqrt();
+                let target = (circles[i].radius + circles[j].radius) * (1.0 + GAP);
+
+                if distance < target {
+                    let (ux, uy) = if distance > 0.0 {
+                        (dx / distance, dy / distance)
+                    } else {
+                        (1.0, 0.0)
+                    };
+                    let shift = (target - distance) / 2.0;
+                    circles[i].center = Some(Point {
+                        x: ci.x + shift * ux,
+                        y: ci.y + shift * uy,
+                    });
+                    circles[j].center = Some(Point {
+                        x: cj.x - shift * ux,
+                        y: cj.y - shift * uy,
+                    });
+                    moved = true;
+                }
+            }
+        }
+
+        for circle in circles.iter_mut() {
+            let center = circle.center.unwrap();
+            let distance = (center.x.powi(2) + center.y.powi(2)).sqrt();
+            let target = main_circle_radius * (1.0 - GAP) - circle.radius;
+
+            if distance > target {
+                let scale = target / distance;
+                circle.center = Some(Point {
+                    x: center.x * scale,
+                    y: center.y * scale,
+                });
+                moved = true;
+            }
+        }
+
+        if !moved {
+            return (sweep, is_valid_pack(main_circle_radius, circles));
+        }
+    }
+
+    (sweeps as u32, is_valid_pack(main_circle_radius, circles))
+}
+
+impl Solver for ProjectedGradientSolver {
+    fn name(&self) -> String {
+        "Projected gradient".to_string()
+    }
+
+    fn solve_canonical(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult {
+        let max_circle_radius = circles.iter().fold(0.0, |a: FloatType, c| a.max(c.radius));
+
+        let mut circles = circles.clone();
+        let mut main_circle_radius = main_circle_radius;
+        let (mut step_changes, mut iterations, mut projections) = (0, 0, 0);
+
+        // start from a feasible point
+        let (sweeps, mut is_valid) =
+            project_circles(main_circle_radius, &mut circles, self.projection_sweeps);
+        projections += sweeps;
+        while !is_valid {
+            main_circle_radius *= 1.1;
+            let (sweeps, valid) =
+                project_circles(main_circle_radius, &mut circles, self.projection_sweeps);
+            (projections, is_valid) = (projections + sweeps, valid);
+        }
+
+        let mut step = self.initial_step * main_circle_radius;
+        while step >= self.min_step * main_circle_radius && iterations < self.max_iterations {
+            iterations += 1;
+
+            let new_main_circle_radius = (main_circle_radius - step).max(max_circle_radius);
+            let scale = new_main_circle_radius / main_circle_radius;
+            let mut new_circles: Vec<Circle> = circles
+                .iter()
+                .map(|c| {
+                    let center = c.center.unwrap();
+                    Circle::new(
+                        c.radius,
+                        Point {
+                            x: center.x * scale,
+                            y: center.y * scale,
+                        },
+                    )
+                })
+                .collect();
+            let (sweeps, is_valid) = project_circles(
+                new_main_circle_radius,
+                &mut new_circles,
+                self.projection_sweeps,
+            );
+            projections += sweeps;
+
+            if is_valid && new_main_circle_radius < main_circle_radius {
+                (main_circle_radius, circles) = (new_main_circle_radius, new_circles);
+                step *= 1.5;
+            } else {
+                step /= 2.0;
+                step_changes += 1;
+            }
+        }
+
+        RalgoResult::new(
+            step_changes,
+            iterations as u32,
+            projections,
+            main_circle_radius,
+            circles,
+        )
+    }
+}
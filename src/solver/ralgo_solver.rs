@@ -0,0 +1,48 @@
+use crate::{
+    circle::Circle,
+    ralgo::{
+        dichotomy_step_ralgo::dichotomy_step_ralgo_result_with_iterations,
+        ralgo_params::RalgoParams, ralgo_result::RalgoResult,
+    },
+    utils::FloatType,
+};
+
+use super::Solver;
+
+/// Shor's r-algorithm with dichotomy over step sizes.
+#[derive(Debug)]
+pub struct RalgoSolver {
+    pub reset_step: bool,
+    pub eps: FloatType,
+    pub ralgo_params: RalgoParams,
+}
+
+impl RalgoSolver {
+    pub fn new(reset_step: bool, eps: FloatType, ralgo_params: RalgoParams) -> Self {
+        RalgoSolver {
+            reset_step,
+            eps,
+            ralgo_params,
+        }
+    }
+}
+
+impl Solver for RalgoSolver {
+    fn name(&self) -> String {
+        let reset_str = if self.reset_step { "P" } else { "B" };
+        format!(
+            "ralgo {} EPS={} step: {}",
+            reset_str, self.eps, self.ralgo_params.step_strategy
+        )
+    }
+
+    fn solve_canonical(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult {
+        dichotomy_step_ralgo_result_with_iterations(
+            main_circle_radius,
+            circles,
+            self.reset_step,
+            self.eps,
+            &self.ralgo_params,
+        )
+    }
+}
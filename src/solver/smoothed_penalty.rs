@@ -0,0 +1,161 @@
+use nalgebra::DVector;
+
+use crate::{
+    circle::Circle,
+    ralgo::{
+        ralgo_result::RalgoResult,
+        utils::{circles_to_dvector, dvector_to_answer},
+    },
+    utils::FloatType,
+};
+
+use super::{
+    constraints::{constraints, lower_bounds, project},
+    Solver,
+};
+
+/// Gradient descent on `R + P * sum(τ * ln(1 + exp(g / τ)))`, a smooth version of the exact
+/// penalty `calcfg` uses. Smoothing `τ` is decreased after every stage.
+#[derive(Debug)]
+pub struct SmoothedPenaltySolver {
+    pub penalty: FloatType,
+    pub initial_smoothing: FloatType,
+    pub smoothing_decrease: FloatType,
+    pub stages: usize,
+    pub max_iterations: usize,
+    pub epsx: FloatType,
+    pub margin: FloatType,
+}
+
+impl Default for SmoothedPenaltySolver {
+    fn default() -> Self {
+        SmoothedPenaltySolver {
+            penalty: 2000.0,
+            initial_smoothing: 10.0,
+            smoothing_decrease: 0.1,
+            stages: 8,
+            max_iterations: 3000,
+            epsx: 1e-14,
+            margin: 1e-10,
+        }
+    }
+}
+
+impl SmoothedPenaltySolver {
+    pub fn with_penalty(&self, penalty: FloatType) -> Self {
+        SmoothedPenaltySolver { penalty, ..*self }
+    }
+
+    pub fn with_stages(&self, stages: usize) -> Self {
+        SmoothedPenaltySolver { stages, ..*self }
+    }
+
+    pub fn with_max_iterations(&self, max_iterations: usize) -> Self {
+        SmoothedPenaltySolver {
+            max_iterations,
+            ..*self
+        }
+    }
+}
+
+/// Numerically stable `τ * ln(1 + exp(t / τ))` and its derivative.
+fn softplus(t: FloatType, smoothing: FloatType) -> (FloatType, FloatType) {
+    let z = t / smoothing;
+    if z > 0.0 {
+        (t + smoothing * (-z).exp().ln_1p(), 1.0 / (1.0 + (-z).exp()))
+    } else {
+        (smoothing * z.exp().ln_1p(), z.exp() / (1.0 + z.exp()))
+    }
+}
+
+fn smoothed_penalty(
+    x: &DVector<FloatType>,
+    radiuses: &DVector<FloatType>,
+    penalty: FloatType,
+    smoothing: FloatType,
+    margin: FloatType,
+) -> (FloatType, DVector<FloatType>) {
+    let mut f = x[x.len() - 1];
+    let mut g = DVector::<FloatType>::zeros(x.len());
+    g[x.len() - 1] = 1.0;
+
+    for constraint in constraints(x, radiuses, margin) {
+        let (value, derivative) = softplus(constraint.value, smoothing);
+        f += penalty * value;
+        for (index, gradient) in constraint.gradient {
+            g[index] += penalty * derivative * gradient;
+        }
+    }
+
+    (f, g)
+}
+
+impl Solver for SmoothedPenaltySolver {
+    fn name(&self) -> String {
+        "Smoothed penalty".to_string()
+    }
+
+    fn solve_canonical(&self, main_circle_radius: FloatType, circles: &Vec<Circle>) -> RalgoResult {
+        const ARMIJO: FloatType = 1e-4;
+
+        let radiuses = DVector::from_iterator(circles.len(), circles.iter().map(|c| c.radius));
+        let lower = lower_bounds(&radiuses);
+        let mut x = project(&circles_to_dvector(circles, main_circle_radius), &lower);
+
+        let mut smoothing = self.initial_smoothing;
+        let (mut stages, mut total_iterations, mut total_calcfg_calls) = (0, 0, 0);
+
+        for _ in 0..self.stages {
+            let calcfg = |x: &DVector<FloatType>| {
+                smoothed_penalty(x, &radiuses, self.penalty, smoothing, self.margin)
+            };
+
+            let (mut f, mut g) = calcfg(&x);
+            total_calcfg_calls += 1;
+            let mut step: FloatType = 1.0 / g.norm();
+
+            for _ in 0..self.max_iterations {
+                total_iterations += 1;
+
+                // Barzilai-Borwein step with backtracking
+                let (mut new_x, mut new_f, mut new_g);
+                loop {
+                    new_x = project(&(&x - step * &g), &lower);
+                    (new_f, new_g) = calcfg(&new_x);
+                    total_calcfg_calls += 1;
+
+                    if new_f <= f - ARMIJO * g.dot(&(&x - &new_x)) || step < 1e-30 {
+                        break;
+                    }
+                    step /= 2.0;
+                }
+
+                let (s, y) = (&new_x - &x, &new_g - &g);
+                (x, f, g) = (new_x, new_f, new_g);
+
+                if s.norm() < self.epsx {
+                    break;
+                }
+                let sy = s.dot(&y);
+                step = if sy > 0.0 {
+                    s.norm_squared() / sy
+                } else {
+                    step * 2.0
+                };
+            }
+
+            stages += 1;
+            smoothing *= self.smoothing_decrease;
+        }
+
+        let (main_circle_radius, circles) = dvector_to_answer(&x, &radiuses);
+
+        RalgoResult::new(
+            stages,
+            total_iterations,
+            total_calcfg_calls,
+            main_circle_radius,
+            circles,
+        )
+    }
+}
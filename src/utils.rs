@@ -1,5 +1,11 @@
 use std::time::Instant;
 
+/// Number type of the heuristic packing, random starts, evaluate drivers, instance and
+/// solution files and plots. Generic number types are limited to the r-algorithm (`ralgo`,
+/// `calcfg`, `calcfg_symmetric`, `dichotomy_step_ralgo`) over `T: RealField + Copy`, e.g.
+/// for cheap `f32` screening, and to geometry, `is_valid_pack` and polishing over
+/// `real::Real`. The packing heuristic (`find_answer`, `pack_circles`) isn't generic, the
+/// rest converts to and from this type at those boundaries.
 pub type FloatType = f64;
 
 pub fn measure_time<F, T>(function: F) -> (FloatType, T)
@@ -0,0 +1,178 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use rust_xlsxwriter::Workbook;
+
+use crate::instance::{
+    toml::parse_toml,
+    value::{ParseError, Value},
+    InstanceError,
+};
+
+/// Config file of the workspace, looked up in the working directory.
+pub const WORKSPACE_FILE: &str = "workspace.toml";
+
+/// Locations of instances, answers and results. Every key of `workspace.toml` is optional,
+/// relative paths are relative to the working directory:
+///
+/// ```toml
+/// input = "./input"            # inputNNN.txt
+/// output = "./output"          # outNNN.txt, jury answers
+/// packomania = "./packomania"  # N.txt
+/// results = "./results"        # batch tables and the 4 directories below by default
+/// random_results = "./results/random"
+/// heuristic_results = "./results/heuristic"
+/// memetic_results = "./results/memetic"
+/// solutions = "./results/solutions"
+/// plot = "circle.png"
+/// bins_plot = "bins.png"
+/// ellipse_plot = "ellipse.png"
+/// ```
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub packomania: PathBuf,
+    /// Root of results, e.g. tables of `batch`.
+    pub results: PathBuf,
+    pub random_results: PathBuf,
+    pub heuristic_results: PathBuf,
+    pub memetic_results: PathBuf,
+    pub solutions: PathBuf,
+    /// Default path of `PlotParams`.
+    pub plot: PathBuf,
+    /// Path of `draw_bins_plot`.
+    pub bins_plot: PathBuf,
+    /// Path of `draw_ellipses_plot`.
+    pub ellipse_plot: PathBuf,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Workspace::with_results(Path::new("./results"))
+    }
+}
+
+impl Workspace {
+    fn with_results(results: &Path) -> Self {
+        Workspace {
+            input: PathBuf::from("./input"),
+            output: PathBuf::from("./output"),
+            packomania: PathBuf::from("./packomania"),
+            results: results.to_path_buf(),
+            random_results: results.join("random"),
+            heuristic_results: results.join("heuristic"),
+            memetic_results: results.join("memetic"),
+            solutions: results.join("solutions"),
+            plot: PathBuf::from("circle.png"),
+            bins_plot: PathBuf::from("bins.png"),
+            ellipse_plot: PathBuf::from("ellipse.png"),
+        }
+    }
+
+    pub fn from_value(value: &Value) -> Result<Self, ParseError> {
+        let entries = value.as_object("workspace")?;
+
+        let results = match value.get("results") {
+            Some(results) => PathBuf::from(results.as_str("results")?),
+            None => PathBuf::from("./results"),
+        };
+        let mut workspace = Workspace::with_results(&results);
+
+        for (key, value) in entries {
+            let path = PathBuf::from(value.as_str(key)?);
+            match key.as_str() {
+                "input" => workspace.input = path,
+                "output" => workspace.output = path,
+                "packomania" => workspace.packomania = path,
+                "results" => {}
+                "random_results" => workspace.random_results = path,
+                "heuristic_results" => workspace.heuristic_results = path,
+                "memetic_results" => workspace.memetic_results = path,
+                "solutions" => workspace.solutions = path,
+                "plot" => workspace.plot = path,
+                "bins_plot" => workspace.bins_plot = path,
+                "ellipse_plot" => workspace.ellipse_plot = path,
+                _ => {
+                    return Err(ParseError::new(
+                        value.line,
+                        format!("Unknown key '{key}' in workspace"),
+                    ))
+                }
+            }
+        }
+
+        Ok(workspace)
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, InstanceError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|error| InstanceError::Io {
+            path: path.display().to_string(),
+            error,
+        })?;
+
+        parse_toml(&text)
+            .and_then(|value| Workspace::from_value(&value))
+            .map_err(|error| InstanceError::Parse {
+                path: path.display().to_string(),
+                error,
+            })
+    }
+
+    /// `inputNNN.txt` of the test.
+    pub fn input_file(&self, test_number: u32) -> PathBuf {
+        self.input.join(format!("input{test_number:03}.txt"))
+    }
+
+    /// `outNNN.txt` with jury answer of the test.
+    pub fn answer_file(&self, test_number: u32) -> PathBuf {
+        self.output.join(format!("out{test_number:03}.txt"))
+    }
+
+    /// `N.txt` with the best known packing of `N` circles.
+    pub fn packomania_file(&self, test_number: u32) -> PathBuf {
+        self.packomania.join(format!("{test_number}.txt"))
+    }
+}
+
+static WORKSPACE: OnceLock<Workspace> = OnceLock::new();
+
+/// Workspace of `workspace.toml` when there is one, default locations otherwise. Read once,
+/// the error has file and line of malformed config. `cli::run` calls it before anything
+/// else, so `workspace()` doesn't fail later.
+pub fn load_workspace() -> Result<&'static Workspace, InstanceError> {
+    if let Some(workspace) = WORKSPACE.get() {
+        return Ok(workspace);
+    }
+
+    let workspace = if Path::new(WORKSPACE_FILE).exists() {
+        Workspace::read(WORKSPACE_FILE)?
+    } else {
+        Workspace::default()
+    };
+    Ok(WORKSPACE.get_or_init(|| workspace))
+}
+
+/// Workspace of `load_workspace`, panics with file and line of malformed config when it
+/// wasn't loaded before.
+pub fn workspace() -> &'static Workspace {
+    load_workspace().unwrap_or_else(|error| panic!("{error}"))
+}
+
+/// Save workbook creating missing directories, errors name the file.
+pub fn save_workbook(workbook: &mut Workbook, path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| {
+            io::Error::new(error.kind(), format!("{}: {error}", parent.display()))
+        })?;
+    }
+
+    workbook
+        .save(path)
+        .map_err(|error| io::Error::other(format!("{}: {error}", path.display())))
+}